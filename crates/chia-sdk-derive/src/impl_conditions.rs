@@ -7,7 +7,7 @@ use syn::{
     parse_macro_input,
     punctuated::{Pair, Punctuated},
     visit_mut::VisitMut,
-    Expr, Ident, Token, Type,
+    Attribute, Expr, Ident, Token, Type,
 };
 
 #[derive(Debug, Clone)]
@@ -32,6 +32,7 @@ struct AdditionalDerives {
 
 #[derive(Debug, Clone)]
 struct ConditionField {
+    attrs: Vec<Attribute>,
     modifier: Modifier,
     name: Ident,
     ty: Type,
@@ -125,6 +126,7 @@ impl Parse for AdditionalDerives {
 
 impl Parse for ConditionField {
     fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
         let mut modifier = Modifier::None;
 
         if input.peek(Token![...]) {
@@ -151,6 +153,7 @@ impl Parse for ConditionField {
         };
 
         Ok(Self {
+            attrs,
             modifier,
             name,
             ty,
@@ -194,6 +197,8 @@ pub(crate) fn impl_conditions(input: TokenStream) -> TokenStream {
     let mut variants = Vec::new();
     let mut structs = Vec::new();
     let mut main_impls = Vec::new();
+    let mut serializers = Vec::new();
+    let mut deserializers = Vec::new();
 
     for Condition {
         name: condition,
@@ -233,33 +238,93 @@ pub(crate) fn impl_conditions(input: TokenStream) -> TokenStream {
             }
         });
 
+        let opcode = fields.iter().find_map(|field| {
+            if field.name == "opcode" {
+                field.constant.clone()
+            } else {
+                None
+            }
+        });
+
+        let Some(opcode) = opcode else {
+            return syn::Error::new(condition.span(), "condition is missing a constant opcode")
+                .to_compile_error()
+                .into();
+        };
+
         let definitions = fields.clone().into_iter().map(|field| {
             let ConditionField {
+                attrs,
                 modifier,
                 name,
                 ty,
                 constant,
             } = field;
 
+            let attrs = attrs.into_iter().map(|attr| {
+                if attr.path().is_ident("serde") {
+                    let meta = attr.meta;
+                    quote!( #[cfg_attr(feature = "serde", #meta)] )
+                } else {
+                    quote!( #attr )
+                }
+            });
+
             match modifier {
                 Modifier::None if constant.is_some() => quote! {
                     #[clvm(constant = #constant)]
                     pub #name: #ty,
                 },
                 Modifier::None => quote! {
+                    #( #attrs )*
+                    #[cfg_attr(feature = "serde", serde(with = "crate::condition::json"))]
                     pub #name: #ty,
                 },
                 Modifier::Spread => quote! {
+                    #( #attrs )*
+                    #[cfg_attr(feature = "serde", serde(with = "crate::condition::json"))]
                     #[clvm(rest)]
                     pub #name: #ty,
                 },
                 Modifier::Optional => quote! {
+                    #( #attrs )*
+                    #[cfg_attr(feature = "serde", serde(with = "crate::condition::json", default))]
                     #[clvm(default)]
                     pub #name: #ty,
                 },
             }
         });
 
+        let serde_bounds = generic_list.as_ref().map(|idents| {
+            let bounds = idents
+                .iter()
+                .map(|ident| format!("{ident}: crate::ConditionArg"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            quote!( #[cfg_attr(feature = "serde", serde(bound = #bounds))] )
+        });
+
+        let field_count = fields
+            .iter()
+            .filter(|field| field.constant.is_none())
+            .count();
+
+        deserializers.push((
+            field_count,
+            quote! {
+                if raw.is_opcode(#opcode) {
+                    match raw.args::<#condition #generics_remapped>() {
+                        Ok(inner) => return Ok(Self::#condition(inner)),
+                        Err(err) => error = Some(err),
+                    }
+                }
+            },
+        ));
+
+        serializers.push(quote! {
+            Self::#condition(inner) => crate::condition::json::serialize_condition(serializer, #opcode, inner),
+        });
+
         let mut parameters_original = Vec::new();
         let mut parameters_remapped = Vec::new();
 
@@ -300,6 +365,8 @@ pub(crate) fn impl_conditions(input: TokenStream) -> TokenStream {
             #[::clvm_traits::apply_constants]
             #[derive(Debug, Clone, PartialEq, Eq)]
             #additional_derives
+            #[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+            #serde_bounds
             #[clvm(list)]
             pub struct #condition #generics_original {
                 #( #definitions )*
@@ -377,6 +444,11 @@ pub(crate) fn impl_conditions(input: TokenStream) -> TokenStream {
         });
     }
 
+    // Conditions that share an opcode are tried with the most specific one first, since
+    // extra arguments in the JSON representation are ignored.
+    deserializers.sort_by(|a, b| b.0.cmp(&a.0));
+    let deserializers = deserializers.into_iter().map(|(_, tokens)| tokens);
+
     quote! {
         #[non_exhaustive]
         #[derive(Debug, Clone, PartialEq, Eq, ::clvm_traits::ToClvm, ::clvm_traits::FromClvm)]
@@ -391,6 +463,47 @@ pub(crate) fn impl_conditions(input: TokenStream) -> TokenStream {
         }
 
         #( #structs )*
+
+        #[cfg(feature = "serde")]
+        impl<#enum_generic> ::serde::Serialize for #name<#enum_generic>
+        where
+            #enum_generic: crate::ConditionArg,
+        {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: ::serde::Serializer,
+            {
+                match self {
+                    #( #serializers )*
+                    Self::Other(inner) => crate::condition::json::serialize_other(serializer, inner),
+                }
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl<'de, #enum_generic> ::serde::Deserialize<'de> for #name<#enum_generic>
+        where
+            #enum_generic: crate::ConditionArg,
+        {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: ::serde::Deserializer<'de>,
+            {
+                use ::serde::de::Error;
+
+                let raw: crate::condition::json::RawCondition =
+                    ::serde::Deserialize::deserialize(deserializer)?;
+                let mut error = None;
+
+                #( #deserializers )*
+
+                if let Some(error) = error {
+                    return Err(D::Error::custom(error));
+                }
+
+                raw.into_other().map(Self::Other).map_err(D::Error::custom)
+            }
+        }
     }
     .into()
 }
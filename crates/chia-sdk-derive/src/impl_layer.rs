@@ -0,0 +1,230 @@
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{
+    parse_macro_input, parse_quote, spanned::Spanned, Data, DeriveInput, Expr, Fields, Path,
+    PathArguments, Type,
+};
+
+#[derive(Default)]
+struct LayerAttrs {
+    krate: Option<Path>,
+    puzzle: Option<Expr>,
+    hash: Option<Expr>,
+    args: Option<Type>,
+    solution: Option<Type>,
+}
+
+impl LayerAttrs {
+    fn parse(input: &DeriveInput) -> syn::Result<Self> {
+        let mut attrs = Self::default();
+
+        for attr in &input.attrs {
+            if !attr.path().is_ident("layer") {
+                continue;
+            }
+
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("crate") {
+                    attrs.krate = Some(meta.value()?.call(Path::parse_mod_style)?);
+                } else if meta.path.is_ident("puzzle") {
+                    attrs.puzzle = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("hash") {
+                    attrs.hash = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("args") {
+                    attrs.args = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("solution") {
+                    attrs.solution = Some(meta.value()?.parse()?);
+                } else {
+                    return Err(meta.error("unknown layer attribute"));
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(attrs)
+    }
+}
+
+pub(crate) fn impl_layer(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_layer(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand_layer(mut input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let attrs = LayerAttrs::parse(&input)?;
+
+    let missing = |name: &str| {
+        syn::Error::new(
+            Span::call_site(),
+            format!("missing `#[layer({name} = ...)]` attribute"),
+        )
+    };
+
+    let krate = attrs
+        .krate
+        .unwrap_or_else(|| parse_quote!(::chia_sdk_driver));
+    let puzzle = attrs.puzzle.ok_or_else(|| missing("puzzle"))?;
+    let hash = attrs.hash.ok_or_else(|| missing("hash"))?;
+    let args_ty = attrs.args.ok_or_else(|| missing("args"))?;
+    let solution_ty = attrs
+        .solution
+        .unwrap_or_else(|| parse_quote!(#krate::__private::NodePtr));
+
+    // The args type may have generic arguments, which aren't allowed in a struct expression.
+    let Type::Path(args_path) = &args_ty else {
+        return Err(syn::Error::new(
+            args_ty.span(),
+            "expected a path to the args struct",
+        ));
+    };
+    let mut args_path = args_path.path.clone();
+    if let Some(segment) = args_path.segments.last_mut() {
+        segment.arguments = PathArguments::None;
+    }
+
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "`Layer` can only be derived for structs",
+        ));
+    };
+
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "`Layer` can only be derived for structs with named fields",
+        ));
+    };
+
+    let mut inner = None;
+    let mut field_names = Vec::new();
+
+    for field in &fields.named {
+        let mut is_inner = false;
+
+        for attr in &field.attrs {
+            if !attr.path().is_ident("layer") {
+                continue;
+            }
+
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("inner") {
+                    is_inner = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown layer field attribute"))
+                }
+            })?;
+        }
+
+        let name = field.ident.clone().expect("named field");
+
+        if !is_inner {
+            field_names.push(name);
+        } else if inner.is_some() {
+            return Err(syn::Error::new(
+                field.span(),
+                "only one field can be the inner puzzle",
+            ));
+        } else {
+            inner = Some((name, field.ty.clone()));
+        }
+    }
+
+    let (parse_inner, construct_inner, inner_field) = if let Some((name, ty)) = &inner {
+        input
+            .generics
+            .make_where_clause()
+            .predicates
+            .push(parse_quote!(#ty: #krate::Layer));
+
+        (
+            quote! {
+                let Some(#name) = <#ty as #krate::Layer>::parse_puzzle(
+                    allocator,
+                    #krate::Puzzle::parse(allocator, args.#name),
+                )? else {
+                    return Ok(None);
+                };
+            },
+            quote! {
+                let #name = #krate::Layer::construct_puzzle(&self.#name, ctx)?;
+            },
+            quote!(#name,),
+        )
+    } else {
+        (quote!(), quote!(), quote!())
+    };
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics #krate::Layer for #ident #ty_generics #where_clause {
+            type Solution = #solution_ty;
+
+            fn parse_puzzle(
+                allocator: &#krate::__private::Allocator,
+                puzzle: #krate::Puzzle,
+            ) -> Result<Option<Self>, #krate::DriverError> {
+                let Some(puzzle) = puzzle.as_curried() else {
+                    return Ok(None);
+                };
+
+                if puzzle.mod_hash != #hash {
+                    return Ok(None);
+                }
+
+                let args = <#args_ty as #krate::__private::FromClvm<
+                    #krate::__private::Allocator,
+                >>::from_clvm(allocator, puzzle.args)?;
+
+                #parse_inner
+
+                Ok(Some(Self {
+                    #( #field_names: args.#field_names, )*
+                    #inner_field
+                }))
+            }
+
+            fn parse_solution(
+                allocator: &#krate::__private::Allocator,
+                solution: #krate::__private::NodePtr,
+            ) -> Result<Self::Solution, #krate::DriverError> {
+                Ok(<#solution_ty as #krate::__private::FromClvm<
+                    #krate::__private::Allocator,
+                >>::from_clvm(allocator, solution)?)
+            }
+
+            fn construct_puzzle(
+                &self,
+                ctx: &mut #krate::SpendContext,
+            ) -> Result<#krate::__private::NodePtr, #krate::DriverError> {
+                #construct_inner
+
+                let args: #args_ty = #args_path {
+                    #( #field_names: ::core::clone::Clone::clone(&self.#field_names), )*
+                    #inner_field
+                };
+
+                let curried = #krate::__private::CurriedProgram {
+                    program: ctx.puzzle(#hash, &#puzzle)?,
+                    args,
+                };
+                ctx.alloc(&curried)
+            }
+
+            fn construct_solution(
+                &self,
+                ctx: &mut #krate::SpendContext,
+                solution: Self::Solution,
+            ) -> Result<#krate::__private::NodePtr, #krate::DriverError> {
+                ctx.alloc(&solution)
+            }
+        }
+    })
+}
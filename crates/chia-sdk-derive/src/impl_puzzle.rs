@@ -0,0 +1,195 @@
+use std::path::PathBuf;
+
+use clvm_utils::tree_hash_from_bytes;
+use convert_case::{Case, Casing};
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::{format_ident, quote};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+    punctuated::Punctuated,
+    Ident, LitByteStr, LitStr, Path, Token,
+};
+
+struct LoadPuzzle {
+    krate: Option<Path>,
+    name: Ident,
+    path: LitStr,
+    hash: LitStr,
+    accessor: Ident,
+    ext: Option<Ident>,
+}
+
+enum PuzzleOption {
+    Crate(Path),
+    Name(Ident),
+    Path(LitStr),
+    Hash(LitStr),
+    Accessor(Ident),
+    Ext(Ident),
+}
+
+impl Parse for PuzzleOption {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        if input.peek(Token![crate]) {
+            input.parse::<Token![crate]>()?;
+            input.parse::<Token![=]>()?;
+            return Ok(Self::Crate(input.call(Path::parse_mod_style)?));
+        }
+
+        let key: Ident = input.parse()?;
+        input.parse::<Token![=]>()?;
+
+        Ok(match key.to_string().as_str() {
+            "name" => Self::Name(input.parse()?),
+            "path" => Self::Path(input.parse()?),
+            "hash" => Self::Hash(input.parse()?),
+            "accessor" => Self::Accessor(input.parse()?),
+            "ext" => Self::Ext(input.parse()?),
+            _ => return Err(syn::Error::new(key.span(), "unknown puzzle option")),
+        })
+    }
+}
+
+impl Parse for LoadPuzzle {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let options = Punctuated::<PuzzleOption, Token![,]>::parse_terminated(input)?;
+
+        let mut krate = None;
+        let mut name = None;
+        let mut path = None;
+        let mut hash = None;
+        let mut accessor = None;
+        let mut ext = None;
+
+        for option in options {
+            match option {
+                PuzzleOption::Crate(value) => krate = Some(value),
+                PuzzleOption::Name(value) => name = Some(value),
+                PuzzleOption::Path(value) => path = Some(value),
+                PuzzleOption::Hash(value) => hash = Some(value),
+                PuzzleOption::Accessor(value) => accessor = Some(value),
+                PuzzleOption::Ext(value) => ext = Some(value),
+            }
+        }
+
+        let missing =
+            |key: &str| syn::Error::new(Span::call_site(), format!("missing `{key} = ...` option"));
+
+        Ok(Self {
+            krate,
+            name: name.ok_or_else(|| missing("name"))?,
+            path: path.ok_or_else(|| missing("path"))?,
+            hash: hash.ok_or_else(|| missing("hash"))?,
+            accessor: accessor.ok_or_else(|| missing("accessor"))?,
+            ext,
+        })
+    }
+}
+
+pub(crate) fn impl_load_puzzle(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as LoadPuzzle);
+    expand_load_puzzle(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand_load_puzzle(input: LoadPuzzle) -> syn::Result<proc_macro2::TokenStream> {
+    let krate = input
+        .krate
+        .unwrap_or_else(|| syn::parse_quote!(::chia_sdk_driver));
+
+    // Paths are relative to the manifest directory of the crate that invokes the macro.
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let full_path = PathBuf::from(manifest_dir).join(input.path.value());
+
+    let contents = std::fs::read_to_string(&full_path).map_err(|error| {
+        syn::Error::new(
+            input.path.span(),
+            format!("couldn't read `{}`: {error}", full_path.display()),
+        )
+    })?;
+
+    let hex_string: String = contents.split_whitespace().collect();
+    let bytes = hex::decode(hex_string)
+        .map_err(|error| syn::Error::new(input.path.span(), format!("invalid hex: {error}")))?;
+
+    let computed_hash = tree_hash_from_bytes(&bytes).map_err(|error| {
+        syn::Error::new(
+            input.path.span(),
+            format!("invalid serialized puzzle: {error}"),
+        )
+    })?;
+
+    let expected_hash = hex::decode(input.hash.value())
+        .ok()
+        .and_then(|hash| <[u8; 32]>::try_from(hash).ok())
+        .ok_or_else(|| syn::Error::new(input.hash.span(), "expected a 32 byte hex string"))?;
+
+    if computed_hash.to_bytes() != expected_hash {
+        return Err(syn::Error::new(
+            input.hash.span(),
+            format!(
+                "puzzle hash mismatch, expected {} but the puzzle hashes to {}",
+                input.hash.value(),
+                hex::encode(computed_hash.to_bytes())
+            ),
+        ));
+    }
+
+    let name = &input.name;
+    let hash_name = format_ident!("{}_HASH", name);
+    let accessor = &input.accessor;
+    let len = bytes.len();
+    let bytes = LitByteStr::new(&bytes, input.path.span());
+    let hash_bytes = LitByteStr::new(&expected_hash, input.hash.span());
+    let full_path = full_path.display().to_string();
+
+    let accessor_doc = format!(
+        "Allocate the {} and return its pointer.",
+        name.to_string().to_case(Case::Lower)
+    );
+
+    let accessor_impl = if let Some(ext) = &input.ext {
+        quote! {
+            pub trait #ext {
+                #[doc = #accessor_doc]
+                fn #accessor(
+                    &mut self,
+                ) -> Result<#krate::__private::NodePtr, #krate::DriverError>;
+            }
+
+            impl #ext for #krate::SpendContext {
+                fn #accessor(
+                    &mut self,
+                ) -> Result<#krate::__private::NodePtr, #krate::DriverError> {
+                    self.puzzle(#hash_name, &#name)
+                }
+            }
+        }
+    } else {
+        quote! {
+            impl #krate::SpendContext {
+                #[doc = #accessor_doc]
+                pub fn #accessor(
+                    &mut self,
+                ) -> Result<#krate::__private::NodePtr, #krate::DriverError> {
+                    self.puzzle(#hash_name, &#name)
+                }
+            }
+        }
+    };
+
+    Ok(quote! {
+        pub const #name: [u8; #len] = *#bytes;
+
+        pub const #hash_name: #krate::__private::TreeHash =
+            #krate::__private::TreeHash::new(*#hash_bytes);
+
+        // Rebuilds the crate when the puzzle file changes.
+        const _: &[u8] = include_bytes!(#full_path);
+
+        #accessor_impl
+    })
+}
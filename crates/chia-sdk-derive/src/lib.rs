@@ -1,10 +1,49 @@
 use proc_macro::TokenStream;
 
 mod impl_conditions;
+mod impl_layer;
+mod impl_puzzle;
 
 use impl_conditions::impl_conditions;
+use impl_layer::impl_layer;
+use impl_puzzle::impl_load_puzzle;
 
 #[proc_macro]
 pub fn conditions(input: TokenStream) -> TokenStream {
     impl_conditions(input)
 }
+
+/// Implements `Layer` for a struct that represents a curried puzzle.
+///
+/// The struct's fields are copied to and from the curried args struct by name.
+/// A single field can be marked with `#[layer(inner)]`, in which case it's parsed as an inner layer
+/// and its args field must be a `NodePtr`.
+///
+/// The following attributes are supported on the struct:
+/// - `puzzle`: the serialized puzzle bytes.
+/// - `hash`: the mod hash of the puzzle.
+/// - `args`: the curried args struct.
+/// - `solution`: the solution type, which defaults to `NodePtr`.
+/// - `crate`: the path to `chia_sdk_driver`, if it's re-exported elsewhere.
+#[proc_macro_derive(Layer, attributes(layer))]
+pub fn layer(input: TokenStream) -> TokenStream {
+    impl_layer(input)
+}
+
+/// Loads a serialized puzzle from a `.clsp.hex` file at compile time.
+///
+/// This emits a `name` constant with the puzzle bytes, a `name_HASH` constant with its tree hash,
+/// and an `accessor` method on `SpendContext` which allocates the puzzle.
+/// The build fails if the tree hash of the puzzle doesn't match the expected `hash`.
+///
+/// The following options are supported:
+/// - `name`: the name of the puzzle constant.
+/// - `path`: the path to the hex file, relative to the crate's manifest directory.
+/// - `hash`: the expected tree hash of the puzzle, as a hex string.
+/// - `accessor`: the name of the `SpendContext` method.
+/// - `ext`: the name of an extension trait to put the accessor in, which is required outside of `chia_sdk_driver`.
+/// - `crate`: the path to `chia_sdk_driver`, if it's re-exported elsewhere.
+#[proc_macro]
+pub fn load_puzzle(input: TokenStream) -> TokenStream {
+    impl_load_puzzle(input)
+}
@@ -0,0 +1,525 @@
+use std::collections::HashSet;
+
+use chia_protocol::{Bytes32, Coin, CoinSpend};
+use chia_puzzles::{
+    did::DID_INNER_PUZZLE_HASH,
+    nft::{NFT_OWNERSHIP_LAYER_PUZZLE_HASH, NFT_STATE_LAYER_PUZZLE_HASH},
+    singleton::{SingletonArgs, SINGLETON_LAUNCHER_PUZZLE_HASH, SINGLETON_TOP_LAYER_PUZZLE_HASH},
+};
+use chia_sdk_types::{run_puzzle, Condition};
+use clvm_traits::{FromClvm, ToClvm};
+use clvmr::{Allocator, NodePtr};
+
+use crate::{
+    Cat, CatLayer, CatVersion, Did, DidLayer, DriverError, HashedPtr, Layer, Nft,
+    NftOwnershipLayer, NftStateLayer, Puzzle, RoyaltyTransferLayer, SingletonLayer,
+};
+
+#[cfg(feature = "chip-0035")]
+use crate::{DataStore, DataStoreMetadata};
+
+/// The kind of asset held by a coin, as determined from its parent spend.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Asset {
+    /// A coin that is confirmed not to be wrapped by any of the known asset puzzles, because it has
+    /// one of the classifier's p2 puzzle hashes, or the same puzzle hash as its parent, which isn't wrapped either.
+    Xch(Coin),
+    Cat(Cat),
+    Nft(Nft<HashedPtr>),
+    Did(Did<HashedPtr>),
+    #[cfg(feature = "chip-0035")]
+    DataStore(DataStore),
+    /// A coin that couldn't be classified from its parent spend alone.
+    /// Eve coins, such as the first coin of a newly issued CAT, can't be recognized from their parent,
+    /// nor can other coins whose puzzle hash isn't known to be unwrapped.
+    Unknown(Coin),
+}
+
+impl Asset {
+    pub fn coin(&self) -> Coin {
+        match self {
+            Self::Xch(coin) | Self::Unknown(coin) => *coin,
+            Self::Cat(cat) => cat.coin,
+            Self::Nft(nft) => nft.coin,
+            Self::Did(did) => did.coin,
+            #[cfg(feature = "chip-0035")]
+            Self::DataStore(datastore) => datastore.coin,
+        }
+    }
+}
+
+/// Classifies coins by parsing the spend of their parent coin.
+///
+/// The parent puzzle is only deserialized and parsed once, and dispatched by its mod hash.
+/// The relevant inner puzzle is run at most once, and the child is found from its output conditions.
+///
+/// A child's puzzle hash doesn't reveal whether it's wrapped, so payments to a wallet can only be classified
+/// as [`Asset::Xch`] if the wallet's p2 puzzle hashes are given with [`CoinClassifier::with_p2_puzzle_hashes`].
+#[derive(Debug, Default, Clone)]
+pub struct CoinClassifier {
+    p2_puzzle_hashes: HashSet<Bytes32>,
+}
+
+impl CoinClassifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds puzzle hashes of unwrapped p2 puzzles, such as those of a wallet.
+    /// Coins with any of these puzzle hashes are classified as [`Asset::Xch`], regardless of their parent.
+    #[must_use]
+    pub fn with_p2_puzzle_hashes(
+        mut self,
+        p2_puzzle_hashes: impl IntoIterator<Item = Bytes32>,
+    ) -> Self {
+        self.p2_puzzle_hashes.extend(p2_puzzle_hashes);
+        self
+    }
+
+    /// Classifies the coin, which must be a child of the given parent spend.
+    ///
+    /// The metadata of NFTs and DIDs is allocated in the given allocator.
+    /// Delegated puzzles of a [`DataStore`](crate::DataStore) that weren't changed by the parent spend aren't known.
+    pub fn classify(
+        &self,
+        allocator: &mut Allocator,
+        parent_spend: &CoinSpend,
+        coin: Coin,
+    ) -> Result<Asset, DriverError> {
+        if coin.parent_coin_info != parent_spend.coin.coin_id() {
+            return Err(DriverError::MissingChild);
+        }
+
+        // The puzzle hash commits to the whole puzzle, so a known p2 puzzle hash can't be wrapped.
+        if self.p2_puzzle_hashes.contains(&coin.puzzle_hash) {
+            return Ok(Asset::Xch(coin));
+        }
+
+        if parent_spend.coin.puzzle_hash == SINGLETON_LAUNCHER_PUZZLE_HASH.into() {
+            // The launcher solution is parsed without running the launcher.
+            #[cfg(feature = "chip-0035")]
+            if let Ok(Some(datastore)) = DataStore::from_spend(allocator, parent_spend, &[]) {
+                if datastore.coin == coin {
+                    return Ok(Asset::DataStore(datastore));
+                }
+            }
+
+            return Ok(Asset::Unknown(coin));
+        }
+
+        let parent_puzzle = parent_spend.puzzle_reveal.to_clvm(allocator)?;
+        let parent_puzzle = Puzzle::parse(allocator, parent_puzzle);
+        let parent_solution = parent_spend.solution.to_clvm(allocator)?;
+
+        if CatVersion::from_mod_hash(parent_puzzle.mod_hash()).is_some() {
+            return classify_cat_child(
+                allocator,
+                parent_spend.coin,
+                parent_puzzle,
+                parent_solution,
+                coin,
+            );
+        }
+
+        if parent_puzzle.mod_hash() == SINGLETON_TOP_LAYER_PUZZLE_HASH {
+            return classify_singleton_child(
+                allocator,
+                parent_spend.coin,
+                parent_puzzle,
+                parent_solution,
+                coin,
+            );
+        }
+
+        // The parent isn't wrapped by a known asset puzzle, so a child with the same puzzle hash isn't either.
+        // Any other puzzle hash could be an eve coin of an asset, so it can't be confirmed.
+        if coin.puzzle_hash == parent_spend.coin.puzzle_hash {
+            return Ok(Asset::Xch(coin));
+        }
+
+        Ok(Asset::Unknown(coin))
+    }
+}
+
+fn classify_cat_child(
+    allocator: &mut Allocator,
+    parent_coin: Coin,
+    parent_puzzle: Puzzle,
+    parent_solution: NodePtr,
+    coin: Coin,
+) -> Result<Asset, DriverError> {
+    let Some(cat_layer) = CatLayer::<Puzzle>::parse_puzzle(allocator, parent_puzzle)? else {
+        return Ok(Asset::Unknown(coin));
+    };
+    let cat_solution = CatLayer::<Puzzle>::parse_solution(allocator, parent_solution)?;
+
+    let conditions = run_conditions(
+        allocator,
+        cat_layer.inner_puzzle.ptr(),
+        cat_solution.inner_puzzle_solution,
+    )?;
+
+    let cat = Cat::child_from_conditions(parent_coin, &cat_layer, conditions, coin);
+    Ok(child_or_unknown(cat)?.map_or(Asset::Unknown(coin), Asset::Cat))
+}
+
+fn classify_singleton_child(
+    allocator: &mut Allocator,
+    parent_coin: Coin,
+    parent_puzzle: Puzzle,
+    parent_solution: NodePtr,
+    coin: Coin,
+) -> Result<Asset, DriverError> {
+    // Singletons can only recreate themselves with an odd amount.
+    // Other children, such as launchers, can't be classified from the parent.
+    if coin.amount % 2 == 0 {
+        return Ok(Asset::Unknown(coin));
+    }
+
+    let Some(singleton_layer) = SingletonLayer::<Puzzle>::parse_puzzle(allocator, parent_puzzle)?
+    else {
+        return Ok(Asset::Unknown(coin));
+    };
+    let singleton_solution = SingletonLayer::<NodePtr>::parse_solution(allocator, parent_solution)?;
+
+    let launcher_id = singleton_layer.launcher_id;
+    let inner_puzzle = singleton_layer.inner_puzzle;
+    let inner_solution = singleton_solution.inner_solution;
+
+    if inner_puzzle.mod_hash() == DID_INNER_PUZZLE_HASH {
+        let Some(did_layer) = DidLayer::<HashedPtr, Puzzle>::parse_puzzle(allocator, inner_puzzle)?
+        else {
+            return Ok(Asset::Unknown(coin));
+        };
+
+        if did_layer.launcher_id != launcher_id {
+            return Err(DriverError::InvalidSingletonStruct);
+        }
+
+        let conditions = run_conditions(allocator, inner_puzzle.ptr(), inner_solution)?;
+        let did = Did::child_from_conditions(
+            allocator,
+            parent_coin,
+            launcher_id,
+            did_layer,
+            conditions,
+            coin,
+        );
+
+        return Ok(match child_or_unknown(did)? {
+            Some(did)
+                if SingletonArgs::curry_tree_hash(
+                    did.info.launcher_id,
+                    did.info.inner_puzzle_hash(),
+                ) == coin.puzzle_hash.into() =>
+            {
+                Asset::Did(did)
+            }
+            _ => Asset::Unknown(coin),
+        });
+    }
+
+    if inner_puzzle.mod_hash() != NFT_STATE_LAYER_PUZZLE_HASH {
+        return Ok(Asset::Unknown(coin));
+    }
+
+    let Some(state_layer) =
+        NftStateLayer::<HashedPtr, Puzzle>::parse_puzzle(allocator, inner_puzzle)?
+    else {
+        return Ok(Asset::Unknown(coin));
+    };
+    let state_solution =
+        NftStateLayer::<HashedPtr, NodePtr>::parse_solution(allocator, inner_solution)?;
+
+    if state_layer.inner_puzzle.mod_hash() == NFT_OWNERSHIP_LAYER_PUZZLE_HASH {
+        let Some(ownership_layer) =
+            NftOwnershipLayer::<RoyaltyTransferLayer, Puzzle>::parse_puzzle(
+                allocator,
+                state_layer.inner_puzzle,
+            )?
+        else {
+            return Ok(Asset::Unknown(coin));
+        };
+        let ownership_solution =
+            NftOwnershipLayer::<RoyaltyTransferLayer, NodePtr>::parse_solution(
+                allocator,
+                state_solution.inner_solution,
+            )?;
+
+        let conditions = run_conditions(
+            allocator,
+            ownership_layer.inner_puzzle.ptr(),
+            ownership_solution.inner_solution,
+        )?;
+
+        let nft = Nft::child_from_conditions(
+            allocator,
+            parent_coin,
+            launcher_id,
+            inner_puzzle.curried_puzzle_hash().into(),
+            NftStateLayer::new(
+                state_layer.metadata,
+                state_layer.metadata_updater_puzzle_hash,
+                ownership_layer,
+            ),
+            conditions,
+            coin,
+        );
+
+        return Ok(match child_or_unknown(nft)? {
+            Some(nft)
+                if SingletonArgs::curry_tree_hash(
+                    nft.info.launcher_id,
+                    nft.info.inner_puzzle_hash(),
+                ) == coin.puzzle_hash.into() =>
+            {
+                Asset::Nft(nft)
+            }
+            _ => Asset::Unknown(coin),
+        });
+    }
+
+    // Data stores use the state layer with either a delegation layer or the owner's puzzle directly.
+    #[cfg(feature = "chip-0035")]
+    {
+        let Ok(metadata) = DataStoreMetadata::from_clvm(allocator, state_layer.metadata.ptr())
+        else {
+            return Ok(Asset::Unknown(coin));
+        };
+
+        let conditions = run_conditions(
+            allocator,
+            state_layer.inner_puzzle.ptr(),
+            state_solution.inner_solution,
+        )?;
+
+        let datastore = DataStore::child_from_conditions(
+            allocator,
+            parent_coin,
+            singleton_layer,
+            NftStateLayer::new(
+                metadata,
+                state_layer.metadata_updater_puzzle_hash,
+                state_layer.inner_puzzle,
+            ),
+            state_solution.inner_solution,
+            conditions,
+            &[],
+        );
+
+        if let Some(datastore) = child_or_unknown(datastore)? {
+            if datastore.coin == coin {
+                return Ok(Asset::DataStore(datastore));
+            }
+        }
+    }
+
+    Ok(Asset::Unknown(coin))
+}
+
+/// Runs a puzzle and parses its output conditions, which are shared by the candidate primitives.
+fn run_conditions(
+    allocator: &mut Allocator,
+    puzzle: NodePtr,
+    solution: NodePtr,
+) -> Result<Vec<Condition>, DriverError> {
+    let output = run_puzzle(allocator, puzzle, solution)?;
+    Ok(Vec::<Condition>::from_clvm(allocator, output)?)
+}
+
+/// A parent spend that doesn't create the expected child means the coin isn't of that type,
+/// rather than being an error.
+fn child_or_unknown<T>(result: Result<T, DriverError>) -> Result<Option<T>, DriverError> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(DriverError::MissingChild | DriverError::MissingHint) => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_protocol::Bytes32;
+    use chia_puzzles::nft::NftMetadata;
+    use chia_sdk_test::Simulator;
+    use chia_sdk_types::Conditions;
+
+    use crate::{DidOwner, IntermediateLauncher, Launcher, NftMint, SpendContext, StandardLayer};
+
+    use super::*;
+
+    #[test]
+    fn test_classify_coins() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(2)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (create_did, did) = Launcher::new(coin.coin_id(), 1).create_simple_did(ctx, &p2)?;
+        p2.spend(ctx, coin, create_did)?;
+
+        let mint = NftMint::new(
+            NftMetadata::default(),
+            puzzle_hash,
+            300,
+            Some(DidOwner::from_did_info(&did.info)),
+        );
+
+        let (mint_nft, nft) = IntermediateLauncher::new(did.coin.coin_id(), 0, 1)
+            .create(ctx)?
+            .mint_nft(ctx, mint)?;
+        let new_did = did.update(ctx, &p2, mint_nft)?;
+        let new_nft = nft.transfer(ctx, &p2, puzzle_hash, Conditions::new())?;
+
+        let (cat_secret_key, cat_public_key, cat_puzzle_hash, cat_coin) = sim.new_p2(3)?;
+        let cat_p2 = StandardLayer::new(cat_public_key);
+        let (issue_cat, cat) = Cat::single_issuance_eve(
+            ctx,
+            cat_coin.coin_id(),
+            3,
+            Conditions::new().create_coin(cat_puzzle_hash, 3, vec![cat_puzzle_hash.into()]),
+        )?;
+        cat_p2.spend(ctx, cat_coin, issue_cat)?;
+
+        let (xch_secret_key, xch_public_key, xch_puzzle_hash, xch_coin) = sim.new_p2(3)?;
+        StandardLayer::new(xch_public_key).spend(
+            ctx,
+            xch_coin,
+            Conditions::new()
+                .create_coin(xch_puzzle_hash, 1, Vec::new())
+                .create_coin(Bytes32::new([1; 32]), 2, Vec::new()),
+        )?;
+
+        let coin_spends = ctx.take();
+        sim.spend_coins(coin_spends.clone(), &[sk, cat_secret_key, xch_secret_key])?;
+
+        let parent_spend = |coin: Coin| {
+            coin_spends
+                .iter()
+                .find(|coin_spend| coin_spend.coin.coin_id() == coin.parent_coin_info)
+                .cloned()
+                .expect("parent spend")
+        };
+
+        let classifier = CoinClassifier::new();
+        let mut allocator = Allocator::new();
+
+        let Asset::Did(found) =
+            classifier.classify(&mut allocator, &parent_spend(new_did.coin), new_did.coin)?
+        else {
+            panic!("expected did");
+        };
+        assert_eq!(found.info.launcher_id, new_did.info.launcher_id);
+        assert_eq!(found.info.p2_puzzle_hash, puzzle_hash);
+
+        let Asset::Nft(found) =
+            classifier.classify(&mut allocator, &parent_spend(new_nft.coin), new_nft.coin)?
+        else {
+            panic!("expected nft");
+        };
+        assert_eq!(found.coin, new_nft.coin);
+        assert_eq!(found.info.launcher_id, new_nft.info.launcher_id);
+
+        let cat_child = cat.wrapped_child(cat_puzzle_hash, 3);
+        assert_eq!(
+            classifier.classify(
+                &mut allocator,
+                &parent_spend(cat_child.coin),
+                cat_child.coin
+            )?,
+            Asset::Cat(cat_child)
+        );
+
+        let xch_child = Coin::new(xch_coin.coin_id(), xch_puzzle_hash, 1);
+        assert_eq!(
+            classifier.classify(&mut allocator, &parent_spend(xch_child), xch_child)?,
+            Asset::Xch(xch_child)
+        );
+
+        // A payment to another puzzle hash is only known to be unwrapped if it's one of the wallet's.
+        let payment = Coin::new(xch_coin.coin_id(), Bytes32::new([1; 32]), 2);
+        assert_eq!(
+            classifier.classify(&mut allocator, &parent_spend(payment), payment)?,
+            Asset::Unknown(payment)
+        );
+        assert_eq!(
+            CoinClassifier::new()
+                .with_p2_puzzle_hashes([Bytes32::new([1; 32])])
+                .classify(&mut allocator, &parent_spend(payment), payment)?,
+            Asset::Xch(payment)
+        );
+
+        // The eve CAT was created by an unwrapped coin, but its puzzle hash can't be confirmed as unwrapped.
+        assert_eq!(
+            classifier.classify(&mut allocator, &parent_spend(cat.coin), cat.coin)?,
+            Asset::Unknown(cat.coin)
+        );
+
+        // The eve DID was created by the launcher, which doesn't reveal its inner puzzle.
+        let eve_did = parent_spend(did.coin).coin;
+        assert_eq!(
+            classifier.classify(&mut allocator, &parent_spend(eve_did), eve_did)?,
+            Asset::Unknown(eve_did)
+        );
+
+        let wrong_parent = Coin::new(Bytes32::default(), xch_puzzle_hash, 1);
+        assert!(classifier
+            .classify(&mut allocator, &parent_spend(xch_child), wrong_parent)
+            .is_err());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "chip-0035")]
+    #[test]
+    fn test_classify_datastore() -> anyhow::Result<()> {
+        use crate::{DataStoreMetadata, MetadataWithRootHash, SpendWithConditions};
+
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (launch_singleton, datastore) = Launcher::new(coin.coin_id(), 1).mint_datastore(
+            ctx,
+            DataStoreMetadata::root_hash_only(Bytes32::default()),
+            puzzle_hash.into(),
+            vec![],
+        )?;
+        p2.spend(ctx, coin, launch_singleton)?;
+
+        let inner_spend = p2.spend_with_conditions(
+            ctx,
+            Conditions::new().create_coin(puzzle_hash, 1, Vec::new()),
+        )?;
+        let datastore_spend = datastore.clone().spend(ctx, inner_spend)?;
+        ctx.insert(datastore_spend.clone());
+
+        let coin_spends = ctx.take();
+        sim.spend_coins(coin_spends.clone(), &[sk])?;
+
+        let classifier = CoinClassifier::new();
+        let mut allocator = Allocator::new();
+
+        let launcher_spend = coin_spends
+            .iter()
+            .find(|coin_spend| coin_spend.coin.coin_id() == datastore.info.launcher_id)
+            .expect("launcher spend");
+        assert_eq!(
+            classifier.classify(&mut allocator, launcher_spend, datastore.coin)?,
+            Asset::DataStore(datastore)
+        );
+
+        let child =
+            DataStore::<DataStoreMetadata>::from_spend(&mut allocator, &datastore_spend, &[])?
+                .expect("datastore child");
+        assert_eq!(
+            classifier.classify(&mut allocator, &datastore_spend, child.coin)?,
+            Asset::DataStore(child)
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,297 @@
+use chia_consensus::{
+    consensus_constants::ConsensusConstants,
+    gen::{
+        flags::MEMPOOL_MODE,
+        opcodes::{compute_unknown_condition_cost, parse_opcode, AGG_SIG_COST, CREATE_COIN_COST},
+        solution_generator::solution_generator,
+    },
+    spendbundle_validation::get_flags_for_height_and_constants,
+};
+use chia_protocol::{Bytes32, Coin, CoinSpend};
+use chia_sdk_types::Condition;
+use clvm_traits::{FromClvm, ToClvm};
+use clvmr::{reduction::Reduction, run_program, Allocator, ChiaDialect, NodePtr};
+
+use crate::DriverError;
+
+/// The number of bytes in the generator for the quote that wraps the list of spends, which isn't charged for.
+const QUOTE_BYTES: usize = 2;
+
+/// The cost argument of a `SOFTFORK` condition is scaled by this to keep it small.
+const SOFTFORK_COST_SCALE: u64 = 10_000;
+
+/// The number of bytes in an empty block generator, which are charged for regardless of the spends.
+fn generator_overhead() -> Result<usize, DriverError> {
+    Ok(solution_generator(Vec::<(Coin, &[u8], &[u8])>::new())?.len())
+}
+
+/// The cost of a single coin spend, split into the parts that the mempool charges for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CoinSpendCost {
+    pub coin_id: Bytes32,
+    /// The cost of running the puzzle with the solution.
+    pub execution_cost: u64,
+    /// The cost of the conditions output by the puzzle, which are `CREATE_COIN`, `AGG_SIG_*`,
+    /// `SOFTFORK` and unknown conditions with a cost. The mempool rejects the last two,
+    /// but they're charged for in blocks.
+    pub condition_cost: u64,
+    /// The cost of the serialized spend in the block generator.
+    pub byte_cost: u64,
+}
+
+impl CoinSpendCost {
+    /// Runs a coin spend with the Chia dialect and calculates its cost.
+    pub fn calculate(
+        allocator: &mut Allocator,
+        coin_spend: &CoinSpend,
+        height: u32,
+        constants: &ConsensusConstants,
+    ) -> Result<Self, DriverError> {
+        let flags = get_flags_for_height_and_constants(height, constants) | MEMPOOL_MODE;
+
+        let puzzle = coin_spend.puzzle_reveal.to_clvm(allocator)?;
+        let solution = coin_spend.solution.to_clvm(allocator)?;
+
+        let Reduction(execution_cost, output) = run_program(
+            allocator,
+            &ChiaDialect::new(flags),
+            puzzle,
+            solution,
+            constants.max_block_cost_clvm,
+        )?;
+
+        let conditions = Vec::<Condition>::from_clvm(allocator, output)?;
+
+        let condition_cost = conditions
+            .iter()
+            .map(|condition| match condition {
+                Condition::CreateCoin(..) => CREATE_COIN_COST,
+                Condition::Softfork(softfork) => softfork.cost.saturating_mul(SOFTFORK_COST_SCALE),
+                Condition::Other(condition) => unknown_condition_cost(allocator, *condition),
+                condition if condition.is_agg_sig() => AGG_SIG_COST,
+                _ => 0,
+            })
+            .fold(0, u64::saturating_add);
+
+        let generator = solution_generator([(
+            coin_spend.coin,
+            coin_spend.puzzle_reveal.as_ref(),
+            coin_spend.solution.as_ref(),
+        )])?;
+        let bytes = generator.len() - generator_overhead()?;
+
+        Ok(Self {
+            coin_id: coin_spend.coin.coin_id(),
+            execution_cost,
+            condition_cost,
+            byte_cost: bytes as u64 * constants.cost_per_byte,
+        })
+    }
+
+    /// The total cost of the coin spend.
+    pub fn total(&self) -> u64 {
+        self.execution_cost + self.condition_cost + self.byte_cost
+    }
+}
+
+/// The cost of a condition with an unknown opcode, which is only charged for two byte opcodes.
+fn unknown_condition_cost(allocator: &Allocator, condition: NodePtr) -> u64 {
+    allocator
+        .next(condition)
+        .and_then(|(opcode, _)| parse_opcode(allocator, opcode, 0))
+        .map_or(0, compute_unknown_condition_cost)
+}
+
+/// The cost of a spend bundle, as it would be calculated by the mempool.
+///
+/// Adding a fee changes the solution of one of the coin spends, so the cost should be
+/// calculated again with the final fee included (or with a placeholder of the same size).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpendBundleCost {
+    pub spends: Vec<CoinSpendCost>,
+    /// The byte cost of the block generator that isn't part of any individual spend.
+    pub overhead_byte_cost: u64,
+}
+
+impl SpendBundleCost {
+    /// Runs every coin spend with the Chia dialect and calculates the cost of the spend bundle.
+    pub fn calculate(
+        allocator: &mut Allocator,
+        coin_spends: &[CoinSpend],
+        height: u32,
+        constants: &ConsensusConstants,
+    ) -> Result<Self, DriverError> {
+        let spends = coin_spends
+            .iter()
+            .map(|coin_spend| CoinSpendCost::calculate(allocator, coin_spend, height, constants))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            spends,
+            overhead_byte_cost: (generator_overhead()? - QUOTE_BYTES) as u64
+                * constants.cost_per_byte,
+        })
+    }
+
+    pub fn execution_cost(&self) -> u64 {
+        self.spends.iter().map(|spend| spend.execution_cost).sum()
+    }
+
+    pub fn condition_cost(&self) -> u64 {
+        self.spends.iter().map(|spend| spend.condition_cost).sum()
+    }
+
+    pub fn byte_cost(&self) -> u64 {
+        self.overhead_byte_cost + self.spends.iter().map(|spend| spend.byte_cost).sum::<u64>()
+    }
+
+    /// The total cost of the spend bundle.
+    pub fn total(&self) -> u64 {
+        self.execution_cost() + self.condition_cost() + self.byte_cost()
+    }
+
+    /// The minimum fee in mojos needed to pay the given fee rate, in mojos per unit of cost.
+    pub fn min_fee(&self, fee_rate: u64) -> u64 {
+        self.total().saturating_mul(fee_rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_bls::Signature;
+    use chia_consensus::{
+        gen::{conditions::EmptyVisitor, run_block_generator::run_block_generator2},
+        spendbundle_conditions::get_conditions_from_spendbundle,
+    };
+    use chia_protocol::SpendBundle;
+    use chia_sdk_test::{to_program, to_puzzle, Simulator};
+    use chia_sdk_types::{Conditions, TESTNET11_CONSTANTS};
+    use clvm_traits::clvm_list;
+
+    use crate::{block_generator, SpendContext, StandardLayer};
+
+    use super::*;
+
+    #[test]
+    fn test_spend_bundle_cost() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1_000)?;
+        let (_, other_pk, other_puzzle_hash, other_coin) = sim.new_p2(500)?;
+        assert_eq!(pk, other_pk);
+
+        let p2 = StandardLayer::new(pk);
+        p2.spend(
+            ctx,
+            coin,
+            Conditions::new()
+                .create_coin(puzzle_hash, 900, Vec::new())
+                .reserve_fee(100)
+                .assert_concurrent_spend(other_coin.coin_id()),
+        )?;
+        p2.spend(
+            ctx,
+            other_coin,
+            Conditions::new().create_coin(other_puzzle_hash, 500, Vec::new()),
+        )?;
+
+        let coin_spends = ctx.take();
+        let mut allocator = Allocator::new();
+
+        let cost = SpendBundleCost::calculate(
+            &mut allocator,
+            &coin_spends,
+            sim.height(),
+            &TESTNET11_CONSTANTS,
+        )?;
+
+        assert_eq!(cost.spends.len(), 2);
+        assert_eq!(cost.spends[0].coin_id, coin.coin_id());
+        assert_eq!(
+            cost.spends[0].condition_cost,
+            CREATE_COIN_COST + AGG_SIG_COST
+        );
+
+        let spend_bundle = SpendBundle::new(coin_spends.clone(), Signature::default());
+        let conditions = get_conditions_from_spendbundle(
+            &mut allocator,
+            &spend_bundle,
+            TESTNET11_CONSTANTS.max_block_cost_clvm,
+            sim.height(),
+            &TESTNET11_CONSTANTS,
+        )
+        .map_err(|error| anyhow::anyhow!("validation error: {:?}", error.1))?;
+
+        assert_eq!(cost.total(), conditions.cost);
+        assert_eq!(cost.min_fee(5), conditions.cost * 5);
+
+        sim.spend_coins(coin_spends, &[sk])?;
+
+        Ok(())
+    }
+
+    /// The cost of the coin spends when they're included in a block, rather than submitted to the mempool.
+    fn block_cost(coin_spends: &[CoinSpend], height: u32) -> anyhow::Result<u64> {
+        let generator = block_generator(coin_spends, false)?;
+        let conditions = run_block_generator2::<_, EmptyVisitor, _>(
+            &mut Allocator::new(),
+            generator.as_ref(),
+            Vec::<&[u8]>::new(),
+            TESTNET11_CONSTANTS.max_block_cost_clvm,
+            get_flags_for_height_and_constants(height, &TESTNET11_CONSTANTS),
+            &TESTNET11_CONSTANTS,
+        )
+        .map_err(|error| anyhow::anyhow!("validation error: {:?}", error.1))?;
+        Ok(conditions.cost)
+    }
+
+    #[test]
+    fn test_softfork_cost() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+
+        // The puzzle `1` returns the solution as its output conditions.
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+        let coin = sim.new_coin(puzzle_hash, 1);
+
+        let create_coin = clvm_list!(51, puzzle_hash, 1);
+        let plain = vec![CoinSpend::new(
+            coin,
+            puzzle_reveal.clone(),
+            to_program(clvm_list!(create_coin))?,
+        )];
+        let softfork = vec![CoinSpend::new(
+            coin,
+            puzzle_reveal,
+            to_program(clvm_list!(
+                create_coin,
+                clvm_list!(90, 3),
+                clvm_list!(0x0100, 1)
+            ))?,
+        )];
+
+        let mut allocator = Allocator::new();
+        let plain_cost =
+            SpendBundleCost::calculate(&mut allocator, &plain, sim.height(), &TESTNET11_CONSTANTS)?;
+        let softfork_cost = SpendBundleCost::calculate(
+            &mut allocator,
+            &softfork,
+            sim.height(),
+            &TESTNET11_CONSTANTS,
+        )?;
+
+        assert_eq!(
+            softfork_cost.condition_cost(),
+            CREATE_COIN_COST + 3 * SOFTFORK_COST_SCALE + compute_unknown_condition_cost(0x0100)
+        );
+
+        // The mempool rejects these conditions, so they're compared with the cost of a block instead.
+        // The block generator also charges for the quote around the spends, which is the same for both.
+        assert_eq!(
+            block_cost(&softfork, sim.height())? - softfork_cost.total(),
+            block_cost(&plain, sim.height())? - plain_cost.total()
+        );
+
+        Ok(())
+    }
+}
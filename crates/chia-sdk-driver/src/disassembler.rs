@@ -0,0 +1,646 @@
+use std::collections::HashMap;
+
+use chia_protocol::Program;
+use chia_puzzles::{
+    cat::{
+        CAT_PUZZLE_HASH, CAT_PUZZLE_HASH_V1, EVERYTHING_WITH_SIGNATURE_TAIL_PUZZLE_HASH,
+        GENESIS_BY_COIN_ID_TAIL_PUZZLE_HASH,
+    },
+    did::DID_INNER_PUZZLE_HASH,
+    nft::{
+        NFT_INTERMEDIATE_LAUNCHER_PUZZLE_HASH, NFT_METADATA_UPDATER_PUZZLE_HASH,
+        NFT_OWNERSHIP_LAYER_PUZZLE_HASH, NFT_ROYALTY_TRANSFER_PUZZLE_HASH,
+        NFT_STATE_LAYER_PUZZLE_HASH,
+    },
+    offer::SETTLEMENT_PAYMENTS_PUZZLE_HASH,
+    singleton::{SINGLETON_LAUNCHER_PUZZLE_HASH, SINGLETON_TOP_LAYER_PUZZLE_HASH},
+    standard::STANDARD_PUZZLE_HASH,
+};
+use clvm_utils::TreeHash;
+use clvmr::{serde::node_from_bytes, Allocator, NodePtr, SExp};
+
+use crate::{
+    DriverError, Puzzle, SpendContext, P2_DELEGATED_CONDITIONS_PUZZLE_HASH,
+    P2_ONE_OF_MANY_PUZZLE_HASH, REVOCATION_LAYER_PUZZLE_HASH,
+};
+
+#[cfg(feature = "chip-0035")]
+use crate::{
+    DELEGATION_LAYER_PUZZLE_HASH, DL_METADATA_UPDATER_PUZZLE_HASH, WRITER_FILTER_PUZZLE_HASH,
+};
+
+/// The name of a curried argument of a known puzzle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CurriedArg {
+    pub name: String,
+    /// Whether the argument is itself a puzzle, which should be disassembled as code rather than data.
+    pub is_puzzle: bool,
+}
+
+impl CurriedArg {
+    pub fn value(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            is_puzzle: false,
+        }
+    }
+
+    pub fn puzzle(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            is_puzzle: true,
+        }
+    }
+}
+
+/// A puzzle that can be recognized by its mod hash when disassembling.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KnownPuzzle {
+    pub name: String,
+    pub args: Vec<CurriedArg>,
+}
+
+impl KnownPuzzle {
+    pub fn new(name: impl Into<String>, args: Vec<CurriedArg>) -> Self {
+        Self {
+            name: name.into(),
+            args,
+        }
+    }
+}
+
+/// Converts CLVM into chialisp-style disassembly, for debugging.
+///
+/// Known puzzles are annotated by name, and their curried arguments are shown by name as well.
+/// For example, a standard puzzle is shown as `(mod standard (synthetic_key 0x...))`.
+#[derive(Debug, Clone)]
+pub struct Disassembler {
+    puzzles: HashMap<TreeHash, KnownPuzzle>,
+}
+
+impl Default for Disassembler {
+    fn default() -> Self {
+        let mut disassembler = Self::empty();
+
+        disassembler.register(
+            STANDARD_PUZZLE_HASH,
+            KnownPuzzle::new("standard", vec![CurriedArg::value("synthetic_key")]),
+        );
+        disassembler.register(
+            CAT_PUZZLE_HASH,
+            KnownPuzzle::new(
+                "cat",
+                vec![
+                    CurriedArg::value("mod_hash"),
+                    CurriedArg::value("asset_id"),
+                    CurriedArg::puzzle("inner_puzzle"),
+                ],
+            ),
+        );
+        disassembler.register(
+            CAT_PUZZLE_HASH_V1,
+            KnownPuzzle::new(
+                "cat_v1",
+                vec![
+                    CurriedArg::value("mod_hash"),
+                    CurriedArg::value("asset_id"),
+                    CurriedArg::puzzle("inner_puzzle"),
+                ],
+            ),
+        );
+        disassembler.register(
+            EVERYTHING_WITH_SIGNATURE_TAIL_PUZZLE_HASH,
+            KnownPuzzle::new(
+                "everything_with_signature",
+                vec![CurriedArg::value("public_key")],
+            ),
+        );
+        disassembler.register(
+            GENESIS_BY_COIN_ID_TAIL_PUZZLE_HASH,
+            KnownPuzzle::new(
+                "genesis_by_coin_id",
+                vec![CurriedArg::value("genesis_coin_id")],
+            ),
+        );
+        disassembler.register(
+            SINGLETON_TOP_LAYER_PUZZLE_HASH,
+            KnownPuzzle::new(
+                "singleton",
+                vec![
+                    CurriedArg::value("singleton_struct"),
+                    CurriedArg::puzzle("inner_puzzle"),
+                ],
+            ),
+        );
+        disassembler.register(
+            SINGLETON_LAUNCHER_PUZZLE_HASH,
+            KnownPuzzle::new("singleton_launcher", Vec::new()),
+        );
+        disassembler.register(
+            NFT_STATE_LAYER_PUZZLE_HASH,
+            KnownPuzzle::new(
+                "nft_state_layer",
+                vec![
+                    CurriedArg::value("mod_hash"),
+                    CurriedArg::value("metadata"),
+                    CurriedArg::value("metadata_updater_puzzle_hash"),
+                    CurriedArg::puzzle("inner_puzzle"),
+                ],
+            ),
+        );
+        disassembler.register(
+            NFT_OWNERSHIP_LAYER_PUZZLE_HASH,
+            KnownPuzzle::new(
+                "nft_ownership_layer",
+                vec![
+                    CurriedArg::value("mod_hash"),
+                    CurriedArg::value("current_owner"),
+                    CurriedArg::puzzle("transfer_program"),
+                    CurriedArg::puzzle("inner_puzzle"),
+                ],
+            ),
+        );
+        disassembler.register(
+            NFT_ROYALTY_TRANSFER_PUZZLE_HASH,
+            KnownPuzzle::new(
+                "nft_royalty_transfer",
+                vec![
+                    CurriedArg::value("singleton_struct"),
+                    CurriedArg::value("royalty_puzzle_hash"),
+                    CurriedArg::value("royalty_ten_thousandths"),
+                ],
+            ),
+        );
+        disassembler.register(
+            NFT_METADATA_UPDATER_PUZZLE_HASH,
+            KnownPuzzle::new("nft_metadata_updater", Vec::new()),
+        );
+        disassembler.register(
+            NFT_INTERMEDIATE_LAUNCHER_PUZZLE_HASH,
+            KnownPuzzle::new(
+                "nft_intermediate_launcher",
+                vec![
+                    CurriedArg::value("launcher_puzzle_hash"),
+                    CurriedArg::value("mint_number"),
+                    CurriedArg::value("mint_total"),
+                ],
+            ),
+        );
+        disassembler.register(
+            DID_INNER_PUZZLE_HASH,
+            KnownPuzzle::new(
+                "did",
+                vec![
+                    CurriedArg::puzzle("inner_puzzle"),
+                    CurriedArg::value("recovery_list_hash"),
+                    CurriedArg::value("num_verifications_required"),
+                    CurriedArg::value("singleton_struct"),
+                    CurriedArg::value("metadata"),
+                ],
+            ),
+        );
+        disassembler.register(
+            SETTLEMENT_PAYMENTS_PUZZLE_HASH,
+            KnownPuzzle::new("settlement_payments", Vec::new()),
+        );
+        disassembler.register(
+            P2_DELEGATED_CONDITIONS_PUZZLE_HASH,
+            KnownPuzzle::new(
+                "p2_delegated_conditions",
+                vec![CurriedArg::value("public_key")],
+            ),
+        );
+        disassembler.register(
+            P2_ONE_OF_MANY_PUZZLE_HASH,
+            KnownPuzzle::new("p2_one_of_many", vec![CurriedArg::value("merkle_root")]),
+        );
+        disassembler.register(
+            REVOCATION_LAYER_PUZZLE_HASH,
+            KnownPuzzle::new(
+                "revocation_layer",
+                vec![
+                    CurriedArg::value("mod_hash"),
+                    CurriedArg::value("hidden_puzzle_hash"),
+                    CurriedArg::value("inner_puzzle_hash"),
+                ],
+            ),
+        );
+
+        #[cfg(feature = "chip-0035")]
+        {
+            disassembler.register(
+                DELEGATION_LAYER_PUZZLE_HASH,
+                KnownPuzzle::new(
+                    "delegation_layer",
+                    vec![
+                        CurriedArg::value("mod_hash"),
+                        CurriedArg::value("launcher_id"),
+                        CurriedArg::value("owner_puzzle_hash"),
+                        CurriedArg::value("merkle_root"),
+                    ],
+                ),
+            );
+            disassembler.register(
+                WRITER_FILTER_PUZZLE_HASH,
+                KnownPuzzle::new("writer_filter", vec![CurriedArg::puzzle("inner_puzzle")]),
+            );
+            disassembler.register(
+                DL_METADATA_UPDATER_PUZZLE_HASH,
+                KnownPuzzle::new("dl_metadata_updater", Vec::new()),
+            );
+        }
+
+        disassembler
+    }
+}
+
+impl Disassembler {
+    /// Creates a disassembler that knows about the puzzles in this SDK.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a disassembler that doesn't know about any puzzles.
+    pub fn empty() -> Self {
+        Self {
+            puzzles: HashMap::new(),
+        }
+    }
+
+    /// Registers a puzzle by its mod hash, so that it will be annotated by name.
+    pub fn register(&mut self, mod_hash: TreeHash, puzzle: KnownPuzzle) {
+        self.puzzles.insert(mod_hash, puzzle);
+    }
+
+    pub fn known_puzzle(&self, mod_hash: TreeHash) -> Option<&KnownPuzzle> {
+        self.puzzles.get(&mod_hash)
+    }
+
+    /// Disassembles the CLVM on a single line.
+    pub fn disassemble(&self, allocator: &Allocator, ptr: NodePtr) -> String {
+        self.puzzle(allocator, ptr).flat()
+    }
+
+    /// Disassembles the CLVM, breaking lists that don't fit within the width onto multiple lines.
+    pub fn pretty(&self, allocator: &Allocator, ptr: NodePtr, width: usize) -> String {
+        let mut output = String::new();
+        self.puzzle(allocator, ptr).pretty(&mut output, 0, width);
+        output
+    }
+
+    /// Disassembles a serialized program on a single line.
+    pub fn disassemble_program(&self, program: &Program) -> Result<String, DriverError> {
+        let mut allocator = Allocator::new();
+        let ptr = node_from_bytes(&mut allocator, program.as_ref())?;
+        Ok(self.disassemble(&allocator, ptr))
+    }
+
+    fn puzzle(&self, allocator: &Allocator, ptr: NodePtr) -> Doc {
+        let puzzle = Puzzle::parse(allocator, ptr);
+
+        let Some(known) = self.puzzles.get(&puzzle.mod_hash()) else {
+            return code(allocator, ptr);
+        };
+
+        let args = match puzzle {
+            Puzzle::Curried(curried) => curried_args(allocator, curried.args),
+            Puzzle::Raw(..) => Some(Vec::new()),
+        };
+
+        let Some(args) = args.filter(|args| args.len() == known.args.len()) else {
+            return code(allocator, ptr);
+        };
+
+        let mut items = vec![Doc::Atom("mod".to_string()), Doc::Atom(known.name.clone())];
+
+        for (arg, value) in known.args.iter().zip(args) {
+            let value = if arg.is_puzzle {
+                self.puzzle(allocator, value)
+            } else {
+                data(allocator, value)
+            };
+            items.push(Doc::List(vec![Doc::Atom(arg.name.clone()), value], None));
+        }
+
+        Doc::List(items, None)
+    }
+}
+
+impl SpendContext {
+    /// Disassembles the CLVM on a single line, annotating the puzzles in this SDK.
+    pub fn disassemble(&self, ptr: NodePtr) -> String {
+        Disassembler::default().disassemble(&self.allocator, ptr)
+    }
+
+    /// Disassembles the CLVM across multiple lines, annotating the puzzles in this SDK.
+    pub fn pretty(&self, ptr: NodePtr) -> String {
+        Disassembler::default().pretty(&self.allocator, ptr, 80)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Doc {
+    Atom(String),
+    List(Vec<Doc>, Option<Box<Doc>>),
+}
+
+impl Doc {
+    fn flat(&self) -> String {
+        match self {
+            Self::Atom(atom) => atom.clone(),
+            Self::List(items, tail) => {
+                let mut output = "(".to_string();
+                output.push_str(&items.iter().map(Self::flat).collect::<Vec<_>>().join(" "));
+                if let Some(tail) = tail {
+                    output.push_str(" . ");
+                    output.push_str(&tail.flat());
+                }
+                output.push(')');
+                output
+            }
+        }
+    }
+
+    fn pretty(&self, output: &mut String, indent: usize, width: usize) {
+        let flat = self.flat();
+
+        let Self::List(items, tail) = self else {
+            output.push_str(&flat);
+            return;
+        };
+
+        if indent + flat.len() <= width || items.len() < 2 {
+            output.push_str(&flat);
+            return;
+        }
+
+        output.push('(');
+        items[0].pretty(output, indent + 1, width);
+
+        for item in &items[1..] {
+            output.push('\n');
+            output.push_str(&" ".repeat(indent + 2));
+            item.pretty(output, indent + 2, width);
+        }
+
+        if let Some(tail) = tail {
+            output.push('\n');
+            output.push_str(&" ".repeat(indent + 2));
+            output.push_str(". ");
+            tail.pretty(output, indent + 4, width);
+        }
+
+        output.push(')');
+    }
+}
+
+/// Disassembles a program, where atoms at the start of a list are shown as operators.
+fn code(allocator: &Allocator, ptr: NodePtr) -> Doc {
+    let SExp::Pair(first, rest) = allocator.sexp(ptr) else {
+        return Doc::Atom(atom(allocator.atom(ptr).as_ref()));
+    };
+
+    let head = match allocator.sexp(first) {
+        SExp::Atom => {
+            let bytes = allocator.atom(first);
+            if bytes.as_ref() == [1] {
+                return list(allocator, Doc::Atom("q".to_string()), rest, data);
+            }
+            Doc::Atom(operator(bytes.as_ref()).map_or_else(|| atom(bytes.as_ref()), str::to_string))
+        }
+        SExp::Pair(..) => code(allocator, first),
+    };
+
+    list(allocator, head, rest, code)
+}
+
+/// Disassembles a value, without treating any atoms as operators.
+fn data(allocator: &Allocator, ptr: NodePtr) -> Doc {
+    match allocator.sexp(ptr) {
+        SExp::Atom => Doc::Atom(atom(allocator.atom(ptr).as_ref())),
+        SExp::Pair(first, rest) => list(allocator, data(allocator, first), rest, data),
+    }
+}
+
+fn list(
+    allocator: &Allocator,
+    head: Doc,
+    mut rest: NodePtr,
+    item: fn(&Allocator, NodePtr) -> Doc,
+) -> Doc {
+    let mut items = vec![head];
+
+    while let SExp::Pair(first, next) = allocator.sexp(rest) {
+        items.push(item(allocator, first));
+        rest = next;
+    }
+
+    let tail = if allocator.atom(rest).as_ref().is_empty() {
+        None
+    } else {
+        Some(Box::new(item(allocator, rest)))
+    };
+
+    Doc::List(items, tail)
+}
+
+fn atom(bytes: &[u8]) -> String {
+    if bytes.is_empty() {
+        return "()".to_string();
+    }
+
+    let canonical = !(bytes.len() > 1
+        && ((bytes[0] == 0 && bytes[1] & 0x80 == 0) || (bytes[0] == 0xff && bytes[1] & 0x80 != 0)))
+        && bytes != [0];
+
+    if bytes.len() <= 4 && canonical {
+        let mut value: i64 = if bytes[0] & 0x80 != 0 { -1 } else { 0 };
+        for byte in bytes {
+            value = (value << 8) | i64::from(*byte);
+        }
+        return value.to_string();
+    }
+
+    if bytes
+        .iter()
+        .all(|byte| (0x20..0x7f).contains(byte) && *byte != b'"')
+    {
+        return format!("\"{}\"", String::from_utf8_lossy(bytes));
+    }
+
+    format!("0x{}", hex::encode(bytes))
+}
+
+fn operator(bytes: &[u8]) -> Option<&'static str> {
+    let name = match bytes {
+        [2] => "a",
+        [3] => "i",
+        [4] => "c",
+        [5] => "f",
+        [6] => "r",
+        [7] => "l",
+        [8] => "x",
+        [9] => "=",
+        [10] => ">s",
+        [11] => "sha256",
+        [12] => "substr",
+        [13] => "strlen",
+        [14] => "concat",
+        [16] => "+",
+        [17] => "-",
+        [18] => "*",
+        [19] => "/",
+        [20] => "divmod",
+        [21] => ">",
+        [22] => "ash",
+        [23] => "lsh",
+        [24] => "logand",
+        [25] => "logior",
+        [26] => "logxor",
+        [27] => "lognot",
+        [29] => "point_add",
+        [30] => "pubkey_for_exp",
+        [32] => "not",
+        [33] => "any",
+        [34] => "all",
+        [36] => "softfork",
+        [48] => "coinid",
+        [49] => "g1_subtract",
+        [50] => "g1_multiply",
+        [51] => "g1_negate",
+        [52] => "g2_add",
+        [53] => "g2_subtract",
+        [54] => "g2_multiply",
+        [55] => "g2_negate",
+        [56] => "g1_map",
+        [57] => "g2_map",
+        [58] => "bls_pairing_identity",
+        [59] => "bls_verify",
+        [60] => "modpow",
+        [61] => "%",
+        [62] => "keccak256",
+        [0x13, 0xd6, 0x1f, 0x00] => "secp256k1_verify",
+        [0x1c, 0x3a, 0x8f, 0x00] => "secp256r1_verify",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// Parses the curried arguments, which are in the form `(c (q . arg) rest)` and terminated by `1`.
+fn curried_args(allocator: &Allocator, mut ptr: NodePtr) -> Option<Vec<NodePtr>> {
+    let mut args = Vec::new();
+
+    loop {
+        let SExp::Pair(op, rest) = allocator.sexp(ptr) else {
+            return (allocator.atom(ptr).as_ref() == [1]).then_some(args);
+        };
+
+        let SExp::Pair(quoted, rest) = allocator.sexp(rest) else {
+            return None;
+        };
+
+        let SExp::Pair(next, nil) = allocator.sexp(rest) else {
+            return None;
+        };
+
+        let SExp::Pair(q, value) = allocator.sexp(quoted) else {
+            return None;
+        };
+
+        if !is_atom(allocator, op, &[4])
+            || !is_atom(allocator, q, &[1])
+            || !is_atom(allocator, nil, &[])
+        {
+            return None;
+        }
+
+        args.push(value);
+        ptr = next;
+    }
+}
+
+fn is_atom(allocator: &Allocator, ptr: NodePtr, value: &[u8]) -> bool {
+    matches!(allocator.sexp(ptr), SExp::Atom) && allocator.atom(ptr).as_ref() == value
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_bls::PublicKey;
+    use chia_protocol::Bytes32;
+    use clvm_traits::{clvm_list, clvm_quote, ToClvm};
+
+    use crate::{CatLayer, Layer, StandardLayer};
+
+    use super::*;
+
+    #[test]
+    fn test_disassemble_code() -> anyhow::Result<()> {
+        let mut allocator = Allocator::new();
+
+        let ptr = clvm_list!(
+            2,
+            clvm_quote!(clvm_list!(1, -1, 300, "hello")),
+            clvm_list!(4, 2, ())
+        )
+        .to_clvm(&mut allocator)?;
+
+        assert_eq!(
+            Disassembler::empty().disassemble(&allocator, ptr),
+            "(a (q 1 -1 300 \"hello\") (c 2 ()))"
+        );
+
+        let ptr = (Bytes32::new([0xff; 32]), 5).to_clvm(&mut allocator)?;
+
+        assert_eq!(
+            Disassembler::empty().disassemble(&allocator, ptr),
+            format!("(0x{} . 5)", "ff".repeat(32))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_disassemble_known_puzzles() -> anyhow::Result<()> {
+        let mut ctx = SpendContext::new();
+
+        let public_key = PublicKey::default();
+        let asset_id = Bytes32::new([1; 32]);
+
+        let puzzle =
+            CatLayer::new(asset_id, StandardLayer::new(public_key)).construct_puzzle(&mut ctx)?;
+        let disassembly = ctx.disassemble(puzzle);
+
+        assert_eq!(
+            disassembly,
+            format!(
+                "(mod cat (mod_hash 0x{}) (asset_id 0x{}) (inner_puzzle (mod standard (synthetic_key 0x{}))))",
+                hex::encode(CAT_PUZZLE_HASH),
+                hex::encode(asset_id),
+                hex::encode(public_key.to_bytes())
+            )
+        );
+
+        let pretty = ctx.pretty(puzzle);
+        assert!(pretty.starts_with("(mod\n  cat\n  (mod_hash"));
+        assert_eq!(
+            pretty.split_whitespace().collect::<Vec<_>>(),
+            disassembly.split_whitespace().collect::<Vec<_>>()
+        );
+
+        let mut disassembler = Disassembler::empty();
+        let standard = StandardLayer::new(public_key).construct_puzzle(&mut ctx)?;
+        assert!(disassembler
+            .disassemble(&ctx.allocator, standard)
+            .starts_with("(a (q 2 "));
+
+        disassembler.register(
+            STANDARD_PUZZLE_HASH,
+            KnownPuzzle::new("p2", vec![CurriedArg::value("key")]),
+        );
+        assert!(disassembler
+            .disassemble(&ctx.allocator, standard)
+            .starts_with("(mod p2 (key 0x"));
+
+        Ok(())
+    }
+}
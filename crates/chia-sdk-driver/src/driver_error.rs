@@ -1,9 +1,19 @@
 use std::num::TryFromIntError;
 
+use chia_protocol::Bytes32;
+use chia_sdk_signer::SignerError;
 use clvm_traits::{FromClvmError, ToClvmError};
 use clvmr::reduction::EvalErr;
 use thiserror::Error;
 
+use crate::CoinSelectionError;
+
+#[cfg(feature = "client")]
+use chia_protocol::RejectStateReason;
+
+#[cfg(feature = "client")]
+use chia_sdk_client::ClientError;
+
 #[derive(Debug, Error)]
 pub enum DriverError {
     #[error("io error: {0}")]
@@ -45,6 +55,53 @@ pub enum DriverError {
     #[error("expected even oracle fee, but it was odd")]
     OddOracleFee,
 
+    #[error("coin selection error: {0}")]
+    CoinSelection(#[from] CoinSelectionError),
+
+    #[error("signer error: {0}")]
+    Signer(#[from] SignerError),
+
+    #[error("missing key for p2 puzzle hash {0}")]
+    MissingKey(Bytes32),
+
+    #[error("missing nft {0}")]
+    MissingNft(Bytes32),
+
+    #[error("missing attestation from recovery did {0}")]
+    MissingAttestation(Bytes32),
+
+    #[error("attestation from recovery did {0} doesn't match the recovery")]
+    InvalidAttestation(Bytes32),
+
+    /// The bytes of atoms allocated by a [`SpendContext`](crate::SpendContext) would exceed its memory limit.
+    /// Pairs don't count towards the limit.
+    #[error("memory limit of {0} bytes exceeded")]
+    MemoryLimit(usize),
+
+    #[cfg(feature = "client")]
+    #[error("client error: {0}")]
+    Client(#[from] ClientError),
+
+    #[cfg(feature = "client")]
+    #[error("coin state request rejected: {0:?}")]
+    RejectCoinState(RejectStateReason),
+
+    #[cfg(feature = "client")]
+    #[error("puzzle and solution request rejected for coin {0}")]
+    RejectPuzzleSolution(Bytes32),
+
+    #[error("missing coin state {0}")]
+    MissingCoinState(Bytes32),
+
+    #[error("coin {0} has not been spent")]
+    UnspentCoin(Bytes32),
+
+    #[error("coin {0} is not a cat2")]
+    NotCat(Bytes32),
+
+    #[error("expected asset id {expected}, but found {found}")]
+    AssetIdMismatch { expected: Bytes32, found: Bytes32 },
+
     #[error("custom driver error: {0}")]
     Custom(String),
 }
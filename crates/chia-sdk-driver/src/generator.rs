@@ -0,0 +1,131 @@
+use chia_consensus::{
+    consensus_constants::ConsensusConstants,
+    gen::solution_generator::{solution_generator, solution_generator_backrefs},
+};
+use chia_protocol::{CoinSpend, Program};
+
+use crate::DriverError;
+
+/// Serializes coin spends into a block generator, which is how they're included in a block.
+///
+/// If `backrefs` is set, repeated subtrees such as the puzzle reveals of CATs and NFTs are replaced
+/// with back-references. Consensus only allows this for block generators, so the coin spends in a
+/// spend bundle that's submitted to the mempool must still use the plain serialization.
+pub fn block_generator(coin_spends: &[CoinSpend], backrefs: bool) -> Result<Program, DriverError> {
+    let spends = coin_spends.iter().map(|coin_spend| {
+        (
+            coin_spend.coin,
+            coin_spend.puzzle_reveal.as_ref(),
+            coin_spend.solution.as_ref(),
+        )
+    });
+
+    let bytes = if backrefs {
+        solution_generator_backrefs(spends)?
+    } else {
+        solution_generator(spends)?
+    };
+
+    Ok(Program::from(bytes))
+}
+
+/// The size of the block generator for a list of coin spends, with and without back-references.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GeneratorSize {
+    /// The number of bytes in the plain block generator, without back-references.
+    pub uncompressed: usize,
+    /// The number of bytes in the block generator when it's serialized with back-references.
+    pub compressed: usize,
+}
+
+impl GeneratorSize {
+    /// Serializes the coin spends both ways and compares their size.
+    pub fn calculate(coin_spends: &[CoinSpend]) -> Result<Self, DriverError> {
+        Ok(Self {
+            uncompressed: block_generator(coin_spends, false)?.len(),
+            compressed: block_generator(coin_spends, true)?.len(),
+        })
+    }
+
+    /// The number of bytes that are saved by using back-references.
+    pub fn saved_bytes(&self) -> usize {
+        self.uncompressed.saturating_sub(self.compressed)
+    }
+
+    /// The byte cost that would be saved by using back-references when the spends are included in a block.
+    /// This doesn't change the cost of the spend bundle in the mempool, which is based on its plain serialization.
+    pub fn saved_byte_cost(&self, constants: &ConsensusConstants) -> u64 {
+        self.saved_bytes() as u64 * constants.cost_per_byte
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_bls::PublicKey;
+    use chia_protocol::{Bytes32, Coin};
+    use chia_puzzles::standard::StandardArgs;
+    use chia_sdk_types::{Conditions, MAINNET_CONSTANTS};
+    use clvm_utils::tree_hash;
+    use clvmr::{
+        serde::{node_from_bytes, node_from_bytes_backrefs},
+        Allocator,
+    };
+
+    use crate::{Cat, CatSpend, SpendContext, SpendWithConditions, StandardLayer};
+
+    use super::*;
+
+    #[test]
+    fn test_generator_size() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+
+        let p2 = StandardLayer::new(PublicKey::default());
+        let puzzle_hash = StandardArgs::curry_tree_hash(PublicKey::default()).into();
+
+        let cats: Vec<Cat> = (0..5)
+            .map(|i| {
+                let coin = Coin::new(Bytes32::new([i; 32]), puzzle_hash, 1);
+                Cat::new(coin, None, Bytes32::default(), puzzle_hash)
+            })
+            .collect();
+
+        let cat_spends = cats
+            .iter()
+            .map(|cat| {
+                let conditions = Conditions::new().create_coin(puzzle_hash, 1, vec![]);
+                Ok(CatSpend::new(
+                    *cat,
+                    p2.spend_with_conditions(ctx, conditions)?,
+                ))
+            })
+            .collect::<Result<Vec<_>, DriverError>>()?;
+        Cat::spend_all(ctx, &cat_spends)?;
+
+        let coin_spends = ctx.take();
+        let size = GeneratorSize::calculate(&coin_spends)?;
+
+        assert!(size.compressed * 3 < size.uncompressed);
+        assert_eq!(size.saved_bytes(), size.uncompressed - size.compressed);
+        assert_eq!(
+            size.saved_byte_cost(&MAINNET_CONSTANTS),
+            size.saved_bytes() as u64 * MAINNET_CONSTANTS.cost_per_byte
+        );
+
+        // Both generators represent the same program.
+        let mut allocator = Allocator::new();
+        let plain = node_from_bytes(
+            &mut allocator,
+            block_generator(&coin_spends, false)?.as_ref(),
+        )?;
+        let compressed = node_from_bytes_backrefs(
+            &mut allocator,
+            block_generator(&coin_spends, true)?.as_ref(),
+        )?;
+        assert_eq!(
+            tree_hash(&allocator, plain),
+            tree_hash(&allocator, compressed)
+        );
+
+        Ok(())
+    }
+}
@@ -92,3 +92,55 @@ where
         Ok(solution)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chia_protocol::Bytes32;
+    use chia_puzzles::cat::{CatArgs, CAT_PUZZLE, CAT_PUZZLE_HASH};
+    use clvm_utils::tree_hash;
+
+    use crate::{CatLayer, Layer, StandardLayer};
+
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Layer)]
+    #[layer(crate = crate, puzzle = CAT_PUZZLE, hash = CAT_PUZZLE_HASH, args = CatArgs<NodePtr>)]
+    struct DerivedCatLayer<I> {
+        mod_hash: Bytes32,
+        asset_id: Bytes32,
+        #[layer(inner)]
+        inner_puzzle: I,
+    }
+
+    #[test]
+    fn test_derived_inner_layer() -> anyhow::Result<()> {
+        let mut ctx = SpendContext::new();
+
+        let inner = StandardLayer::new(chia_bls::PublicKey::default());
+        let derived = DerivedCatLayer {
+            mod_hash: CAT_PUZZLE_HASH.into(),
+            asset_id: Bytes32::new([1; 32]),
+            inner_puzzle: inner,
+        };
+
+        let derived_ptr = derived.construct_puzzle(&mut ctx)?;
+        let expected_ptr = CatLayer::new(derived.asset_id, inner).construct_puzzle(&mut ctx)?;
+        assert_eq!(
+            tree_hash(&ctx.allocator, derived_ptr),
+            tree_hash(&ctx.allocator, expected_ptr)
+        );
+
+        let puzzle = Puzzle::parse(&ctx.allocator, expected_ptr);
+        let parsed = DerivedCatLayer::<StandardLayer>::parse_puzzle(&ctx.allocator, puzzle)?;
+        assert_eq!(parsed, Some(derived));
+
+        let inner_ptr = inner.construct_puzzle(&mut ctx)?;
+        let other = Puzzle::parse(&ctx.allocator, inner_ptr);
+        assert_eq!(
+            DerivedCatLayer::<StandardLayer>::parse_puzzle(&ctx.allocator, other)?,
+            None
+        );
+
+        Ok(())
+    }
+}
@@ -4,6 +4,7 @@ mod nft_ownership_layer;
 mod nft_state_layer;
 mod p2_delegated_conditions_layer;
 mod p2_one_of_many;
+mod revocation_layer;
 mod royalty_transfer_layer;
 mod settlement_layer;
 mod singleton_layer;
@@ -15,6 +16,7 @@ pub use nft_ownership_layer::*;
 pub use nft_state_layer::*;
 pub use p2_delegated_conditions_layer::*;
 pub use p2_one_of_many::*;
+pub use revocation_layer::*;
 pub use royalty_transfer_layer::*;
 pub use settlement_layer::*;
 pub use singleton_layer::*;
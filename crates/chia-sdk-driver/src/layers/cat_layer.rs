@@ -1,11 +1,56 @@
 use chia_protocol::Bytes32;
-use chia_puzzles::cat::{CatArgs, CatSolution, CAT_PUZZLE_HASH};
+use chia_puzzles::cat::{CatArgs, CatSolution, CAT_PUZZLE_HASH, CAT_PUZZLE_HASH_V1};
 use clvm_traits::FromClvm;
 use clvm_utils::{CurriedProgram, ToTreeHash, TreeHash};
 use clvmr::{Allocator, NodePtr};
 
 use crate::{DriverError, Layer, Puzzle, SpendContext};
 
+/// The version of the CAT standard that a coin uses.
+///
+/// CAT1 was deprecated in favor of CAT2 due to a vulnerability, and the tokens were reissued.
+/// Old CAT1 coins can still be parsed and spent, but the same asset id refers to a different token.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CatVersion {
+    V1,
+    #[default]
+    V2,
+}
+
+impl CatVersion {
+    /// Returns the version of the CAT puzzle with the given mod hash, if it's a CAT.
+    pub fn from_mod_hash(mod_hash: TreeHash) -> Option<Self> {
+        if mod_hash == CAT_PUZZLE_HASH {
+            Some(Self::V2)
+        } else if mod_hash == CAT_PUZZLE_HASH_V1 {
+            Some(Self::V1)
+        } else {
+            None
+        }
+    }
+
+    /// The tree hash of the uncurried CAT puzzle.
+    pub fn mod_hash(self) -> TreeHash {
+        match self {
+            Self::V1 => CAT_PUZZLE_HASH_V1,
+            Self::V2 => CAT_PUZZLE_HASH,
+        }
+    }
+
+    /// Calculates the puzzle hash of a CAT with this version.
+    pub fn curry_tree_hash(self, asset_id: Bytes32, inner_puzzle_hash: TreeHash) -> TreeHash {
+        CurriedProgram {
+            program: self.mod_hash(),
+            args: CatArgs {
+                mod_hash: self.mod_hash().into(),
+                asset_id,
+                inner_puzzle: inner_puzzle_hash,
+            },
+        }
+        .tree_hash()
+    }
+}
+
 /// The CAT [`Layer`] enforces restrictions on the supply of a token.
 /// Specifically, unless the TAIL program is run, the supply cannot change.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -14,6 +59,8 @@ pub struct CatLayer<I> {
     pub asset_id: Bytes32,
     /// The inner puzzle layer, commonly used for determining ownership.
     pub inner_puzzle: I,
+    /// The version of the CAT puzzle, which is CAT2 unless an old CAT1 coin is being parsed.
+    pub version: CatVersion,
 }
 
 impl<I> CatLayer<I> {
@@ -21,8 +68,16 @@ impl<I> CatLayer<I> {
         Self {
             asset_id,
             inner_puzzle,
+            version: CatVersion::V2,
         }
     }
+
+    /// Changes the version of the CAT puzzle.
+    #[must_use]
+    pub fn with_version(mut self, version: CatVersion) -> Self {
+        self.version = version;
+        self
+    }
 }
 
 impl<I> Layer for CatLayer<I>
@@ -36,13 +91,13 @@ where
             return Ok(None);
         };
 
-        if puzzle.mod_hash != CAT_PUZZLE_HASH {
+        let Some(version) = CatVersion::from_mod_hash(puzzle.mod_hash) else {
             return Ok(None);
-        }
+        };
 
         let args = CatArgs::<NodePtr>::from_clvm(allocator, puzzle.args)?;
 
-        if args.mod_hash != CAT_PUZZLE_HASH.into() {
+        if args.mod_hash != version.mod_hash().into() {
             return Err(DriverError::InvalidModHash);
         }
 
@@ -55,6 +110,7 @@ where
         Ok(Some(Self {
             asset_id: args.asset_id,
             inner_puzzle,
+            version,
         }))
     }
 
@@ -76,9 +132,17 @@ where
     }
 
     fn construct_puzzle(&self, ctx: &mut SpendContext) -> Result<NodePtr, DriverError> {
+        let program = match self.version {
+            CatVersion::V1 => ctx.cat_v1_puzzle()?,
+            CatVersion::V2 => ctx.cat_puzzle()?,
+        };
         let curried = CurriedProgram {
-            program: ctx.cat_puzzle()?,
-            args: CatArgs::new(self.asset_id, self.inner_puzzle.construct_puzzle(ctx)?),
+            program,
+            args: CatArgs {
+                mod_hash: self.version.mod_hash().into(),
+                asset_id: self.asset_id,
+                inner_puzzle: self.inner_puzzle.construct_puzzle(ctx)?,
+            },
         };
         ctx.alloc(&curried)
     }
@@ -109,7 +173,8 @@ where
 {
     fn tree_hash(&self) -> TreeHash {
         let inner_puzzle_hash = self.inner_puzzle.tree_hash();
-        CatArgs::curry_tree_hash(self.asset_id, inner_puzzle_hash)
+        self.version
+            .curry_tree_hash(self.asset_id, inner_puzzle_hash)
     }
 }
 
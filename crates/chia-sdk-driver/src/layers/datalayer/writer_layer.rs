@@ -3,9 +3,8 @@ use chia_sdk_types::Conditions;
 use clvm_traits::{clvm_quote, FromClvm, ToClvm};
 use clvm_utils::{CurriedProgram, ToTreeHash, TreeHash};
 use clvmr::{Allocator, NodePtr};
-use hex_literal::hex;
 
-use crate::{DriverError, Layer, Puzzle, Spend, SpendContext, StandardLayer};
+use crate::{load_puzzle, DriverError, Layer, Puzzle, Spend, SpendContext, StandardLayer};
 
 /// The Writer [`Layer`] removes an authorized puzzle's ability to change the list of authorized puzzles.
 /// It's typically used with [`DelegationLayer`](crate::DelegationLayer).
@@ -108,20 +107,14 @@ impl WriterLayer<StandardLayer> {
     }
 }
 
-pub const WRITER_FILTER_PUZZLE: [u8; 110] = hex!(
-    "
-    ff02ffff01ff02ff02ffff04ff02ffff04ffff02ff05ff0b80ff80808080ffff04ffff01ff02ffff
-    03ff05ffff01ff02ffff03ffff09ff11ffff0181f380ffff01ff0880ffff01ff04ff09ffff02ff02
-    ffff04ff02ffff04ff0dff808080808080ff0180ff8080ff0180ff018080
-    "
+load_puzzle!(
+    crate = crate,
+    name = WRITER_FILTER_PUZZLE,
+    path = "puzzles/writer_filter.clsp.hex",
+    hash = "407f70ea751c25052708219ae148b45db2f61af2287da53d600b2486f12b3ca6",
+    accessor = delegated_writer_filter,
 );
 
-pub const WRITER_FILTER_PUZZLE_HASH: TreeHash = TreeHash::new(hex!(
-    "
-    407f70ea751c25052708219ae148b45db2f61af2287da53d600b2486f12b3ca6
-    "
-));
-
 #[derive(ToClvm, FromClvm, Debug, Clone, Copy, PartialEq, Eq)]
 #[clvm(curry)]
 pub struct WriterLayerArgs<I> {
@@ -149,9 +142,3 @@ impl WriterLayerArgs<TreeHash> {
 pub struct WriterLayerSolution<I> {
     pub inner_solution: I,
 }
-
-impl SpendContext {
-    pub fn delegated_writer_filter(&mut self) -> Result<NodePtr, DriverError> {
-        self.puzzle(WRITER_FILTER_PUZZLE_HASH, &WRITER_FILTER_PUZZLE)
-    }
-}
@@ -0,0 +1,207 @@
+use chia_protocol::Bytes32;
+use clvm_traits::{FromClvm, ToClvm};
+use clvm_utils::{CurriedProgram, ToTreeHash, TreeHash};
+use clvmr::{Allocator, NodePtr};
+
+use crate::{load_puzzle, DriverError, Layer, Puzzle, SpendContext};
+
+/// The revocation [`Layer`] allows a coin to be spent either by its inner puzzle, or by a hidden puzzle
+/// that's typically controlled by the issuer of an asset. When spent by the inner puzzle, the revocation
+/// layer is kept on each created coin. When spent by the hidden puzzle, the output is left unchanged,
+/// which allows the asset to be revoked or clawed back.
+///
+/// Only the hashes of the puzzles are curried in, so the puzzle itself is revealed in the solution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RevocationLayer {
+    /// The puzzle hash that can revoke the coin.
+    pub hidden_puzzle_hash: Bytes32,
+    /// The puzzle hash of the inner puzzle, commonly used for determining ownership.
+    pub inner_puzzle_hash: Bytes32,
+}
+
+impl RevocationLayer {
+    pub fn new(hidden_puzzle_hash: Bytes32, inner_puzzle_hash: Bytes32) -> Self {
+        Self {
+            hidden_puzzle_hash,
+            inner_puzzle_hash,
+        }
+    }
+}
+
+impl Layer for RevocationLayer {
+    type Solution = RevocationSolution<NodePtr, NodePtr>;
+
+    fn parse_puzzle(allocator: &Allocator, puzzle: Puzzle) -> Result<Option<Self>, DriverError> {
+        let Some(puzzle) = puzzle.as_curried() else {
+            return Ok(None);
+        };
+
+        if puzzle.mod_hash != REVOCATION_LAYER_PUZZLE_HASH {
+            return Ok(None);
+        }
+
+        let args = RevocationArgs::from_clvm(allocator, puzzle.args)?;
+
+        if args.mod_hash != REVOCATION_LAYER_PUZZLE_HASH.into() {
+            return Err(DriverError::InvalidModHash);
+        }
+
+        Ok(Some(Self {
+            hidden_puzzle_hash: args.hidden_puzzle_hash,
+            inner_puzzle_hash: args.inner_puzzle_hash,
+        }))
+    }
+
+    fn parse_solution(
+        allocator: &Allocator,
+        solution: NodePtr,
+    ) -> Result<Self::Solution, DriverError> {
+        Ok(RevocationSolution::from_clvm(allocator, solution)?)
+    }
+
+    fn construct_puzzle(&self, ctx: &mut SpendContext) -> Result<NodePtr, DriverError> {
+        let curried = CurriedProgram {
+            program: ctx.revocation_layer_puzzle()?,
+            args: RevocationArgs::new(self.hidden_puzzle_hash, self.inner_puzzle_hash),
+        };
+        ctx.alloc(&curried)
+    }
+
+    fn construct_solution(
+        &self,
+        ctx: &mut SpendContext,
+        solution: Self::Solution,
+    ) -> Result<NodePtr, DriverError> {
+        ctx.alloc(&solution)
+    }
+}
+
+impl ToTreeHash for RevocationLayer {
+    fn tree_hash(&self) -> TreeHash {
+        RevocationArgs::curry_tree_hash(self.hidden_puzzle_hash, self.inner_puzzle_hash)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ToClvm, FromClvm)]
+#[clvm(curry)]
+pub struct RevocationArgs {
+    pub mod_hash: Bytes32,
+    pub hidden_puzzle_hash: Bytes32,
+    pub inner_puzzle_hash: Bytes32,
+}
+
+impl RevocationArgs {
+    pub fn new(hidden_puzzle_hash: Bytes32, inner_puzzle_hash: Bytes32) -> Self {
+        Self {
+            mod_hash: REVOCATION_LAYER_PUZZLE_HASH.into(),
+            hidden_puzzle_hash,
+            inner_puzzle_hash,
+        }
+    }
+
+    pub fn curry_tree_hash(hidden_puzzle_hash: Bytes32, inner_puzzle_hash: Bytes32) -> TreeHash {
+        CurriedProgram {
+            program: REVOCATION_LAYER_PUZZLE_HASH,
+            args: RevocationArgs::new(hidden_puzzle_hash, inner_puzzle_hash),
+        }
+        .tree_hash()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ToClvm, FromClvm)]
+#[clvm(list)]
+pub struct RevocationSolution<P, S> {
+    /// Whether the hidden puzzle is being revealed, rather than the inner puzzle.
+    pub hidden: bool,
+    pub puzzle: P,
+    pub solution: S,
+}
+
+impl<P, S> RevocationSolution<P, S> {
+    pub fn new(puzzle: P, solution: S) -> Self {
+        Self {
+            hidden: false,
+            puzzle,
+            solution,
+        }
+    }
+
+    pub fn revoke(puzzle: P, solution: S) -> Self {
+        Self {
+            hidden: true,
+            puzzle,
+            solution,
+        }
+    }
+}
+
+load_puzzle!(
+    crate = crate,
+    name = REVOCATION_LAYER_PUZZLE,
+    path = "puzzles/revocation_layer.clsp.hex",
+    hash = "a7bc1626972e59f9c6fac166d6d2b10cec9d314836aa7dee88480eb9883156dd",
+    accessor = revocation_layer_puzzle,
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_revocation_layer() -> anyhow::Result<()> {
+        let mut ctx = SpendContext::new();
+
+        let layer = RevocationLayer::new(Bytes32::new([1; 32]), Bytes32::new([2; 32]));
+        let ptr = layer.construct_puzzle(&mut ctx)?;
+
+        let puzzle = Puzzle::parse(&ctx.allocator, ptr);
+        let roundtrip = RevocationLayer::parse_puzzle(&ctx.allocator, puzzle)?
+            .expect("invalid revocation layer");
+
+        assert_eq!(roundtrip, layer);
+        assert_eq!(ctx.tree_hash(ptr), layer.tree_hash());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_revocation_layer_outputs() -> anyhow::Result<()> {
+        let mut ctx = SpendContext::new();
+
+        let hidden_puzzle = ctx.alloc(&1)?;
+        let inner_puzzle = ctx.alloc(&1)?;
+        let hidden_puzzle_hash = ctx.tree_hash(hidden_puzzle).into();
+        let layer = RevocationLayer::new(hidden_puzzle_hash, ctx.tree_hash(inner_puzzle).into());
+
+        let puzzle_hash = Bytes32::new([3; 32]);
+        let conditions = ctx.alloc(&[chia_sdk_types::CreateCoin::new(
+            puzzle_hash,
+            1,
+            vec![puzzle_hash.into()],
+        )])?;
+        let puzzle = layer.construct_puzzle(&mut ctx)?;
+
+        // The inner puzzle's outputs are wrapped in the revocation layer.
+        let solution = ctx.alloc(&RevocationSolution::new(inner_puzzle, conditions))?;
+        let output = ctx.run(puzzle, solution)?;
+        let [create_coin] = ctx.extract::<[chia_sdk_types::CreateCoin; 1]>(output)?;
+        assert_eq!(
+            create_coin.puzzle_hash,
+            RevocationArgs::curry_tree_hash(hidden_puzzle_hash, puzzle_hash).into()
+        );
+        assert_eq!(create_coin.memos, vec![puzzle_hash.into()]);
+
+        // The hidden puzzle's outputs are left unchanged.
+        let solution = ctx.alloc(&RevocationSolution::revoke(hidden_puzzle, conditions))?;
+        let output = ctx.run(puzzle, solution)?;
+        let [create_coin] = ctx.extract::<[chia_sdk_types::CreateCoin; 1]>(output)?;
+        assert_eq!(create_coin.puzzle_hash, puzzle_hash);
+
+        // Any other puzzle is rejected.
+        let other_puzzle = ctx.alloc(&(1, ()))?;
+        let solution = ctx.alloc(&RevocationSolution::new(other_puzzle, conditions))?;
+        assert!(ctx.run(puzzle, solution).is_err());
+
+        Ok(())
+    }
+}
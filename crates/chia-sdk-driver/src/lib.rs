@@ -1,6 +1,11 @@
 #![doc = include_str!("../docs.md")]
 
+mod coin_classifier;
+mod coin_selection;
+mod cost;
+mod disassembler;
 mod driver_error;
+mod generator;
 mod hashed_ptr;
 mod layer;
 mod layers;
@@ -8,11 +13,20 @@ mod merkle_tree;
 mod primitive;
 mod primitives;
 mod puzzle;
+mod puzzle_registry;
+mod simulation;
 mod spend;
+mod spend_bundle_validator;
 mod spend_context;
 mod spend_with_conditions;
+mod transaction_builder;
 
+pub use coin_classifier::*;
+pub use coin_selection::*;
+pub use cost::*;
+pub use disassembler::*;
 pub use driver_error::*;
+pub use generator::*;
 pub use hashed_ptr::*;
 pub use layer::*;
 pub use layers::*;
@@ -20,6 +34,19 @@ pub use merkle_tree::*;
 pub use primitive::*;
 pub use primitives::*;
 pub use puzzle::*;
+pub use puzzle_registry::*;
+pub use simulation::*;
 pub use spend::*;
+pub use spend_bundle_validator::*;
 pub use spend_context::*;
 pub use spend_with_conditions::*;
+pub use transaction_builder::*;
+
+pub use chia_sdk_derive::{load_puzzle, Layer};
+
+#[doc(hidden)]
+pub mod __private {
+    pub use clvm_traits::FromClvm;
+    pub use clvm_utils::{CurriedProgram, TreeHash};
+    pub use clvmr::{Allocator, NodePtr};
+}
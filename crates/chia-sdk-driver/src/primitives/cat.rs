@@ -1,7 +1,7 @@
 use chia_bls::PublicKey;
 use chia_protocol::{Bytes32, Coin};
 use chia_puzzles::{
-    cat::{CatArgs, CatSolution, EverythingWithSignatureTailArgs, GenesisByCoinIdTailArgs},
+    cat::{CatSolution, EverythingWithSignatureTailArgs, GenesisByCoinIdTailArgs},
     CoinProof, LineageProof,
 };
 use chia_sdk_types::{run_puzzle, Condition, Conditions, CreateCoin};
@@ -9,13 +9,18 @@ use clvm_traits::{clvm_quote, FromClvm};
 use clvm_utils::CurriedProgram;
 use clvmr::{Allocator, NodePtr};
 
-use crate::{CatLayer, DriverError, Layer, Primitive, Puzzle, Spend, SpendContext};
+use crate::{CatLayer, CatVersion, DriverError, Layer, Primitive, Puzzle, Spend, SpendContext};
 
 mod cat_spend;
 mod single_cat_spend;
+mod tails;
+
+#[cfg(feature = "client")]
+mod lineage;
 
 pub use cat_spend::*;
 pub use single_cat_spend::*;
+pub use tails::*;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Cat {
@@ -23,6 +28,8 @@ pub struct Cat {
     pub lineage_proof: Option<LineageProof>,
     pub asset_id: Bytes32,
     pub p2_puzzle_hash: Bytes32,
+    /// The version of the CAT puzzle. CAT1 coins can be spent, but their children are also CAT1.
+    pub version: CatVersion,
 }
 
 impl Cat {
@@ -37,9 +44,17 @@ impl Cat {
             lineage_proof,
             asset_id,
             p2_puzzle_hash,
+            version: CatVersion::V2,
         }
     }
 
+    /// Changes the version of the CAT puzzle.
+    #[must_use]
+    pub fn with_version(mut self, version: CatVersion) -> Self {
+        self.version = version;
+        self
+    }
+
     pub fn single_issuance_eve(
         ctx: &mut SpendContext,
         parent_coin_id: Bytes32,
@@ -69,11 +84,31 @@ impl Cat {
         amount: u64,
         extra_conditions: Conditions,
     ) -> Result<(Conditions, Cat), DriverError> {
-        let everything_with_signature_ptr = ctx.everything_with_signature_tail_puzzle()?;
+        let tail = Self::multi_issuance_tail(ctx, public_key)?;
 
-        let tail = ctx.alloc(&CurriedProgram {
-            program: everything_with_signature_ptr,
-            args: EverythingWithSignatureTailArgs::new(public_key),
+        Self::create_and_spend_eve(
+            ctx,
+            parent_coin_id,
+            ctx.tree_hash(tail).into(),
+            amount,
+            extra_conditions.run_cat_tail(tail, NodePtr::NIL),
+        )
+    }
+
+    /// Issues a CAT with the delegated TAIL. The delegated puzzle is run in place of the TAIL,
+    /// and must be signed with the public key using `AGG_SIG_UNSAFE`.
+    pub fn delegated_issuance_eve(
+        ctx: &mut SpendContext,
+        parent_coin_id: Bytes32,
+        public_key: PublicKey,
+        amount: u64,
+        delegated_spend: Spend,
+        extra_conditions: Conditions,
+    ) -> Result<(Conditions, Cat), DriverError> {
+        let tail = Self::delegated_tail(ctx, public_key)?;
+        let tail_solution = ctx.alloc(&DelegatedTailSolution {
+            delegated_puzzle: delegated_spend.puzzle,
+            delegated_solution: delegated_spend.solution,
         })?;
 
         Self::create_and_spend_eve(
@@ -81,10 +116,66 @@ impl Cat {
             parent_coin_id,
             ctx.tree_hash(tail).into(),
             amount,
-            extra_conditions.run_cat_tail(tail, NodePtr::NIL),
+            extra_conditions.run_cat_tail(tail, tail_solution),
+        )
+    }
+
+    /// Issues a CAT with the genesis by puzzle hash TAIL, which is unique to the puzzle hash of the parent coin.
+    /// The supply can't be changed after the eve spend.
+    pub fn genesis_by_puzzle_hash_eve(
+        ctx: &mut SpendContext,
+        parent_coin: Coin,
+        amount: u64,
+        extra_conditions: Conditions,
+    ) -> Result<(Conditions, Cat), DriverError> {
+        let genesis_by_puzzle_hash_ptr = ctx.genesis_by_puzzle_hash_tail_puzzle()?;
+
+        let tail = ctx.alloc(&CurriedProgram {
+            program: genesis_by_puzzle_hash_ptr,
+            args: GenesisByPuzzleHashTailArgs::new(parent_coin.puzzle_hash),
+        })?;
+        let tail_solution = ctx.alloc(&GenesisByPuzzleHashTailSolution {
+            parent_parent_coin_info: parent_coin.parent_coin_info,
+            parent_amount: parent_coin.amount,
+        })?;
+
+        Self::create_and_spend_eve(
+            ctx,
+            parent_coin.coin_id(),
+            ctx.tree_hash(tail).into(),
+            amount,
+            extra_conditions.run_cat_tail(tail, tail_solution),
         )
     }
 
+    /// Allocates the multi-issuance TAIL for a public key. It can be revealed with a signature
+    /// to issue more of the CAT or melt it, by spending a CAT with [`CatSpend::with_extra_delta`].
+    pub fn multi_issuance_tail(
+        ctx: &mut SpendContext,
+        public_key: PublicKey,
+    ) -> Result<NodePtr, DriverError> {
+        let everything_with_signature_ptr = ctx.everything_with_signature_tail_puzzle()?;
+
+        ctx.alloc(&CurriedProgram {
+            program: everything_with_signature_ptr,
+            args: EverythingWithSignatureTailArgs::new(public_key),
+        })
+    }
+
+    /// Allocates the delegated TAIL for a public key. Like the multi-issuance TAIL, it can be revealed
+    /// to issue more of the CAT or melt it, but the rules are decided by the delegated puzzle.
+    pub fn delegated_tail(
+        ctx: &mut SpendContext,
+        public_key: PublicKey,
+    ) -> Result<NodePtr, DriverError> {
+        let delegated_tail_ptr = ctx.delegated_tail_puzzle()?;
+
+        ctx.alloc(&CurriedProgram {
+            program: delegated_tail_ptr,
+            args: DelegatedTailArgs::new(public_key),
+        })
+    }
+
     /// Creates and spends an eve CAT with the provided conditions.
     /// To issue the CAT, you will need to reveal the TAIL puzzle and solution.
     /// This can be done with the [`RunCatTail`] condition.
@@ -148,7 +239,7 @@ impl Cat {
                 .filter_map(|ptr| ctx.extract::<CreateCoin>(ptr).ok());
 
             let delta = create_coins.fold(
-                i128::from(cat.coin.amount) - i128::from(*extra_delta),
+                i128::from(cat.coin.amount) + i128::from(*extra_delta),
                 |delta, create_coin| delta - i128::from(create_coin.amount),
             );
 
@@ -180,7 +271,8 @@ impl Cat {
 
     /// Creates a coin spend for this CAT.
     pub fn spend(&self, ctx: &mut SpendContext, spend: SingleCatSpend) -> Result<(), DriverError> {
-        let cat_layer = CatLayer::new(self.asset_id, spend.inner_spend.puzzle);
+        let cat_layer =
+            CatLayer::new(self.asset_id, spend.inner_spend.puzzle).with_version(self.version);
 
         let puzzle = cat_layer.construct_puzzle(ctx)?;
         let solution = cat_layer.construct_solution(
@@ -211,39 +303,26 @@ impl Cat {
     /// Creates a wrapped spendable CAT for a given output.
     #[must_use]
     pub fn wrapped_child(&self, p2_puzzle_hash: Bytes32, amount: u64) -> Self {
-        let puzzle_hash = CatArgs::curry_tree_hash(self.asset_id, p2_puzzle_hash.into());
+        let puzzle_hash = self
+            .version
+            .curry_tree_hash(self.asset_id, p2_puzzle_hash.into());
         Self {
             coin: Coin::new(self.coin.coin_id(), puzzle_hash.into(), amount),
             lineage_proof: Some(self.child_lineage_proof()),
             asset_id: self.asset_id,
             p2_puzzle_hash,
+            version: self.version,
         }
     }
-}
 
-impl Primitive for Cat {
-    fn from_parent_spend(
-        allocator: &mut Allocator,
+    /// Finds the child of a parent CAT spend, given the output conditions of its inner puzzle.
+    /// This allows the inner puzzle to be run once and reused.
+    pub(crate) fn child_from_conditions(
         parent_coin: Coin,
-        parent_puzzle: Puzzle,
-        parent_solution: NodePtr,
+        parent_layer: &CatLayer<Puzzle>,
+        conditions: Vec<Condition>,
         coin: Coin,
-    ) -> Result<Option<Self>, DriverError>
-    where
-        Self: Sized,
-    {
-        let Some(parent_layer) = CatLayer::<Puzzle>::parse_puzzle(allocator, parent_puzzle)? else {
-            return Ok(None);
-        };
-        let parent_solution = CatLayer::<Puzzle>::parse_solution(allocator, parent_solution)?;
-
-        let output = run_puzzle(
-            allocator,
-            parent_layer.inner_puzzle.ptr(),
-            parent_solution.inner_puzzle_solution,
-        )?;
-        let conditions = Vec::<Condition>::from_clvm(allocator, output)?;
-
+    ) -> Result<Self, DriverError> {
         let p2_puzzle_hash = conditions
             .into_iter()
             .filter_map(Condition::into_create_coin)
@@ -255,8 +334,9 @@ impl Primitive for Cat {
 
                 // Calculate what the wrapped puzzle hash would be for the created coin.
                 // This is because we're running the inner layer.
-                let wrapped_puzzle_hash =
-                    CatArgs::curry_tree_hash(parent_layer.asset_id, create_coin.puzzle_hash.into());
+                let wrapped_puzzle_hash = parent_layer
+                    .version
+                    .curry_tree_hash(parent_layer.asset_id, create_coin.puzzle_hash.into());
 
                 // If the puzzle hash doesn't match the coin, this isn't the correct puzzle_hash puzzle hash.
                 if wrapped_puzzle_hash != coin.puzzle_hash.into() {
@@ -271,7 +351,7 @@ impl Primitive for Cat {
             return Err(DriverError::MissingChild);
         };
 
-        Ok(Some(Self {
+        Ok(Self {
             coin,
             lineage_proof: Some(LineageProof {
                 parent_parent_coin_info: parent_coin.parent_coin_info,
@@ -280,7 +360,35 @@ impl Primitive for Cat {
             }),
             asset_id: parent_layer.asset_id,
             p2_puzzle_hash,
-        }))
+            version: parent_layer.version,
+        })
+    }
+}
+
+impl Primitive for Cat {
+    fn from_parent_spend(
+        allocator: &mut Allocator,
+        parent_coin: Coin,
+        parent_puzzle: Puzzle,
+        parent_solution: NodePtr,
+        coin: Coin,
+    ) -> Result<Option<Self>, DriverError>
+    where
+        Self: Sized,
+    {
+        let Some(parent_layer) = CatLayer::<Puzzle>::parse_puzzle(allocator, parent_puzzle)? else {
+            return Ok(None);
+        };
+        let parent_solution = CatLayer::<Puzzle>::parse_solution(allocator, parent_solution)?;
+
+        let output = run_puzzle(
+            allocator,
+            parent_layer.inner_puzzle.ptr(),
+            parent_solution.inner_puzzle_solution,
+        )?;
+        let conditions = Vec::<Condition>::from_clvm(allocator, output)?;
+
+        Self::child_from_conditions(parent_coin, &parent_layer, conditions, coin).map(Some)
     }
 }
 
@@ -291,6 +399,8 @@ mod tests {
     use chia_sdk_test::{Simulator, SimulatorError};
     use rstest::rstest;
 
+    use clvm_traits::ToClvm;
+
     use crate::{SpendWithConditions, StandardLayer};
 
     use super::*;
@@ -548,4 +658,298 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_cat_v1() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1)?;
+        let p2 = StandardLayer::new(pk);
+
+        // This TAIL allows anything, since the CAT1 TAILs take a different solution.
+        let tail = ctx.alloc(&clvm_quote!(()))?;
+        let asset_id = ctx.tree_hash(tail).into();
+
+        let inner_puzzle = ctx.alloc(&clvm_quote!(Conditions::new()
+            .create_coin(puzzle_hash, 1, vec![puzzle_hash.into()])
+            .run_cat_tail(tail, NodePtr::NIL)))?;
+        let inner_puzzle_hash = ctx.tree_hash(inner_puzzle);
+        let eve_puzzle_hash = CatVersion::V1
+            .curry_tree_hash(asset_id, inner_puzzle_hash)
+            .into();
+
+        let eve = Cat::new(
+            Coin::new(coin.coin_id(), eve_puzzle_hash, 1),
+            None,
+            asset_id,
+            inner_puzzle_hash.into(),
+        )
+        .with_version(CatVersion::V1);
+
+        p2.spend(
+            ctx,
+            coin,
+            Conditions::new().create_coin(eve_puzzle_hash, 1, Vec::new()),
+        )?;
+        eve.spend(
+            ctx,
+            SingleCatSpend::eve(
+                eve.coin,
+                inner_puzzle_hash.into(),
+                Spend::new(inner_puzzle, NodePtr::NIL),
+            ),
+        )?;
+        sim.spend_coins(ctx.take(), &[sk.clone()])?;
+
+        // The child of the eve spend is parsed as a CAT1.
+        let child = eve.wrapped_child(puzzle_hash, 1);
+        let parent_puzzle = sim
+            .puzzle_reveal(eve.coin.coin_id())
+            .expect("missing puzzle")
+            .to_clvm(&mut ctx.allocator)?;
+        let parent_solution = sim
+            .solution(eve.coin.coin_id())
+            .expect("missing solution")
+            .to_clvm(&mut ctx.allocator)?;
+        let parent_puzzle = Puzzle::parse(&ctx.allocator, parent_puzzle);
+        let parsed = Cat::from_parent_spend(
+            &mut ctx.allocator,
+            eve.coin,
+            parent_puzzle,
+            parent_solution,
+            child.coin,
+        )?
+        .expect("not a CAT");
+        assert_eq!(parsed, child);
+        assert_eq!(parsed.version, CatVersion::V1);
+
+        // CAT1 coins can still be spent, for example to move them to a burn address.
+        let burn_puzzle_hash = Bytes32::new([0; 32]);
+        let inner_spend = p2.spend_with_conditions(
+            ctx,
+            Conditions::new().create_coin(burn_puzzle_hash, 1, Vec::new()),
+        )?;
+        Cat::spend_all(ctx, &[CatSpend::new(child, inner_spend)])?;
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        assert!(sim
+            .coin_state(child.wrapped_child(burn_puzzle_hash, 1).coin.coin_id())
+            .is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cat_melt_multiple_coins() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(300)?;
+        let p2 = StandardLayer::new(pk);
+
+        let conditions = Conditions::new()
+            .create_coin(puzzle_hash, 100, vec![puzzle_hash.into()])
+            .create_coin(puzzle_hash, 200, vec![puzzle_hash.into()]);
+        let (issue_cat, cat) = Cat::multi_issuance_eve(ctx, coin.coin_id(), pk, 300, conditions)?;
+        p2.spend(ctx, coin, issue_cat)?;
+        sim.spend_coins(ctx.take(), &[sk.clone()])?;
+
+        let tail = Cat::multi_issuance_tail(ctx, pk)?;
+
+        // The first coin is melted entirely, and the second is melted by 50.
+        let cat_spends = [
+            CatSpend::with_extra_delta(
+                cat.wrapped_child(puzzle_hash, 100),
+                p2.spend_with_conditions(ctx, Conditions::new().run_cat_tail(tail, NodePtr::NIL))?,
+                -150,
+            ),
+            CatSpend::new(
+                cat.wrapped_child(puzzle_hash, 200),
+                p2.spend_with_conditions(
+                    ctx,
+                    Conditions::new().create_coin(puzzle_hash, 150, vec![puzzle_hash.into()]),
+                )?,
+            ),
+        ];
+
+        Cat::spend_all(ctx, &cat_spends)?;
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        let child = cat
+            .wrapped_child(puzzle_hash, 200)
+            .wrapped_child(puzzle_hash, 150);
+        assert!(sim.coin_state(child.coin.coin_id()).is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_multi_issuance_reissue() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1000)?;
+        let xch = sim.new_coin(puzzle_hash, 500);
+        let p2 = StandardLayer::new(pk);
+
+        let conditions = Conditions::new().create_coin(puzzle_hash, 1000, vec![puzzle_hash.into()]);
+        let (issue_cat, cat) = Cat::multi_issuance_eve(ctx, coin.coin_id(), pk, 1000, conditions)?;
+        p2.spend(ctx, coin, issue_cat)?;
+        sim.spend_coins(ctx.take(), &[sk.clone()])?;
+
+        // The value of the new CAT supply comes from an XCH coin.
+        let tail = Cat::multi_issuance_tail(ctx, pk)?;
+        let cat = cat.wrapped_child(puzzle_hash, 1000);
+
+        let cat_spend = CatSpend::with_extra_delta(
+            cat,
+            p2.spend_with_conditions(
+                ctx,
+                Conditions::new()
+                    .create_coin(puzzle_hash, 1500, vec![puzzle_hash.into()])
+                    .run_cat_tail(tail, NodePtr::NIL),
+            )?,
+            500,
+        );
+
+        Cat::spend_all(ctx, &[cat_spend])?;
+        p2.spend(
+            ctx,
+            xch,
+            Conditions::new().assert_concurrent_spend(cat.coin.coin_id()),
+        )?;
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        assert!(sim
+            .coin_state(cat.wrapped_child(puzzle_hash, 1500).coin.coin_id())
+            .is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_delegated_tail() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1000)?;
+        let xch = sim.new_coin(puzzle_hash, 500);
+        let p2 = StandardLayer::new(pk);
+
+        // This delegated puzzle allows any change in supply, so it shouldn't be reused in practice.
+        let delegated_puzzle = ctx.alloc(&clvm_quote!(()))?;
+        let delegated_spend = Spend::new(delegated_puzzle, NodePtr::NIL);
+
+        let (issue_cat, cat) = Cat::delegated_issuance_eve(
+            ctx,
+            coin.coin_id(),
+            pk,
+            1000,
+            delegated_spend,
+            Conditions::new().create_coin(puzzle_hash, 1000, vec![puzzle_hash.into()]),
+        )?;
+        p2.spend(ctx, coin, issue_cat)?;
+        sim.spend_coins(ctx.take(), &[sk.clone()])?;
+
+        let tail = Cat::delegated_tail(ctx, pk)?;
+        assert_eq!(cat.asset_id, DelegatedTailArgs::curry_tree_hash(pk).into());
+        assert_eq!(ctx.tree_hash(tail), DelegatedTailArgs::curry_tree_hash(pk));
+
+        let tail_solution = ctx.alloc(&DelegatedTailSolution {
+            delegated_puzzle,
+            delegated_solution: NodePtr::NIL,
+        })?;
+
+        // Reissue 500 more, using the value of an XCH coin.
+        let cat = cat.wrapped_child(puzzle_hash, 1000);
+        let cat_spend = CatSpend::with_extra_delta(
+            cat,
+            p2.spend_with_conditions(
+                ctx,
+                Conditions::new()
+                    .create_coin(puzzle_hash, 1500, vec![puzzle_hash.into()])
+                    .run_cat_tail(tail, tail_solution),
+            )?,
+            500,
+        );
+        Cat::spend_all(ctx, &[cat_spend])?;
+        p2.spend(
+            ctx,
+            xch,
+            Conditions::new().assert_concurrent_spend(cat.coin.coin_id()),
+        )?;
+        sim.spend_coins(ctx.take(), &[sk.clone()])?;
+
+        // Melt 1000 of it back into XCH, which is claimed by another coin spend.
+        let xch = sim.new_coin(puzzle_hash, 1);
+        let cat = cat.wrapped_child(puzzle_hash, 1500);
+        let cat_spend = CatSpend::with_extra_delta(
+            cat,
+            p2.spend_with_conditions(
+                ctx,
+                Conditions::new()
+                    .create_coin(puzzle_hash, 500, vec![puzzle_hash.into()])
+                    .run_cat_tail(tail, tail_solution),
+            )?,
+            -1000,
+        );
+        Cat::spend_all(ctx, &[cat_spend])?;
+        p2.spend(
+            ctx,
+            xch,
+            Conditions::new()
+                .create_coin(puzzle_hash, 1001, Vec::new())
+                .assert_concurrent_spend(cat.coin.coin_id()),
+        )?;
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        assert!(sim
+            .coin_state(Coin::new(xch.coin_id(), puzzle_hash, 1001).coin_id())
+            .is_some());
+
+        assert!(sim
+            .coin_state(cat.wrapped_child(puzzle_hash, 500).coin.coin_id())
+            .is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_genesis_by_puzzle_hash_tail() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1)?;
+        let other_coin = sim.new_coin(puzzle_hash, 2);
+        let p2 = StandardLayer::new(pk);
+
+        let (issue_cat, cat) = Cat::genesis_by_puzzle_hash_eve(
+            ctx,
+            coin,
+            1,
+            Conditions::new().create_coin(puzzle_hash, 1, vec![puzzle_hash.into()]),
+        )?;
+        p2.spend(ctx, coin, issue_cat)?;
+
+        // Any coin with the same puzzle hash can issue more of the same CAT.
+        let (issue_cat, other_cat) = Cat::genesis_by_puzzle_hash_eve(
+            ctx,
+            other_coin,
+            2,
+            Conditions::new().create_coin(puzzle_hash, 2, vec![puzzle_hash.into()]),
+        )?;
+        p2.spend(ctx, other_coin, issue_cat)?;
+
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        assert_eq!(
+            cat.asset_id,
+            GenesisByPuzzleHashTailArgs::curry_tree_hash(puzzle_hash).into()
+        );
+        assert_eq!(cat.asset_id, other_cat.asset_id);
+        assert!(sim
+            .coin_state(cat.wrapped_child(puzzle_hash, 1).coin.coin_id())
+            .is_some());
+        assert!(sim
+            .coin_state(other_cat.wrapped_child(puzzle_hash, 2).coin.coin_id())
+            .is_some());
+
+        Ok(())
+    }
 }
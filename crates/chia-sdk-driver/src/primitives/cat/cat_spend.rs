@@ -18,6 +18,8 @@ impl CatSpend {
         }
     }
 
+    /// Changes the supply of the CAT by the extra delta, which is negative when melting and positive when issuing.
+    /// The TAIL must be revealed by one of the CAT spends in the ring for this to be allowed.
     pub fn with_extra_delta(cat: Cat, inner_spend: Spend, extra_delta: i64) -> Self {
         Self {
             cat,
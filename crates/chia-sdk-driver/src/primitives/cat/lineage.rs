@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+
+use chia_protocol::{Bytes32, CoinState};
+use chia_sdk_client::Peer;
+use clvm_traits::ToClvm;
+use clvmr::Allocator;
+use futures_util::{stream, StreamExt, TryStreamExt};
+use indexmap::IndexSet;
+
+use crate::{Cat, CatVersion, DriverError, Primitive, Puzzle};
+
+/// The maximum number of coin ids in a single coin state request.
+const MAX_COIN_IDS_PER_REQUEST: usize = 1000;
+
+/// The maximum number of puzzle and solution requests that are sent to the peer at once.
+const MAX_CONCURRENT_REQUESTS: usize = 10;
+
+impl Cat {
+    /// Fetches the parent spend of a CAT coin from a peer, and returns a spendable [`Cat`]
+    /// with its lineage proof. This fails if the coin isn't a CAT2 of the expected asset id.
+    ///
+    /// The genesis challenge of the network is needed to request coin states from the start of the chain.
+    pub async fn fetch(
+        peer: &Peer,
+        coin_state: CoinState,
+        asset_id: Bytes32,
+        genesis_challenge: Bytes32,
+    ) -> Result<Self, DriverError> {
+        let mut cats = Self::fetch_many(peer, &[coin_state], asset_id, genesis_challenge).await?;
+        Ok(cats.remove(0))
+    }
+
+    /// Fetches the parent spends of many CAT coins of the same asset id at once.
+    /// The parent coin states are requested in batches, and a limited number of parent spends
+    /// are requested concurrently.
+    ///
+    /// The CATs are returned in the same order as the coin states.
+    pub async fn fetch_many(
+        peer: &Peer,
+        coin_states: &[CoinState],
+        asset_id: Bytes32,
+        genesis_challenge: Bytes32,
+    ) -> Result<Vec<Self>, DriverError> {
+        let parent_ids: Vec<Bytes32> = coin_states
+            .iter()
+            .map(|coin_state| coin_state.coin.parent_coin_info)
+            .collect::<IndexSet<_>>()
+            .into_iter()
+            .collect();
+
+        if parent_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut parent_states = HashMap::new();
+
+        for parent_ids in parent_ids.chunks(MAX_COIN_IDS_PER_REQUEST) {
+            let response = peer
+                .request_coin_state(parent_ids.to_vec(), None, genesis_challenge, false)
+                .await?
+                .map_err(|rejection| DriverError::RejectCoinState(rejection.reason))?;
+
+            parent_states.extend(
+                response
+                    .coin_states
+                    .into_iter()
+                    .map(|coin_state| (coin_state.coin.coin_id(), coin_state)),
+            );
+        }
+
+        let parent_spends: HashMap<Bytes32, _> = stream::iter(parent_ids)
+            .map(|parent_id| {
+                let parent_states = &parent_states;
+
+                async move {
+                    let parent_state: &CoinState = parent_states
+                        .get(&parent_id)
+                        .ok_or(DriverError::MissingCoinState(parent_id))?;
+
+                    let height = parent_state
+                        .spent_height
+                        .ok_or(DriverError::UnspentCoin(parent_id))?;
+
+                    let response = peer
+                        .request_puzzle_and_solution(parent_id, height)
+                        .await?
+                        .map_err(|_| DriverError::RejectPuzzleSolution(parent_id))?;
+
+                    Ok::<_, DriverError>((parent_id, (parent_state.coin, response)))
+                }
+            })
+            .buffer_unordered(MAX_CONCURRENT_REQUESTS)
+            .try_collect()
+            .await?;
+
+        let mut allocator = Allocator::new();
+        let mut cats = Vec::with_capacity(coin_states.len());
+
+        for coin_state in coin_states {
+            let coin = coin_state.coin;
+            let (parent_coin, response) = &parent_spends[&coin.parent_coin_info];
+
+            let parent_puzzle = response.puzzle.to_clvm(&mut allocator)?;
+            let parent_puzzle = Puzzle::parse(&allocator, parent_puzzle);
+            let parent_solution = response.solution.to_clvm(&mut allocator)?;
+
+            let Some(cat) = Cat::from_parent_spend(
+                &mut allocator,
+                *parent_coin,
+                parent_puzzle,
+                parent_solution,
+                coin,
+            )?
+            else {
+                return Err(DriverError::NotCat(coin.coin_id()));
+            };
+
+            if cat.version != CatVersion::V2 {
+                return Err(DriverError::NotCat(coin.coin_id()));
+            }
+
+            if cat.asset_id != asset_id {
+                return Err(DriverError::AssetIdMismatch {
+                    expected: asset_id,
+                    found: cat.asset_id,
+                });
+            }
+
+            cats.push(cat);
+        }
+
+        Ok(cats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_protocol::Coin;
+    use chia_sdk_test::{test_secret_key, test_transaction, PeerSimulator};
+    use chia_sdk_types::Conditions;
+    use clvm_traits::clvm_quote;
+    use clvm_utils::ToTreeHash;
+    use clvmr::NodePtr;
+
+    use crate::{
+        CatSpend, SingleCatSpend, Spend, SpendContext, SpendWithConditions, StandardLayer,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_fetch_cats() -> anyhow::Result<()> {
+        let sim = PeerSimulator::new().await?;
+        let peer = sim.connect().await?;
+        let genesis_challenge = sim.config().constants.genesis_challenge;
+
+        let ctx = &mut SpendContext::new();
+        let sk = test_secret_key()?;
+        let pk = sk.public_key();
+        let p2 = StandardLayer::new(pk);
+        let puzzle_hash = p2.tree_hash().into();
+
+        let coin = sim.mint_coin(puzzle_hash, 3).await;
+
+        let (issue_cat, cats) = Cat::single_issuance_eve(
+            ctx,
+            coin.coin_id(),
+            3,
+            Conditions::new()
+                .create_coin(puzzle_hash, 1, vec![puzzle_hash.into()])
+                .create_coin(puzzle_hash, 2, vec![puzzle_hash.into()]),
+        )?;
+        p2.spend(ctx, coin, issue_cat)?;
+
+        test_transaction(&peer, ctx.take(), &[sk.clone()], &sim.config().constants).await;
+
+        let expected = vec![
+            cats.wrapped_child(puzzle_hash, 1),
+            cats.wrapped_child(puzzle_hash, 2),
+        ];
+
+        let mut coin_states = Vec::new();
+        for cat in &expected {
+            coin_states.push(
+                sim.coin_state(cat.coin.coin_id())
+                    .await
+                    .expect("missing coin state"),
+            );
+        }
+
+        let fetched =
+            Cat::fetch_many(&peer, &coin_states, cats.asset_id, genesis_challenge).await?;
+        assert_eq!(fetched, expected);
+
+        // The fetched CAT can be spent.
+        let cat = Cat::fetch(&peer, coin_states[0], cats.asset_id, genesis_challenge).await?;
+        let inner_spend = p2.spend_with_conditions(
+            ctx,
+            Conditions::new().create_coin(puzzle_hash, 1, vec![puzzle_hash.into()]),
+        )?;
+        Cat::spend_all(ctx, &[CatSpend::new(cat, inner_spend)])?;
+
+        test_transaction(&peer, ctx.take(), &[sk], &sim.config().constants).await;
+
+        // The wrong asset id is rejected.
+        assert!(matches!(
+            Cat::fetch(&peer, coin_states[1], Bytes32::default(), genesis_challenge).await,
+            Err(DriverError::AssetIdMismatch { .. })
+        ));
+
+        // The eve CAT was created by a spend that isn't a CAT, so its lineage can't be proven.
+        let eve_state = sim
+            .coin_state(cats.coin.coin_id())
+            .await
+            .expect("missing coin state");
+        assert!(matches!(
+            Cat::fetch(&peer, eve_state, cats.asset_id, genesis_challenge).await,
+            Err(DriverError::NotCat(..))
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fetch_cat1() -> anyhow::Result<()> {
+        let sim = PeerSimulator::new().await?;
+        let peer = sim.connect().await?;
+        let genesis_challenge = sim.config().constants.genesis_challenge;
+
+        let ctx = &mut SpendContext::new();
+        let sk = test_secret_key()?;
+        let p2 = StandardLayer::new(sk.public_key());
+        let puzzle_hash = p2.tree_hash().into();
+
+        let coin = sim.mint_coin(puzzle_hash, 1).await;
+
+        // This TAIL allows anything, since the CAT1 TAILs take a different solution.
+        let tail = ctx.alloc(&clvm_quote!(()))?;
+        let asset_id = ctx.tree_hash(tail).into();
+
+        let inner_puzzle = ctx.alloc(&clvm_quote!(Conditions::new()
+            .create_coin(puzzle_hash, 1, vec![puzzle_hash.into()])
+            .run_cat_tail(tail, NodePtr::NIL)))?;
+        let inner_puzzle_hash = ctx.tree_hash(inner_puzzle);
+        let eve_puzzle_hash = CatVersion::V1
+            .curry_tree_hash(asset_id, inner_puzzle_hash)
+            .into();
+
+        let eve = Cat::new(
+            Coin::new(coin.coin_id(), eve_puzzle_hash, 1),
+            None,
+            asset_id,
+            inner_puzzle_hash.into(),
+        )
+        .with_version(CatVersion::V1);
+
+        p2.spend(
+            ctx,
+            coin,
+            Conditions::new().create_coin(eve_puzzle_hash, 1, Vec::new()),
+        )?;
+        eve.spend(
+            ctx,
+            SingleCatSpend::eve(
+                eve.coin,
+                inner_puzzle_hash.into(),
+                Spend::new(inner_puzzle, NodePtr::NIL),
+            ),
+        )?;
+
+        test_transaction(&peer, ctx.take(), &[sk], &sim.config().constants).await;
+
+        // CAT1 coins aren't returned, even though their lineage can be proven.
+        let child = eve.wrapped_child(puzzle_hash, 1);
+        let child_state = sim
+            .coin_state(child.coin.coin_id())
+            .await
+            .expect("missing coin state");
+        assert!(matches!(
+            Cat::fetch(&peer, child_state, asset_id, genesis_challenge).await,
+            Err(DriverError::NotCat(..))
+        ));
+
+        Ok(())
+    }
+}
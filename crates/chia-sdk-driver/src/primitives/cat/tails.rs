@@ -0,0 +1,84 @@
+use chia_bls::PublicKey;
+use chia_protocol::Bytes32;
+use clvm_traits::{FromClvm, ToClvm};
+use clvm_utils::{CurriedProgram, ToTreeHash, TreeHash};
+
+use crate::load_puzzle;
+
+/// The delegated TAIL allows the owner of a key to sign a delegated puzzle, which is run in place of the TAIL.
+/// This allows the issuance rules to be decided later, such as for reissuance or melting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ToClvm, FromClvm)]
+#[clvm(curry)]
+pub struct DelegatedTailArgs {
+    pub public_key: PublicKey,
+}
+
+impl DelegatedTailArgs {
+    pub fn new(public_key: PublicKey) -> Self {
+        Self { public_key }
+    }
+
+    pub fn curry_tree_hash(public_key: PublicKey) -> TreeHash {
+        CurriedProgram {
+            program: DELEGATED_TAIL_PUZZLE_HASH,
+            args: DelegatedTailArgs::new(public_key),
+        }
+        .tree_hash()
+    }
+}
+
+/// The delegated puzzle is signed with `AGG_SIG_UNSAFE`, so the same delegated puzzle can be reused
+/// by anyone who has seen it. It should be restricted to a specific issuance, for example by asserting a coin id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ToClvm, FromClvm)]
+#[clvm(list)]
+pub struct DelegatedTailSolution<P, S> {
+    pub delegated_puzzle: P,
+    pub delegated_solution: S,
+}
+
+/// The genesis by puzzle hash TAIL allows a single issuance from a coin with a specific puzzle hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ToClvm, FromClvm)]
+#[clvm(curry)]
+pub struct GenesisByPuzzleHashTailArgs {
+    pub genesis_puzzle_hash: Bytes32,
+}
+
+impl GenesisByPuzzleHashTailArgs {
+    pub fn new(genesis_puzzle_hash: Bytes32) -> Self {
+        Self {
+            genesis_puzzle_hash,
+        }
+    }
+
+    pub fn curry_tree_hash(genesis_puzzle_hash: Bytes32) -> TreeHash {
+        CurriedProgram {
+            program: GENESIS_BY_PUZZLE_HASH_TAIL_PUZZLE_HASH,
+            args: GenesisByPuzzleHashTailArgs::new(genesis_puzzle_hash),
+        }
+        .tree_hash()
+    }
+}
+
+/// The parent of the eve CAT, which must have been created by the genesis puzzle hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ToClvm, FromClvm)]
+#[clvm(list)]
+pub struct GenesisByPuzzleHashTailSolution {
+    pub parent_parent_coin_info: Bytes32,
+    pub parent_amount: u64,
+}
+
+load_puzzle!(
+    crate = crate,
+    name = DELEGATED_TAIL_PUZZLE,
+    path = "puzzles/delegated_tail.clsp.hex",
+    hash = "a83b8b86c255830b8c11b57cff405b15efa08ada9cbeb7ba9e670a34696c04b5",
+    accessor = delegated_tail_puzzle,
+);
+
+load_puzzle!(
+    crate = crate,
+    name = GENESIS_BY_PUZZLE_HASH_TAIL_PUZZLE,
+    path = "puzzles/genesis_by_puzzle_hash_tail.clsp.hex",
+    hash = "a6347d5b25465beafd8931cfaa94d1feca6e380df0e069510fe399d763c963bb",
+    accessor = genesis_by_puzzle_hash_tail_puzzle,
+);
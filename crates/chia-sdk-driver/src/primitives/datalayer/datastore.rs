@@ -320,6 +320,29 @@ where
         let inner_output = run_puzzle(allocator, inner_puzzle, inner_solution)?;
         let inner_conditions = Vec::<Condition>::from_clvm(allocator, inner_output)?;
 
+        Self::child_from_conditions(
+            allocator,
+            cs.coin,
+            singleton_layer,
+            state_layer,
+            inner_solution,
+            inner_conditions,
+            parent_delegated_puzzles,
+        )
+        .map(Some)
+    }
+
+    /// Finds the child of a parent [`DataStore`] spend, given the output conditions
+    /// of the inner puzzle of its state layer. This allows the inner puzzle to be run once and reused.
+    pub(crate) fn child_from_conditions(
+        allocator: &mut Allocator,
+        parent_coin: Coin,
+        singleton_layer: SingletonLayer<Puzzle>,
+        state_layer: NftStateLayer<M, Puzzle>,
+        inner_solution: NodePtr,
+        inner_conditions: Vec<Condition>,
+        parent_delegated_puzzles: &[DelegatedPuzzle],
+    ) -> Result<Self, DriverError> {
         let mut inner_create_coin_condition = None;
         let mut inner_new_metadata_condition = None;
 
@@ -369,7 +392,7 @@ where
         );
 
         let new_coin = Coin {
-            parent_coin_info: cs.coin.coin_id(),
+            parent_coin_info: parent_coin.coin_id(),
             puzzle_hash: new_puzzle_hash.into(),
             amount: inner_create_coin_condition.amount,
         };
@@ -378,14 +401,14 @@ where
         // and delegated puzzles have been updated (we can rebuild the list from memos)
         if inner_create_coin_condition.memos.len() > 1 {
             // keep in mind that there's always the launcher id memo being added
-            return Ok(Some(Self::build_datastore(
+            return Self::build_datastore(
                 new_coin,
                 singleton_layer.launcher_id,
-                Proof::Lineage(singleton_layer.lineage_proof(cs.coin)),
+                Proof::Lineage(singleton_layer.lineage_proof(parent_coin)),
                 new_metadata,
                 state_layer.inner_puzzle.tree_hash().into(),
                 inner_create_coin_condition.memos,
-            )?));
+            );
         }
 
         let mut owner_puzzle_hash: Bytes32 = state_layer.inner_puzzle.tree_hash().into();
@@ -426,16 +449,16 @@ where
             let Some(odd_create_coin) = odd_create_coin else {
                 // no CREATE_COIN was created by the innermost puzzle
                 // delegation layer therefore added one (assuming the spend is valid)]
-                return Ok(Some(DataStore {
+                return Ok(DataStore {
                     coin: new_coin,
-                    proof: Proof::Lineage(singleton_layer.lineage_proof(cs.coin)),
+                    proof: Proof::Lineage(singleton_layer.lineage_proof(parent_coin)),
                     info: DataStoreInfo {
                         launcher_id: singleton_layer.launcher_id,
                         metadata: new_metadata,
                         owner_puzzle_hash,
                         delegated_puzzles: parent_delegated_puzzles.to_vec(),
                     },
-                }));
+                });
             };
 
             let odd_create_coin = odd_create_coin?;
@@ -448,16 +471,16 @@ where
 
                 if create_coin.puzzle_hash == prev_deleg_layer_ph.into() {
                     // owner is re-creating the delegation layer with the same options
-                    return Ok(Some(DataStore {
+                    return Ok(DataStore {
                         coin: new_coin,
-                        proof: Proof::Lineage(singleton_layer.lineage_proof(cs.coin)),
+                        proof: Proof::Lineage(singleton_layer.lineage_proof(parent_coin)),
                         info: DataStoreInfo {
                             launcher_id: singleton_layer.launcher_id,
                             metadata: new_metadata,
                             owner_puzzle_hash, // owner puzzle was ran
                             delegated_puzzles: parent_delegated_puzzles.to_vec(),
                         },
-                    }));
+                    });
                 }
 
                 // owner is exiting the delegation layer
@@ -466,16 +489,16 @@ where
         }
 
         // all methods exhausted; this coin doesn't seem to have a delegation layer
-        Ok(Some(DataStore {
+        Ok(DataStore {
             coin: new_coin,
-            proof: Proof::Lineage(singleton_layer.lineage_proof(cs.coin)),
+            proof: Proof::Lineage(singleton_layer.lineage_proof(parent_coin)),
             info: DataStoreInfo {
                 launcher_id: singleton_layer.launcher_id,
                 metadata: new_metadata,
                 owner_puzzle_hash,
                 delegated_puzzles: vec![],
             },
-        }))
+        })
     }
 }
 
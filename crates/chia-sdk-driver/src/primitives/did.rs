@@ -1,4 +1,4 @@
-use chia_protocol::Coin;
+use chia_protocol::{Bytes32, Coin};
 use chia_puzzles::{did::DidSolution, singleton::SingletonSolution, LineageProof, Proof};
 use chia_sdk_types::{run_puzzle, Condition, Conditions};
 use clvm_traits::{FromClvm, ToClvm};
@@ -10,9 +10,11 @@ use crate::{
     SpendWithConditions,
 };
 
+mod did_attestation;
 mod did_info;
 mod did_launcher;
 
+pub use did_attestation::*;
 pub use did_info::*;
 
 #[must_use]
@@ -97,36 +99,56 @@ where
         self.spend(ctx, inner_spend)
     }
 
-    /// Recreates this DID and outputs additional conditions via the inner puzzle.
-    pub fn update_with_metadata<I, N>(
+    /// Recreates this DID with new info, and outputs additional conditions via the inner puzzle.
+    /// The child coin is hinted to its p2 puzzle hash.
+    fn recreate<I, N>(
         self,
         ctx: &mut SpendContext,
         inner: &I,
-        metadata: N,
+        info: DidInfo<N>,
         extra_conditions: Conditions,
     ) -> Result<Did<N>, DriverError>
     where
         I: SpendWithConditions,
         M: ToTreeHash,
-        N: ToClvm<Allocator> + ToTreeHash + Clone,
+        N: ToTreeHash,
     {
-        let new_inner_puzzle_hash = self
-            .info
-            .clone()
-            .with_metadata(metadata.clone())
-            .inner_puzzle_hash();
-
         self.spend_with(
             ctx,
             inner,
             extra_conditions.create_coin(
-                new_inner_puzzle_hash.into(),
+                info.inner_puzzle_hash().into(),
                 self.coin.amount,
-                vec![self.info.p2_puzzle_hash.into()],
+                vec![info.p2_puzzle_hash.into()],
             ),
         )?;
 
-        Ok(self.wrapped_child().with_metadata(metadata))
+        Ok(Did {
+            coin: Coin::new(
+                self.coin.coin_id(),
+                info.puzzle_hash().into(),
+                self.coin.amount,
+            ),
+            proof: Proof::Lineage(self.child_lineage_proof()),
+            info,
+        })
+    }
+
+    /// Recreates this DID and outputs additional conditions via the inner puzzle.
+    pub fn update_with_metadata<I, N>(
+        self,
+        ctx: &mut SpendContext,
+        inner: &I,
+        metadata: N,
+        extra_conditions: Conditions,
+    ) -> Result<Did<N>, DriverError>
+    where
+        I: SpendWithConditions,
+        M: ToTreeHash,
+        N: ToClvm<Allocator> + ToTreeHash + Clone,
+    {
+        let info = self.info.clone().with_metadata(metadata);
+        self.recreate(ctx, inner, info, extra_conditions)
     }
 
     /// Creates a new DID coin with the given metadata.
@@ -143,6 +165,91 @@ where
         let metadata = self.info.metadata.clone();
         self.update_with_metadata(ctx, inner, metadata, extra_conditions)
     }
+
+    /// Transfers this DID to a new p2 puzzle hash, keeping its recovery list and metadata.
+    pub fn transfer<I>(
+        self,
+        ctx: &mut SpendContext,
+        inner: &I,
+        p2_puzzle_hash: Bytes32,
+        extra_conditions: Conditions,
+    ) -> Result<Did<M>, DriverError>
+    where
+        M: ToTreeHash,
+        I: SpendWithConditions,
+    {
+        let mut info = self.info.clone();
+        info.p2_puzzle_hash = p2_puzzle_hash;
+        self.recreate(ctx, inner, info, extra_conditions)
+    }
+
+    /// Replaces the list of DIDs that can recover this DID, and the number of them required.
+    /// The recovery list hash is the tree hash of the list of recovery DID launcher ids,
+    /// or [`None`] to disable recovery.
+    pub fn update_recovery_list<I>(
+        self,
+        ctx: &mut SpendContext,
+        inner: &I,
+        recovery_list_hash: Option<Bytes32>,
+        num_verifications_required: u64,
+        extra_conditions: Conditions,
+    ) -> Result<Did<M>, DriverError>
+    where
+        M: ToTreeHash,
+        I: SpendWithConditions,
+    {
+        let mut info = self.info.clone();
+        info.recovery_list_hash = recovery_list_hash;
+        info.num_verifications_required = num_verifications_required;
+        self.recreate(ctx, inner, info, extra_conditions)
+    }
+
+    /// Finds the child of a parent DID spend, given the output conditions of its singleton inner puzzle.
+    /// This allows the inner puzzle to be run once and reused.
+    pub(crate) fn child_from_conditions(
+        allocator: &mut Allocator,
+        parent_coin: Coin,
+        launcher_id: Bytes32,
+        did_layer: DidLayer<M, Puzzle>,
+        conditions: Vec<Condition>,
+        coin: Coin,
+    ) -> Result<Self, DriverError> {
+        let Some(create_coin) = conditions
+            .into_iter()
+            .filter_map(Condition::into_create_coin)
+            .find(|create_coin| create_coin.amount % 2 == 1)
+        else {
+            return Err(DriverError::MissingChild);
+        };
+
+        let Some(hint) = create_coin
+            .memos
+            .into_iter()
+            .find_map(|memo| memo.try_into().ok())
+        else {
+            return Err(DriverError::MissingHint);
+        };
+
+        let metadata_ptr = did_layer.metadata.to_clvm(allocator)?;
+        let metadata_hash = tree_hash(allocator, metadata_ptr);
+        let did_layer_hashed = did_layer.clone().with_metadata(metadata_hash);
+
+        let parent_inner_puzzle_hash = did_layer_hashed.tree_hash().into();
+        let layers = SingletonLayer::new(launcher_id, did_layer);
+
+        let mut info = DidInfo::from_layers(layers);
+        info.p2_puzzle_hash = hint;
+
+        Ok(Self {
+            coin,
+            proof: Proof::Lineage(LineageProof {
+                parent_parent_coin_info: parent_coin.parent_coin_info,
+                parent_inner_puzzle_hash,
+                parent_amount: parent_coin.amount,
+            }),
+            info,
+        })
+    }
 }
 
 impl<M> Primitive for Did<M>
@@ -185,41 +292,15 @@ where
         )?;
         let conditions = Vec::<Condition>::from_clvm(allocator, output)?;
 
-        let Some(create_coin) = conditions
-            .into_iter()
-            .filter_map(Condition::into_create_coin)
-            .find(|create_coin| create_coin.amount % 2 == 1)
-        else {
-            return Err(DriverError::MissingChild);
-        };
-
-        let Some(hint) = create_coin
-            .memos
-            .into_iter()
-            .find_map(|memo| memo.try_into().ok())
-        else {
-            return Err(DriverError::MissingHint);
-        };
-
-        let metadata_ptr = did_layer.metadata.to_clvm(allocator)?;
-        let metadata_hash = tree_hash(allocator, metadata_ptr);
-        let did_layer_hashed = did_layer.clone().with_metadata(metadata_hash);
-
-        let parent_inner_puzzle_hash = did_layer_hashed.tree_hash().into();
-        let layers = SingletonLayer::new(singleton_layer.launcher_id, did_layer);
-
-        let mut info = DidInfo::from_layers(layers);
-        info.p2_puzzle_hash = hint;
-
-        Ok(Some(Self {
+        Self::child_from_conditions(
+            allocator,
+            parent_coin,
+            singleton_layer.launcher_id,
+            did_layer,
+            conditions,
             coin,
-            proof: Proof::Lineage(LineageProof {
-                parent_parent_coin_info: parent_coin.parent_coin_info,
-                parent_inner_puzzle_hash,
-                parent_amount: parent_coin.amount,
-            }),
-            info,
-        }))
+        )
+        .map(Some)
     }
 }
 
@@ -227,8 +308,7 @@ where
 mod tests {
     use std::fmt;
 
-    use chia_protocol::Bytes32;
-    use chia_sdk_test::Simulator;
+    use chia_sdk_test::{test_secret_keys, Simulator};
     use clvm_traits::clvm_list;
     use rstest::rstest;
 
@@ -382,4 +462,65 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_transfer_did() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        let sks = test_secret_keys(2)?;
+        let alice = StandardLayer::new(sks[0].public_key());
+        let bob = StandardLayer::new(sks[1].public_key());
+        let alice_puzzle_hash = alice.tree_hash().into();
+        let bob_puzzle_hash = bob.tree_hash().into();
+
+        let coin = sim.new_coin(alice_puzzle_hash, 1);
+        let (create_did, did) = Launcher::new(coin.coin_id(), 1).create_simple_did(ctx, &alice)?;
+        alice.spend(ctx, coin, create_did)?;
+
+        let did = did.transfer(ctx, &alice, bob_puzzle_hash, Conditions::new())?;
+        assert_eq!(did.info.p2_puzzle_hash, bob_puzzle_hash);
+        sim.spend_coins(ctx.take(), &[sks[0].clone()])?;
+        assert!(sim.coin_state(did.coin.coin_id()).is_some());
+
+        // The previous owner can no longer spend the DID.
+        did.spend_with(ctx, &alice, Conditions::new())?;
+        assert!(sim.spend_coins(ctx.take(), &[sks[0].clone()]).is_err());
+
+        let did = did.update(ctx, &bob, Conditions::new())?;
+        sim.spend_coins(ctx.take(), &[sks[1].clone()])?;
+        assert!(sim.coin_state(did.coin.coin_id()).is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_did_recovery_list() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (create_did, did) = Launcher::new(coin.coin_id(), 1).create_simple_did(ctx, &p2)?;
+        p2.spend(ctx, coin, create_did)?;
+
+        let recovery_list_hash = vec![Bytes32::new([1; 32]), Bytes32::new([2; 32])]
+            .tree_hash()
+            .into();
+        let did =
+            did.update_recovery_list(ctx, &p2, Some(recovery_list_hash), 2, Conditions::new())?;
+        assert_eq!(did.info.recovery_list_hash, Some(recovery_list_hash));
+        assert_eq!(did.info.num_verifications_required, 2);
+        assert_eq!(did.info.p2_puzzle_hash, puzzle_hash);
+
+        let did = did.update_recovery_list(ctx, &p2, None, 0, Conditions::new())?;
+        assert_eq!(did.info.recovery_list_hash, None);
+
+        // The updated DID can still be spent.
+        let did = did.update(ctx, &p2, Conditions::new())?;
+        sim.spend_coins(ctx.take(), &[sk])?;
+        assert!(sim.coin_state(did.coin.coin_id()).is_some());
+
+        Ok(())
+    }
 }
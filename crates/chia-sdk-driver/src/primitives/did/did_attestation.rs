@@ -0,0 +1,473 @@
+use chia_bls::PublicKey;
+use chia_protocol::{Bytes32, Coin};
+use chia_puzzles::{singleton::SingletonSolution, CoinProof, Proof};
+use chia_sdk_types::Conditions;
+use clvm_traits::{apply_constants, clvm_quote, FromClvm, ToClvm};
+use clvm_utils::ToTreeHash;
+use clvmr::{Allocator, NodePtr};
+
+use crate::{DriverError, Layer, Spend, SpendContext, SpendWithConditions};
+
+use super::Did;
+
+/// An approval from a recovery DID to recover another DID to a new inner puzzle hash.
+/// It's created as an ephemeral message coin, which must be spent alongside the recovery.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DidAttestation {
+    /// The message coin created by the recovery DID.
+    pub coin: Coin,
+    /// The launcher id of the recovery DID.
+    pub launcher_id: Bytes32,
+    /// The recovery DID coin that created the message coin.
+    pub coin_proof: CoinProof,
+    /// The coin id of the DID being recovered.
+    pub recovering_coin_id: Bytes32,
+    /// The new inner puzzle hash of the DID being recovered.
+    pub new_inner_puzzle_hash: Bytes32,
+    /// The public key that must sign the new inner puzzle hash to complete the recovery.
+    pub public_key: PublicKey,
+}
+
+impl DidAttestation {
+    /// Creates the message puzzle, which announces the coin id of the DID being recovered
+    /// and requires a signature of the new inner puzzle hash.
+    pub fn message_puzzle(
+        ctx: &mut SpendContext,
+        recovering_coin_id: Bytes32,
+        new_inner_puzzle_hash: Bytes32,
+        public_key: PublicKey,
+    ) -> Result<NodePtr, DriverError> {
+        ctx.alloc(&clvm_quote!(Conditions::new()
+            .create_coin_announcement(recovering_coin_id.into())
+            .agg_sig_unsafe(public_key, new_inner_puzzle_hash.into())))
+    }
+
+    /// Spends the message coin, which is required for the recovery spend to be valid.
+    pub fn spend(&self, ctx: &mut SpendContext) -> Result<(), DriverError> {
+        let puzzle = Self::message_puzzle(
+            ctx,
+            self.recovering_coin_id,
+            self.new_inner_puzzle_hash,
+            self.public_key,
+        )?;
+        ctx.spend(self.coin, Spend::new(puzzle, NodePtr::NIL))
+    }
+}
+
+/// The solution for recovering a DID. Unlike [`DidRecoverySolution`](chia_puzzles::did::DidRecoverySolution),
+/// this includes the coin id of the DID, which is asserted by the puzzle.
+#[derive(ToClvm, FromClvm)]
+#[apply_constants]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[clvm(list)]
+pub(crate) struct DidRecoverSolution {
+    #[clvm(constant = 0)]
+    pub mode: u8,
+    pub amount: u64,
+    pub new_inner_puzzle_hash: Bytes32,
+    /// The coin proof of each recovery DID's attestation.
+    pub recovery_coins: Vec<CoinProof>,
+    pub public_key: PublicKey,
+    pub recovery_list_reveal: Vec<Bytes32>,
+    pub my_id: Bytes32,
+}
+
+impl<M> Did<M>
+where
+    M: ToClvm<Allocator> + FromClvm<Allocator> + ToTreeHash + Clone,
+{
+    /// Spends this recovery DID to approve the recovery of another DID to a new inner puzzle hash.
+    /// The public key must sign the new inner puzzle hash when the recovery is spent.
+    pub fn attest<I>(
+        self,
+        ctx: &mut SpendContext,
+        inner: &I,
+        recovering_coin_id: Bytes32,
+        new_inner_puzzle_hash: Bytes32,
+        public_key: PublicKey,
+        extra_conditions: Conditions,
+    ) -> Result<(Did<M>, DidAttestation), DriverError>
+    where
+        I: SpendWithConditions,
+    {
+        let message_puzzle = DidAttestation::message_puzzle(
+            ctx,
+            recovering_coin_id,
+            new_inner_puzzle_hash,
+            public_key,
+        )?;
+        let message_puzzle_hash = ctx.tree_hash(message_puzzle).into();
+
+        let attestation = DidAttestation {
+            coin: Coin::new(self.coin.coin_id(), message_puzzle_hash, 0),
+            launcher_id: self.info.launcher_id,
+            coin_proof: CoinProof {
+                parent_coin_info: self.coin.parent_coin_info,
+                inner_puzzle_hash: self.info.inner_puzzle_hash().into(),
+                amount: self.coin.amount,
+            },
+            recovering_coin_id,
+            new_inner_puzzle_hash,
+            public_key,
+        };
+
+        let did = self.update(
+            ctx,
+            inner,
+            extra_conditions.create_coin(message_puzzle_hash, 0, Vec::new()),
+        )?;
+
+        Ok((did, attestation))
+    }
+
+    /// Recovers this DID to a new p2 puzzle hash, using attestations from the recovery DIDs.
+    /// The attestations are spent as well, and the public key must sign the new inner puzzle hash.
+    ///
+    /// The p2 puzzle is the current owner's puzzle, which is revealed but not run.
+    /// The recovery list is the list of recovery DID launcher ids, in the order used for its hash.
+    /// Every DID in the list must attest, even if `num_verifications_required` is lower, since the
+    /// branch of the DID puzzle that skips a recovery DID doesn't pass all of its arguments along.
+    /// Each attestation must be for this coin, the new inner puzzle hash, and the public key.
+    ///
+    /// The recovered DID isn't hinted to its p2 puzzle hash, so it can't be parsed from this spend.
+    pub fn recover(
+        self,
+        ctx: &mut SpendContext,
+        p2_puzzle: NodePtr,
+        recovery_list: Vec<Bytes32>,
+        attestations: &[DidAttestation],
+        p2_puzzle_hash: Bytes32,
+        public_key: PublicKey,
+    ) -> Result<Did<M>, DriverError> {
+        let mut info = self.info.clone();
+        info.p2_puzzle_hash = p2_puzzle_hash;
+        let new_inner_puzzle_hash: Bytes32 = info.inner_puzzle_hash().into();
+
+        let mut used_attestations = Vec::with_capacity(recovery_list.len());
+
+        for &launcher_id in &recovery_list {
+            let attestation = attestations
+                .iter()
+                .find(|attestation| attestation.launcher_id == launcher_id)
+                .ok_or(DriverError::MissingAttestation(launcher_id))?;
+
+            if attestation.recovering_coin_id != self.coin.coin_id()
+                || attestation.new_inner_puzzle_hash != new_inner_puzzle_hash
+                || attestation.public_key != public_key
+            {
+                return Err(DriverError::InvalidAttestation(launcher_id));
+            }
+
+            used_attestations.push(attestation);
+        }
+
+        let mut recovery_coins = Vec::with_capacity(used_attestations.len());
+
+        for attestation in used_attestations {
+            attestation.spend(ctx)?;
+            recovery_coins.push(attestation.coin_proof);
+        }
+
+        let layers = self.info.clone().into_layers(p2_puzzle);
+        let puzzle = layers.construct_puzzle(ctx)?;
+        let solution = ctx.alloc(&SingletonSolution {
+            lineage_proof: self.proof,
+            amount: self.coin.amount,
+            inner_solution: DidRecoverSolution {
+                amount: self.coin.amount,
+                new_inner_puzzle_hash,
+                recovery_coins,
+                public_key,
+                recovery_list_reveal: recovery_list,
+                my_id: self.coin.coin_id(),
+            },
+        })?;
+
+        ctx.spend(self.coin, Spend::new(puzzle, solution))?;
+
+        Ok(Did {
+            coin: Coin::new(
+                self.coin.coin_id(),
+                info.puzzle_hash().into(),
+                self.coin.amount,
+            ),
+            proof: Proof::Lineage(self.child_lineage_proof()),
+            info,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_sdk_test::{test_secret_keys, Simulator};
+
+    use crate::{Launcher, StandardLayer};
+
+    use super::*;
+
+    #[test]
+    fn test_recover_did() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        let sks = test_secret_keys(3)?;
+        let recoverer = StandardLayer::new(sks[0].public_key());
+        let lost = StandardLayer::new(sks[1].public_key());
+        let new_owner = StandardLayer::new(sks[2].public_key());
+        let recoverer_puzzle_hash = recoverer.tree_hash().into();
+        let lost_puzzle_hash = lost.tree_hash().into();
+        let new_puzzle_hash = new_owner.tree_hash().into();
+
+        // Create two recovery DIDs.
+        let mut recovery_dids = Vec::new();
+        for _ in 0..2 {
+            let coin = sim.new_coin(recoverer_puzzle_hash, 1);
+            let (create_did, did) =
+                Launcher::new(coin.coin_id(), 1).create_simple_did(ctx, &recoverer)?;
+            recoverer.spend(ctx, coin, create_did)?;
+            recovery_dids.push(did);
+        }
+        sim.spend_coins(ctx.take(), &[sks[0].clone()])?;
+
+        let recovery_list: Vec<Bytes32> = recovery_dids
+            .iter()
+            .map(|did| did.info.launcher_id)
+            .collect();
+
+        // Create a DID that can be recovered with both of them.
+        let coin = sim.new_coin(lost_puzzle_hash, 1);
+        let (create_did, did) = Launcher::new(coin.coin_id(), 1).create_did(
+            ctx,
+            Some(recovery_list.tree_hash().into()),
+            2,
+            (),
+            &lost,
+        )?;
+        lost.spend(ctx, coin, create_did)?;
+        sim.spend_coins(ctx.take(), &[sks[1].clone()])?;
+
+        let mut new_info = did.info;
+        new_info.p2_puzzle_hash = new_puzzle_hash;
+        let new_inner_puzzle_hash = new_info.inner_puzzle_hash().into();
+
+        // Each recovery DID attests to the recovery.
+        let mut attestations = Vec::new();
+        for recovery_did in recovery_dids {
+            let (_, attestation) = recovery_did.attest(
+                ctx,
+                &recoverer,
+                did.coin.coin_id(),
+                new_inner_puzzle_hash,
+                sks[2].public_key(),
+                Conditions::new(),
+            )?;
+            attestations.push(attestation);
+        }
+        sim.spend_coins(ctx.take(), &[sks[0].clone()])?;
+
+        // Both of the recovery DIDs must attest.
+        let lost_puzzle = lost.construct_puzzle(ctx)?;
+        assert!(matches!(
+            did.recover(
+                ctx,
+                lost_puzzle,
+                recovery_list.clone(),
+                &attestations[..1],
+                new_puzzle_hash,
+                sks[2].public_key(),
+            ),
+            Err(DriverError::MissingAttestation(launcher_id)) if launcher_id == recovery_list[1]
+        ));
+        assert_eq!(ctx.iter().count(), 0);
+
+        // The attestations must be for the same public key and new puzzle hash.
+        assert!(matches!(
+            did.recover(
+                ctx,
+                lost_puzzle,
+                recovery_list.clone(),
+                &attestations,
+                new_puzzle_hash,
+                sks[1].public_key(),
+            ),
+            Err(DriverError::InvalidAttestation(launcher_id)) if launcher_id == recovery_list[0]
+        ));
+        assert!(matches!(
+            did.recover(
+                ctx,
+                lost_puzzle,
+                recovery_list.clone(),
+                &attestations,
+                lost_puzzle_hash,
+                sks[2].public_key(),
+            ),
+            Err(DriverError::InvalidAttestation(launcher_id)) if launcher_id == recovery_list[0]
+        ));
+        assert_eq!(ctx.iter().count(), 0);
+
+        // The new owner recovers the DID without the lost key.
+        let did = did.recover(
+            ctx,
+            lost_puzzle,
+            recovery_list,
+            &attestations,
+            new_puzzle_hash,
+            sks[2].public_key(),
+        )?;
+        sim.spend_coins(ctx.take(), &[sks[2].clone()])?;
+
+        assert_eq!(did.info.p2_puzzle_hash, new_puzzle_hash);
+        assert!(sim.coin_state(did.coin.coin_id()).is_some());
+
+        let did = did.update(ctx, &new_owner, Conditions::new())?;
+        sim.spend_coins(ctx.take(), &[sks[2].clone()])?;
+        assert!(sim.coin_state(did.coin.coin_id()).is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_recover_did_one_of_two() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        let sks = test_secret_keys(3)?;
+        let recoverer = StandardLayer::new(sks[0].public_key());
+        let lost = StandardLayer::new(sks[1].public_key());
+        let new_owner = StandardLayer::new(sks[2].public_key());
+        let recoverer_puzzle_hash = recoverer.tree_hash().into();
+        let lost_puzzle_hash = lost.tree_hash().into();
+        let new_puzzle_hash = new_owner.tree_hash().into();
+
+        let mut recovery_dids = Vec::new();
+        for _ in 0..2 {
+            let coin = sim.new_coin(recoverer_puzzle_hash, 1);
+            let (create_did, did) =
+                Launcher::new(coin.coin_id(), 1).create_simple_did(ctx, &recoverer)?;
+            recoverer.spend(ctx, coin, create_did)?;
+            recovery_dids.push(did);
+        }
+        sim.spend_coins(ctx.take(), &[sks[0].clone()])?;
+
+        let recovery_list: Vec<Bytes32> = recovery_dids
+            .iter()
+            .map(|did| did.info.launcher_id)
+            .collect();
+
+        // Create a DID that can be recovered with either of them.
+        let coin = sim.new_coin(lost_puzzle_hash, 1);
+        let (create_did, did) = Launcher::new(coin.coin_id(), 1).create_did(
+            ctx,
+            Some(recovery_list.tree_hash().into()),
+            1,
+            (),
+            &lost,
+        )?;
+        lost.spend(ctx, coin, create_did)?;
+        sim.spend_coins(ctx.take(), &[sks[1].clone()])?;
+
+        let mut new_info = did.info;
+        new_info.p2_puzzle_hash = new_puzzle_hash;
+
+        // Each recovery DID attests to the recovery.
+        let mut attestations = Vec::new();
+        for recovery_did in recovery_dids {
+            let (_, attestation) = recovery_did.attest(
+                ctx,
+                &recoverer,
+                did.coin.coin_id(),
+                new_info.inner_puzzle_hash().into(),
+                sks[2].public_key(),
+                Conditions::new(),
+            )?;
+            attestations.push(attestation);
+        }
+        sim.spend_coins(ctx.take(), &[sks[0].clone()])?;
+
+        // One attestation is enough for the DID, but the DID puzzle fails when it skips a recovery DID.
+        let lost_puzzle = lost.construct_puzzle(ctx)?;
+        assert!(matches!(
+            did.recover(
+                ctx,
+                lost_puzzle,
+                recovery_list.clone(),
+                &attestations[1..],
+                new_puzzle_hash,
+                sks[2].public_key(),
+            ),
+            Err(DriverError::MissingAttestation(launcher_id)) if launcher_id == recovery_list[0]
+        ));
+        assert_eq!(ctx.iter().count(), 0);
+
+        // With both attestations, nothing is skipped and the DID is recovered.
+        let did = did.recover(
+            ctx,
+            lost_puzzle,
+            recovery_list,
+            &attestations,
+            new_puzzle_hash,
+            sks[2].public_key(),
+        )?;
+        sim.spend_coins(ctx.take(), &[sks[2].clone()])?;
+
+        assert_eq!(did.info.p2_puzzle_hash, new_puzzle_hash);
+        assert!(sim.coin_state(did.coin.coin_id()).is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_recover_did_wrong_key() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        let sks = test_secret_keys(2)?;
+        let owner = StandardLayer::new(sks[0].public_key());
+        let puzzle_hash = owner.tree_hash().into();
+
+        let coin = sim.new_coin(puzzle_hash, 1);
+        let (create_did, recovery_did) =
+            Launcher::new(coin.coin_id(), 1).create_simple_did(ctx, &owner)?;
+        owner.spend(ctx, coin, create_did)?;
+
+        let recovery_list = vec![recovery_did.info.launcher_id];
+
+        let coin = sim.new_coin(puzzle_hash, 1);
+        let (create_did, did) = Launcher::new(coin.coin_id(), 1).create_did(
+            ctx,
+            Some(recovery_list.tree_hash().into()),
+            1,
+            (),
+            &owner,
+        )?;
+        owner.spend(ctx, coin, create_did)?;
+        sim.spend_coins(ctx.take(), &[sks[0].clone()])?;
+
+        let mut new_info = did.info;
+        new_info.p2_puzzle_hash = Bytes32::default();
+
+        let (_, attestation) = recovery_did.attest(
+            ctx,
+            &owner,
+            did.coin.coin_id(),
+            new_info.inner_puzzle_hash().into(),
+            sks[1].public_key(),
+            Conditions::new(),
+        )?;
+        sim.spend_coins(ctx.take(), &[sks[0].clone()])?;
+
+        // The recovery must be signed by the key in the attestation.
+        let owner_puzzle = owner.construct_puzzle(ctx)?;
+        let _did = did.recover(
+            ctx,
+            owner_puzzle,
+            recovery_list,
+            &[attestation],
+            Bytes32::default(),
+            sks[1].public_key(),
+        )?;
+        assert!(sim.spend_coins(ctx.take(), &[sks[0].clone()]).is_err());
+
+        Ok(())
+    }
+}
@@ -1,5 +1,8 @@
 use chia_protocol::Bytes32;
-use chia_puzzles::{did::DidArgs, singleton::SingletonStruct};
+use chia_puzzles::{
+    did::DidArgs,
+    singleton::{SingletonArgs, SingletonStruct},
+};
 use clvm_traits::{FromClvm, ToClvm};
 use clvm_utils::{ToTreeHash, TreeHash};
 use clvmr::Allocator;
@@ -101,6 +104,14 @@ impl<M> DidInfo<M> {
             self.metadata.tree_hash(),
         )
     }
+
+    /// Calculates the full puzzle hash of the DID coin, including the singleton layer.
+    pub fn puzzle_hash(&self) -> TreeHash
+    where
+        M: ToTreeHash,
+    {
+        SingletonArgs::curry_tree_hash(self.launcher_id, self.inner_puzzle_hash())
+    }
 }
 
 #[cfg(test)]
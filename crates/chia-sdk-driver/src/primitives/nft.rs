@@ -260,45 +260,21 @@ where
     }
 }
 
-impl<M> Primitive for Nft<M>
+impl<M> Nft<M>
 where
     M: ToClvm<Allocator> + FromClvm<Allocator>,
 {
-    fn from_parent_spend(
+    /// Finds the child of a parent NFT spend, given the output conditions of its p2 puzzle.
+    /// This allows the p2 puzzle to be run once and reused.
+    pub(crate) fn child_from_conditions(
         allocator: &mut Allocator,
         parent_coin: Coin,
-        parent_puzzle: Puzzle,
-        parent_solution: NodePtr,
+        launcher_id: Bytes32,
+        parent_inner_puzzle_hash: Bytes32,
+        inner_layers: NftStateLayer<M, NftOwnershipLayer<RoyaltyTransferLayer, Puzzle>>,
+        conditions: Vec<Condition>,
         coin: Coin,
-    ) -> Result<Option<Self>, DriverError>
-    where
-        Self: Sized,
-    {
-        let Some(singleton_layer) =
-            SingletonLayer::<Puzzle>::parse_puzzle(allocator, parent_puzzle)?
-        else {
-            return Ok(None);
-        };
-
-        let Some(inner_layers) =
-            NftStateLayer::<M, NftOwnershipLayer<RoyaltyTransferLayer, Puzzle>>::parse_puzzle(
-                allocator,
-                singleton_layer.inner_puzzle,
-            )?
-        else {
-            return Ok(None);
-        };
-
-        let parent_solution = SingletonLayer::<
-            NftStateLayer<M, NftOwnershipLayer<RoyaltyTransferLayer, Puzzle>>,
-        >::parse_solution(allocator, parent_solution)?;
-
-        let inner_puzzle = inner_layers.inner_puzzle.inner_puzzle;
-        let inner_solution = parent_solution.inner_solution.inner_solution.inner_solution;
-
-        let output = run_puzzle(allocator, inner_puzzle.ptr(), inner_solution)?;
-        let conditions = Vec::<Condition>::from_clvm(allocator, output)?;
-
+    ) -> Result<Self, DriverError> {
         let mut create_coin = None;
         let mut new_owner = None;
         let mut new_metadata = None;
@@ -322,7 +298,7 @@ where
             return Err(DriverError::MissingChild);
         };
 
-        let mut layers = SingletonLayer::new(singleton_layer.launcher_id, inner_layers);
+        let mut layers = SingletonLayer::new(launcher_id, inner_layers);
 
         if let Some(new_owner) = new_owner {
             layers.inner_puzzle.inner_puzzle.current_owner = new_owner.did_id;
@@ -344,15 +320,67 @@ where
         let mut info = NftInfo::from_layers(layers);
         info.p2_puzzle_hash = create_coin.puzzle_hash;
 
-        Ok(Some(Self {
+        Ok(Self {
             coin,
             proof: Proof::Lineage(LineageProof {
                 parent_parent_coin_info: parent_coin.parent_coin_info,
-                parent_inner_puzzle_hash: singleton_layer.inner_puzzle.curried_puzzle_hash().into(),
+                parent_inner_puzzle_hash,
                 parent_amount: parent_coin.amount,
             }),
             info,
-        }))
+        })
+    }
+}
+
+impl<M> Primitive for Nft<M>
+where
+    M: ToClvm<Allocator> + FromClvm<Allocator>,
+{
+    fn from_parent_spend(
+        allocator: &mut Allocator,
+        parent_coin: Coin,
+        parent_puzzle: Puzzle,
+        parent_solution: NodePtr,
+        coin: Coin,
+    ) -> Result<Option<Self>, DriverError>
+    where
+        Self: Sized,
+    {
+        let Some(singleton_layer) =
+            SingletonLayer::<Puzzle>::parse_puzzle(allocator, parent_puzzle)?
+        else {
+            return Ok(None);
+        };
+
+        let Some(inner_layers) =
+            NftStateLayer::<M, NftOwnershipLayer<RoyaltyTransferLayer, Puzzle>>::parse_puzzle(
+                allocator,
+                singleton_layer.inner_puzzle,
+            )?
+        else {
+            return Ok(None);
+        };
+
+        let parent_solution = SingletonLayer::<
+            NftStateLayer<M, NftOwnershipLayer<RoyaltyTransferLayer, Puzzle>>,
+        >::parse_solution(allocator, parent_solution)?;
+
+        let inner_puzzle = inner_layers.inner_puzzle.inner_puzzle;
+        let inner_solution = parent_solution.inner_solution.inner_solution.inner_solution;
+
+        let output = run_puzzle(allocator, inner_puzzle.ptr(), inner_solution)?;
+        let conditions = Vec::<Condition>::from_clvm(allocator, output)?;
+
+        Self::child_from_conditions(
+            allocator,
+            parent_coin,
+            singleton_layer.launcher_id,
+            singleton_layer.inner_puzzle.curried_puzzle_hash().into(),
+            inner_layers,
+            conditions,
+            coin,
+        )
+        .map(Some)
     }
 }
 
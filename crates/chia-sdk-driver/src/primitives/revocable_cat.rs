@@ -0,0 +1,300 @@
+use chia_protocol::{Bytes32, Coin};
+use chia_puzzles::LineageProof;
+use chia_sdk_types::{run_puzzle, Condition};
+use clvm_traits::FromClvm;
+use clvm_utils::{ToTreeHash, TreeHash};
+use clvmr::{Allocator, NodePtr};
+
+use crate::{
+    Cat, CatLayer, CatSpend, CatVersion, DriverError, Layer, Primitive, Puzzle, RevocationArgs,
+    RevocationLayer, RevocationSolution, Spend, SpendContext,
+};
+
+/// A CAT whose inner puzzle is wrapped in a [`RevocationLayer`], so that it can be revoked
+/// by the issuer with the hidden puzzle. It's spent as part of a ring with [`Cat::spend_all`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RevocableCat {
+    pub coin: Coin,
+    pub lineage_proof: Option<LineageProof>,
+    pub asset_id: Bytes32,
+    /// The puzzle hash that can revoke the CAT, which is typically controlled by the issuer.
+    pub hidden_puzzle_hash: Bytes32,
+    /// The puzzle hash of the owner, which is wrapped by the revocation layer.
+    pub p2_puzzle_hash: Bytes32,
+}
+
+impl RevocableCat {
+    pub fn new(
+        coin: Coin,
+        lineage_proof: Option<LineageProof>,
+        asset_id: Bytes32,
+        hidden_puzzle_hash: Bytes32,
+        p2_puzzle_hash: Bytes32,
+    ) -> Self {
+        Self {
+            coin,
+            lineage_proof,
+            asset_id,
+            hidden_puzzle_hash,
+            p2_puzzle_hash,
+        }
+    }
+
+    /// The puzzle hash of the revocation layer, which is the inner puzzle hash of the CAT.
+    pub fn inner_puzzle_hash(&self) -> TreeHash {
+        RevocationArgs::curry_tree_hash(self.hidden_puzzle_hash, self.p2_puzzle_hash)
+    }
+
+    /// The underlying CAT, whose inner puzzle is the revocation layer.
+    pub fn as_cat(&self) -> Cat {
+        Cat::new(
+            self.coin,
+            self.lineage_proof,
+            self.asset_id,
+            self.inner_puzzle_hash().into(),
+        )
+    }
+
+    /// Spends the CAT with its p2 puzzle. The created coins are wrapped in the revocation layer,
+    /// so they can still be revoked.
+    pub fn p2_spend(
+        &self,
+        ctx: &mut SpendContext,
+        inner_spend: Spend,
+    ) -> Result<CatSpend, DriverError> {
+        self.revocation_spend(
+            ctx,
+            RevocationSolution::new(inner_spend.puzzle, inner_spend.solution),
+        )
+    }
+
+    /// Revokes the CAT with the hidden puzzle. The created coins aren't wrapped in the revocation layer,
+    /// so they're normal CATs unless the hidden puzzle wraps them again.
+    pub fn revoke_spend(
+        &self,
+        ctx: &mut SpendContext,
+        hidden_spend: Spend,
+    ) -> Result<CatSpend, DriverError> {
+        self.revocation_spend(
+            ctx,
+            RevocationSolution::revoke(hidden_spend.puzzle, hidden_spend.solution),
+        )
+    }
+
+    fn revocation_spend(
+        &self,
+        ctx: &mut SpendContext,
+        solution: RevocationSolution<NodePtr, NodePtr>,
+    ) -> Result<CatSpend, DriverError> {
+        let layer = RevocationLayer::new(self.hidden_puzzle_hash, self.p2_puzzle_hash);
+        let puzzle = layer.construct_puzzle(ctx)?;
+        let solution = layer.construct_solution(ctx, solution)?;
+        Ok(CatSpend::new(self.as_cat(), Spend::new(puzzle, solution)))
+    }
+
+    /// Creates a wrapped spendable revocable CAT for an output of the p2 puzzle.
+    #[must_use]
+    pub fn wrapped_child(&self, p2_puzzle_hash: Bytes32, amount: u64) -> Self {
+        let child = self.as_cat().wrapped_child(
+            RevocationArgs::curry_tree_hash(self.hidden_puzzle_hash, p2_puzzle_hash).into(),
+            amount,
+        );
+        Self {
+            coin: child.coin,
+            lineage_proof: child.lineage_proof,
+            asset_id: self.asset_id,
+            hidden_puzzle_hash: self.hidden_puzzle_hash,
+            p2_puzzle_hash,
+        }
+    }
+}
+
+impl Primitive for RevocableCat {
+    fn from_parent_spend(
+        allocator: &mut Allocator,
+        parent_coin: Coin,
+        parent_puzzle: Puzzle,
+        parent_solution: NodePtr,
+        coin: Coin,
+    ) -> Result<Option<Self>, DriverError>
+    where
+        Self: Sized,
+    {
+        let Some(parent_layer) =
+            CatLayer::<RevocationLayer>::parse_puzzle(allocator, parent_puzzle)?
+        else {
+            return Ok(None);
+        };
+
+        if parent_layer.version != CatVersion::V2 {
+            return Ok(None);
+        }
+
+        let parent_solution =
+            CatLayer::<RevocationLayer>::parse_solution(allocator, parent_solution)?;
+        let revocation_solution = parent_solution.inner_puzzle_solution;
+
+        // The outputs of the hidden puzzle aren't wrapped in the revocation layer.
+        if revocation_solution.hidden {
+            return Ok(None);
+        }
+
+        let output = run_puzzle(
+            allocator,
+            revocation_solution.puzzle,
+            revocation_solution.solution,
+        )?;
+        let conditions = Vec::<Condition>::from_clvm(allocator, output)?;
+
+        let hidden_puzzle_hash = parent_layer.inner_puzzle.hidden_puzzle_hash;
+
+        let p2_puzzle_hash = conditions
+            .into_iter()
+            .filter_map(Condition::into_create_coin)
+            .find_map(|create_coin| {
+                if create_coin.amount != coin.amount {
+                    return None;
+                }
+
+                let inner_puzzle_hash =
+                    RevocationArgs::curry_tree_hash(hidden_puzzle_hash, create_coin.puzzle_hash);
+                let wrapped_puzzle_hash = parent_layer
+                    .version
+                    .curry_tree_hash(parent_layer.asset_id, inner_puzzle_hash);
+
+                if wrapped_puzzle_hash != coin.puzzle_hash.into() {
+                    return None;
+                }
+
+                Some(create_coin.puzzle_hash)
+            });
+
+        let Some(p2_puzzle_hash) = p2_puzzle_hash else {
+            return Err(DriverError::MissingChild);
+        };
+
+        Ok(Some(Self {
+            coin,
+            lineage_proof: Some(LineageProof {
+                parent_parent_coin_info: parent_coin.parent_coin_info,
+                parent_inner_puzzle_hash: parent_layer.inner_puzzle.tree_hash().into(),
+                parent_amount: parent_coin.amount,
+            }),
+            asset_id: parent_layer.asset_id,
+            hidden_puzzle_hash,
+            p2_puzzle_hash,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_puzzles::standard::StandardArgs;
+    use chia_sdk_test::{test_secret_keys, Simulator};
+    use chia_sdk_types::Conditions;
+    use clvm_traits::ToClvm;
+
+    use crate::{SpendWithConditions, StandardLayer};
+
+    use super::*;
+
+    fn parse_child(
+        ctx: &mut SpendContext,
+        sim: &Simulator,
+        parent_coin: Coin,
+        coin: Coin,
+    ) -> anyhow::Result<Option<RevocableCat>> {
+        let parent_puzzle = sim
+            .puzzle_reveal(parent_coin.coin_id())
+            .expect("missing puzzle")
+            .to_clvm(&mut ctx.allocator)?;
+        let parent_solution = sim
+            .solution(parent_coin.coin_id())
+            .expect("missing solution")
+            .to_clvm(&mut ctx.allocator)?;
+        let parent_puzzle = Puzzle::parse(&ctx.allocator, parent_puzzle);
+        Ok(RevocableCat::from_parent_spend(
+            &mut ctx.allocator,
+            parent_coin,
+            parent_puzzle,
+            parent_solution,
+            coin,
+        )?)
+    }
+
+    #[test]
+    fn test_revocable_cat() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        let [issuer_sk, owner_sk]: [_; 2] = test_secret_keys(2)?.try_into().expect("two keys");
+        let issuer = StandardLayer::new(issuer_sk.public_key());
+        let owner = StandardLayer::new(owner_sk.public_key());
+        let issuer_puzzle_hash: Bytes32 =
+            StandardArgs::curry_tree_hash(issuer_sk.public_key()).into();
+        let owner_puzzle_hash: Bytes32 =
+            StandardArgs::curry_tree_hash(owner_sk.public_key()).into();
+
+        // Issue the CAT to the owner, with the issuer's puzzle hash as the hidden puzzle.
+        let coin = sim.new_coin(issuer_puzzle_hash, 10);
+        let inner_puzzle_hash =
+            RevocationArgs::curry_tree_hash(issuer_puzzle_hash, owner_puzzle_hash).into();
+
+        let (issue_cat, eve) = Cat::single_issuance_eve(
+            ctx,
+            coin.coin_id(),
+            10,
+            Conditions::new().create_coin(inner_puzzle_hash, 10, vec![owner_puzzle_hash.into()]),
+        )?;
+        issuer.spend(ctx, coin, issue_cat)?;
+        sim.spend_coins(ctx.take(), &[issuer_sk.clone()])?;
+
+        let child = eve.wrapped_child(inner_puzzle_hash, 10);
+        let cat = RevocableCat::new(
+            child.coin,
+            child.lineage_proof,
+            eve.asset_id,
+            issuer_puzzle_hash,
+            owner_puzzle_hash,
+        );
+        assert_eq!(cat.as_cat(), child);
+
+        // The owner can transfer the CAT, and the outputs are still revocable.
+        let inner_spend = owner.spend_with_conditions(
+            ctx,
+            Conditions::new()
+                .create_coin(owner_puzzle_hash, 6, vec![owner_puzzle_hash.into()])
+                .create_coin(owner_puzzle_hash, 4, vec![owner_puzzle_hash.into()]),
+        )?;
+        let cat_spend = cat.p2_spend(ctx, inner_spend)?;
+        Cat::spend_all(ctx, &[cat_spend])?;
+        sim.spend_coins(ctx.take(), &[owner_sk])?;
+
+        let child = cat.wrapped_child(owner_puzzle_hash, 6);
+        assert_eq!(parse_child(ctx, &sim, cat.coin, child.coin)?, Some(child));
+
+        // The owner can't use the hidden puzzle path with their own puzzle.
+        let inner_spend = owner.spend_with_conditions(
+            ctx,
+            Conditions::new().create_coin(owner_puzzle_hash, 6, Vec::new()),
+        )?;
+        let cat_spend = child.revoke_spend(ctx, inner_spend)?;
+        assert!(Cat::spend_all(ctx, &[cat_spend]).is_err());
+        assert_eq!(ctx.iter().count(), 0);
+
+        // The issuer can claw it back, and the output is no longer revocable.
+        let hidden_spend = issuer.spend_with_conditions(
+            ctx,
+            Conditions::new().create_coin(issuer_puzzle_hash, 6, vec![issuer_puzzle_hash.into()]),
+        )?;
+        let cat_spend = child.revoke_spend(ctx, hidden_spend)?;
+        Cat::spend_all(ctx, &[cat_spend])?;
+        sim.spend_coins(ctx.take(), &[issuer_sk])?;
+
+        let revoked = child.as_cat().wrapped_child(issuer_puzzle_hash, 6);
+        assert!(sim.coin_state(revoked.coin.coin_id()).is_some());
+        assert_eq!(parse_child(ctx, &sim, child.coin, revoked.coin)?, None);
+
+        Ok(())
+    }
+}
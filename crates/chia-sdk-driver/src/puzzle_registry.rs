@@ -0,0 +1,472 @@
+use std::{any::Any, collections::HashMap, fmt};
+
+use chia_puzzles::{
+    cat::{CAT_PUZZLE_HASH, CAT_PUZZLE_HASH_V1},
+    did::DID_INNER_PUZZLE_HASH,
+    nft::{
+        NFT_OWNERSHIP_LAYER_PUZZLE_HASH, NFT_ROYALTY_TRANSFER_PUZZLE_HASH,
+        NFT_STATE_LAYER_PUZZLE_HASH,
+    },
+    offer::SETTLEMENT_PAYMENTS_PUZZLE_HASH,
+    singleton::SINGLETON_TOP_LAYER_PUZZLE_HASH,
+    standard::STANDARD_PUZZLE_HASH,
+};
+use clvm_utils::TreeHash;
+use clvmr::{Allocator, NodePtr};
+
+use crate::{
+    CatLayer, DidLayer, DriverError, HashedPtr, Layer, NftOwnershipLayer, NftStateLayer,
+    P2DelegatedConditionsLayer, P2OneOfMany, Puzzle, RevocationLayer, RoyaltyTransferLayer,
+    SettlementLayer, SingletonLayer, StandardLayer, P2_DELEGATED_CONDITIONS_PUZZLE_HASH,
+    P2_ONE_OF_MANY_PUZZLE_HASH, REVOCATION_LAYER_PUZZLE_HASH,
+};
+
+#[cfg(feature = "chip-0035")]
+use crate::{
+    DelegationLayer, WriterLayer, DELEGATION_LAYER_PUZZLE_HASH, WRITER_FILTER_PUZZLE_HASH,
+};
+
+trait AnyLayer: Any + fmt::Debug {
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T> AnyLayer for T
+where
+    T: Any + fmt::Debug,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+type ParsedLayer = (Box<dyn AnyLayer>, Vec<Puzzle>);
+type ParseFn = Box<dyn Fn(&Allocator, Puzzle) -> Result<Option<ParsedLayer>, DriverError>>;
+
+struct RegisteredLayer {
+    name: String,
+    parse: ParseFn,
+}
+
+/// Identifies the layers of a puzzle by looking up the mod hash of each layer.
+///
+/// Layers are parsed with [`Puzzle`] as their inner puzzles, which are then identified recursively.
+/// Applications can register their own [`Layer`] implementations alongside the ones in this SDK.
+pub struct PuzzleRegistry {
+    layers: HashMap<TreeHash, RegisteredLayer>,
+}
+
+impl fmt::Debug for PuzzleRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.layers.iter().map(|(hash, layer)| (hash, &layer.name)))
+            .finish()
+    }
+}
+
+impl Default for PuzzleRegistry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+
+        registry.register::<StandardLayer>(STANDARD_PUZZLE_HASH, "standard", |_| Vec::new());
+        registry
+            .register::<CatLayer<Puzzle>>(CAT_PUZZLE_HASH, "cat", |layer| vec![layer.inner_puzzle]);
+        registry.register::<CatLayer<Puzzle>>(CAT_PUZZLE_HASH_V1, "cat_v1", |layer| {
+            vec![layer.inner_puzzle]
+        });
+        registry.register::<SingletonLayer<Puzzle>>(
+            SINGLETON_TOP_LAYER_PUZZLE_HASH,
+            "singleton",
+            |layer| vec![layer.inner_puzzle],
+        );
+        registry.register::<NftStateLayer<HashedPtr, Puzzle>>(
+            NFT_STATE_LAYER_PUZZLE_HASH,
+            "nft_state_layer",
+            |layer| vec![layer.inner_puzzle],
+        );
+        registry.register::<NftOwnershipLayer<Puzzle, Puzzle>>(
+            NFT_OWNERSHIP_LAYER_PUZZLE_HASH,
+            "nft_ownership_layer",
+            |layer| vec![layer.transfer_layer, layer.inner_puzzle],
+        );
+        registry.register::<RoyaltyTransferLayer>(
+            NFT_ROYALTY_TRANSFER_PUZZLE_HASH,
+            "royalty_transfer_layer",
+            |_| Vec::new(),
+        );
+        registry.register::<DidLayer<HashedPtr, Puzzle>>(DID_INNER_PUZZLE_HASH, "did", |layer| {
+            vec![layer.inner_puzzle]
+        });
+        registry.register::<SettlementLayer>(
+            SETTLEMENT_PAYMENTS_PUZZLE_HASH,
+            "settlement_payments",
+            |_| Vec::new(),
+        );
+        registry.register::<P2DelegatedConditionsLayer>(
+            P2_DELEGATED_CONDITIONS_PUZZLE_HASH,
+            "p2_delegated_conditions",
+            |_| Vec::new(),
+        );
+        registry
+            .register::<P2OneOfMany>(P2_ONE_OF_MANY_PUZZLE_HASH, "p2_one_of_many", |_| Vec::new());
+        registry.register::<RevocationLayer>(
+            REVOCATION_LAYER_PUZZLE_HASH,
+            "revocation_layer",
+            |_| Vec::new(),
+        );
+
+        #[cfg(feature = "chip-0035")]
+        {
+            registry.register::<DelegationLayer>(
+                DELEGATION_LAYER_PUZZLE_HASH,
+                "delegation_layer",
+                |_| Vec::new(),
+            );
+            registry.register::<WriterLayer<Puzzle>>(
+                WRITER_FILTER_PUZZLE_HASH,
+                "writer_layer",
+                |layer| vec![layer.inner_puzzle],
+            );
+        }
+
+        registry
+    }
+}
+
+impl PuzzleRegistry {
+    /// Creates a registry with the layers in this SDK.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a registry without any layers.
+    pub fn empty() -> Self {
+        Self {
+            layers: HashMap::new(),
+        }
+    }
+
+    /// Registers a layer by its mod hash, replacing any existing layer with the same mod hash.
+    /// The inner puzzles are identified recursively, and the inner puzzle of the layer itself should be last.
+    pub fn register<L>(
+        &mut self,
+        mod_hash: TreeHash,
+        name: impl Into<String>,
+        inner_puzzles: fn(&L) -> Vec<Puzzle>,
+    ) where
+        L: Layer + fmt::Debug + 'static,
+    {
+        let parse = move |allocator: &Allocator, puzzle: Puzzle| {
+            let Some(layer) = L::parse_puzzle(allocator, puzzle)? else {
+                return Ok(None);
+            };
+            let inner = inner_puzzles(&layer);
+            let layer: Box<dyn AnyLayer> = Box::new(layer);
+            Ok(Some((layer, inner)))
+        };
+
+        self.layers.insert(
+            mod_hash,
+            RegisteredLayer {
+                name: name.into(),
+                parse: Box::new(parse),
+            },
+        );
+    }
+
+    pub fn is_registered(&self, mod_hash: TreeHash) -> bool {
+        self.layers.contains_key(&mod_hash)
+    }
+
+    /// Identifies every layer of the puzzle. Puzzles that aren't recognized are left as [`PuzzleTree::Unknown`].
+    pub fn identify(
+        &self,
+        allocator: &Allocator,
+        puzzle: Puzzle,
+    ) -> Result<PuzzleTree, DriverError> {
+        let Some(registered) = self.layers.get(&puzzle.mod_hash()) else {
+            return Ok(PuzzleTree::Unknown(puzzle));
+        };
+
+        let Some((layer, inner_puzzles)) = (registered.parse)(allocator, puzzle)? else {
+            return Ok(PuzzleTree::Unknown(puzzle));
+        };
+
+        let children = inner_puzzles
+            .into_iter()
+            .map(|inner| self.identify(allocator, inner))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(PuzzleTree::Layer(IdentifiedLayer {
+            name: registered.name.clone(),
+            puzzle,
+            layer,
+            children,
+        }))
+    }
+
+    /// Parses the puzzle and identifies every layer of it.
+    pub fn identify_ptr(
+        &self,
+        allocator: &Allocator,
+        ptr: NodePtr,
+    ) -> Result<PuzzleTree, DriverError> {
+        self.identify(allocator, Puzzle::parse(allocator, ptr))
+    }
+}
+
+/// The layers of a puzzle, as identified by a [`PuzzleRegistry`].
+#[derive(Debug)]
+pub enum PuzzleTree {
+    Layer(IdentifiedLayer),
+    Unknown(Puzzle),
+}
+
+impl PuzzleTree {
+    pub fn puzzle(&self) -> Puzzle {
+        match self {
+            Self::Layer(layer) => layer.puzzle,
+            Self::Unknown(puzzle) => *puzzle,
+        }
+    }
+
+    pub fn as_layer(&self) -> Option<&IdentifiedLayer> {
+        match self {
+            Self::Layer(layer) => Some(layer),
+            Self::Unknown(..) => None,
+        }
+    }
+
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, Self::Unknown(..))
+    }
+
+    /// The names of each layer from the outermost to the innermost, following the inner puzzle of each layer.
+    pub fn layer_names(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        let mut current = self;
+
+        while let Self::Layer(layer) = current {
+            names.push(layer.name.as_str());
+            let Some(inner) = layer.children.last() else {
+                break;
+            };
+            current = inner;
+        }
+
+        names
+    }
+
+    /// Finds the first layer of the given type, searching depth first.
+    pub fn find<L>(&self) -> Option<&L>
+    where
+        L: 'static,
+    {
+        let layer = self.as_layer()?;
+        layer
+            .layer()
+            .or_else(|| layer.children.iter().find_map(Self::find))
+    }
+
+    /// The innermost puzzle, following the inner puzzle of each layer.
+    pub fn innermost(&self) -> &Self {
+        match self {
+            Self::Layer(layer) => layer.children.last().map_or(self, Self::innermost),
+            Self::Unknown(..) => self,
+        }
+    }
+}
+
+/// A layer that was recognized by its mod hash, along with the identified inner puzzles.
+#[derive(Debug)]
+pub struct IdentifiedLayer {
+    name: String,
+    puzzle: Puzzle,
+    layer: Box<dyn AnyLayer>,
+    children: Vec<PuzzleTree>,
+}
+
+impl IdentifiedLayer {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn puzzle(&self) -> Puzzle {
+        self.puzzle
+    }
+
+    /// The parsed layer, if it's of the given type.
+    pub fn layer<L>(&self) -> Option<&L>
+    where
+        L: 'static,
+    {
+        self.layer.as_ref().as_any().downcast_ref()
+    }
+
+    pub fn children(&self) -> &[PuzzleTree] {
+        &self.children
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_bls::PublicKey;
+    use chia_protocol::Bytes32;
+    use clvm_traits::{FromClvm, ToClvm};
+    use clvm_utils::{tree_hash_atom, CurriedProgram};
+
+    use crate::SpendContext;
+
+    use super::*;
+
+    #[test]
+    fn test_identify_nft() -> anyhow::Result<()> {
+        let mut ctx = SpendContext::new();
+
+        let launcher_id = Bytes32::new([1; 32]);
+        let metadata = HashedPtr::from_ptr(&ctx.allocator, NodePtr::NIL);
+
+        let layers = SingletonLayer::new(
+            launcher_id,
+            NftStateLayer::new(
+                metadata,
+                Bytes32::default(),
+                NftOwnershipLayer::new(
+                    None,
+                    RoyaltyTransferLayer::new(launcher_id, Bytes32::default(), 300),
+                    StandardLayer::new(PublicKey::default()),
+                ),
+            ),
+        );
+
+        let puzzle = layers.construct_puzzle(&mut ctx)?;
+        let tree = PuzzleRegistry::new().identify_ptr(&ctx.allocator, puzzle)?;
+
+        assert_eq!(
+            tree.layer_names(),
+            [
+                "singleton",
+                "nft_state_layer",
+                "nft_ownership_layer",
+                "standard"
+            ]
+        );
+
+        let royalty = tree
+            .find::<RoyaltyTransferLayer>()
+            .expect("royalty transfer layer");
+        assert_eq!(royalty.royalty_ten_thousandths, 300);
+
+        let singleton = tree
+            .as_layer()
+            .and_then(IdentifiedLayer::layer::<SingletonLayer<Puzzle>>)
+            .expect("singleton layer");
+        assert_eq!(singleton.launcher_id, launcher_id);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_identify_unknown_inner_puzzle() -> anyhow::Result<()> {
+        let mut ctx = SpendContext::new();
+
+        let inner_puzzle = ctx.alloc(&1)?;
+        let puzzle = CatLayer::new(
+            Bytes32::default(),
+            Puzzle::parse(&ctx.allocator, inner_puzzle),
+        )
+        .construct_puzzle(&mut ctx)?;
+
+        let tree = PuzzleRegistry::new().identify_ptr(&ctx.allocator, puzzle)?;
+        assert_eq!(tree.layer_names(), ["cat"]);
+        assert!(tree.innermost().is_unknown());
+        assert_eq!(tree.innermost().puzzle().ptr(), inner_puzzle);
+
+        let tree = PuzzleRegistry::empty().identify_ptr(&ctx.allocator, puzzle)?;
+        assert!(tree.is_unknown());
+
+        Ok(())
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    struct WrapperLayer {
+        inner_puzzle: Puzzle,
+    }
+
+    #[derive(ToClvm, FromClvm)]
+    #[clvm(curry)]
+    struct WrapperArgs<I> {
+        inner_puzzle: I,
+    }
+
+    impl Layer for WrapperLayer {
+        type Solution = NodePtr;
+
+        fn parse_puzzle(
+            allocator: &Allocator,
+            puzzle: Puzzle,
+        ) -> Result<Option<Self>, DriverError> {
+            let Some(puzzle) = puzzle.as_curried() else {
+                return Ok(None);
+            };
+
+            if puzzle.mod_hash != tree_hash_atom(&[5]) {
+                return Ok(None);
+            }
+
+            let args = WrapperArgs::<NodePtr>::from_clvm(allocator, puzzle.args)?;
+
+            Ok(Some(Self {
+                inner_puzzle: Puzzle::parse(allocator, args.inner_puzzle),
+            }))
+        }
+
+        fn parse_solution(
+            _allocator: &Allocator,
+            solution: NodePtr,
+        ) -> Result<Self::Solution, DriverError> {
+            Ok(solution)
+        }
+
+        fn construct_puzzle(&self, ctx: &mut SpendContext) -> Result<NodePtr, DriverError> {
+            let program = ctx.alloc(&5)?;
+            ctx.alloc(&CurriedProgram {
+                program,
+                args: WrapperArgs {
+                    inner_puzzle: self.inner_puzzle.ptr(),
+                },
+            })
+        }
+
+        fn construct_solution(
+            &self,
+            _ctx: &mut SpendContext,
+            solution: Self::Solution,
+        ) -> Result<NodePtr, DriverError> {
+            Ok(solution)
+        }
+    }
+
+    #[test]
+    fn test_custom_layer() -> anyhow::Result<()> {
+        let mut ctx = SpendContext::new();
+
+        let inner_puzzle = StandardLayer::new(PublicKey::default()).construct_puzzle(&mut ctx)?;
+        let puzzle = WrapperLayer {
+            inner_puzzle: Puzzle::parse(&ctx.allocator, inner_puzzle),
+        }
+        .construct_puzzle(&mut ctx)?;
+
+        let mut registry = PuzzleRegistry::new();
+        assert!(registry.identify_ptr(&ctx.allocator, puzzle)?.is_unknown());
+
+        registry.register::<WrapperLayer>(tree_hash_atom(&[5]), "wrapper", |layer| {
+            vec![layer.inner_puzzle]
+        });
+
+        let tree = registry.identify_ptr(&ctx.allocator, puzzle)?;
+        assert_eq!(tree.layer_names(), ["wrapper", "standard"]);
+        assert!(tree.find::<WrapperLayer>().is_some());
+
+        Ok(())
+    }
+}
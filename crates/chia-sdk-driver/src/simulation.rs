@@ -0,0 +1,163 @@
+use chia_protocol::{Bytes32, Coin};
+use chia_sdk_types::{Condition, MAINNET_CONSTANTS};
+use clvmr::Allocator;
+use indexmap::IndexMap;
+
+use crate::{
+    CatLayer, CatVersion, CoinStateLookup, Layer, Puzzle, SpendBundleValidator, SpendContext,
+    ValidationDiagnostic,
+};
+
+/// The result of running the coin spends in a [`SpendContext`] without submitting them.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Simulation {
+    /// The coins created by the spends, including ephemeral coins.
+    pub additions: Vec<Coin>,
+    /// The coins being spent.
+    pub removals: Vec<Coin>,
+    /// The net change in value of each puzzle hash, which is negative if it lost value.
+    pub puzzle_hash_deltas: IndexMap<Bytes32, i128>,
+    /// The net change in value of each asset, where `None` is XCH and `Some` is the asset id and version of a CAT.
+    /// CAT1 and CAT2 are different tokens, even if they have the same asset id.
+    /// The XCH delta is the negative of the fee, unless value is created.
+    pub asset_deltas: IndexMap<Option<(Bytes32, CatVersion)>, i128>,
+    /// The problems that would cause the spends to be rejected, other than the signature.
+    pub diagnostics: Vec<ValidationDiagnostic>,
+}
+
+impl Simulation {
+    /// Whether the spends would be accepted, assuming they are signed correctly.
+    pub fn is_valid(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    /// The fee paid by the spends, which is the XCH that isn't accounted for by the additions.
+    pub fn fee(&self) -> i128 {
+        -self.asset_deltas.get(&None).copied().unwrap_or(0)
+    }
+}
+
+impl SpendContext {
+    /// Runs the coin spends against the given coin states, and checks their conditions across spends.
+    ///
+    /// This doesn't know the peak of the blockchain, so time locks aren't checked,
+    /// and neither is the signature. Use [`SpendBundleValidator`] to validate a signed spend bundle.
+    pub fn simulate<L>(&self, coin_states: &L) -> Simulation
+    where
+        L: CoinStateLookup + ?Sized,
+    {
+        let coin_spends: Vec<_> = self.iter().cloned().collect();
+        let validator = SpendBundleValidator::without_time_locks(coin_states, &MAINNET_CONSTANTS);
+
+        let mut allocator = Allocator::new();
+        let (spends, diagnostics) = validator.check_coin_spends(&mut allocator, &coin_spends);
+
+        let mut simulation = Simulation {
+            diagnostics,
+            ..Default::default()
+        };
+
+        for spend in spends {
+            let puzzle = Puzzle::parse(&allocator, spend.puzzle);
+            let asset_id = CatLayer::<Puzzle>::parse_puzzle(&allocator, puzzle)
+                .ok()
+                .flatten()
+                .map(|cat| (cat.asset_id, cat.version));
+
+            let coin_id = spend.coin.coin_id();
+            let amount = i128::from(spend.coin.amount);
+
+            simulation.removals.push(spend.coin);
+            *simulation
+                .puzzle_hash_deltas
+                .entry(spend.coin.puzzle_hash)
+                .or_default() -= amount;
+            *simulation.asset_deltas.entry(asset_id).or_default() -= amount;
+
+            for (_, condition) in spend.conditions {
+                let Condition::CreateCoin(create_coin) = condition else {
+                    continue;
+                };
+
+                let amount = i128::from(create_coin.amount);
+
+                simulation.additions.push(Coin::new(
+                    coin_id,
+                    create_coin.puzzle_hash,
+                    create_coin.amount,
+                ));
+                *simulation
+                    .puzzle_hash_deltas
+                    .entry(create_coin.puzzle_hash)
+                    .or_default() += amount;
+                *simulation.asset_deltas.entry(asset_id).or_default() += amount;
+            }
+        }
+
+        simulation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_protocol::CoinState;
+    use chia_sdk_test::Simulator;
+    use chia_sdk_types::Conditions;
+
+    use crate::{Cat, StandardLayer};
+
+    use super::*;
+
+    #[test]
+    fn test_simulate() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1_000)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (issue_cat, cat) = Cat::single_issuance_eve(
+            ctx,
+            coin.coin_id(),
+            300,
+            Conditions::new().create_coin(puzzle_hash, 300, vec![puzzle_hash.into()]),
+        )?;
+        p2.spend(
+            ctx,
+            coin,
+            issue_cat
+                .create_coin(puzzle_hash, 600, Vec::new())
+                .reserve_fee(100),
+        )?;
+
+        let coin_states = vec![CoinState::new(coin, None, Some(0))];
+        let simulation = ctx.simulate(&coin_states);
+
+        assert!(simulation.is_valid());
+        assert_eq!(simulation.removals, vec![cat.coin, coin]);
+        assert_eq!(simulation.additions.len(), 3);
+        assert!(simulation
+            .additions
+            .contains(&cat.wrapped_child(puzzle_hash, 300).coin));
+        assert_eq!(simulation.fee(), 100);
+        assert_eq!(
+            simulation.asset_deltas[&Some((cat.asset_id, CatVersion::V2))],
+            0
+        );
+
+        // The eve CAT is ephemeral, so the only net effect is on the p2 and the new CAT.
+        let cat_puzzle_hash = cat.wrapped_child(puzzle_hash, 300).coin.puzzle_hash;
+        assert_eq!(simulation.puzzle_hash_deltas[&puzzle_hash], -400);
+        assert_eq!(simulation.puzzle_hash_deltas[&cat_puzzle_hash], 300);
+        assert_eq!(simulation.puzzle_hash_deltas[&cat.coin.puzzle_hash], 0);
+
+        // The XCH coin isn't known, so the spend would be rejected.
+        let simulation = ctx.simulate(&Vec::new());
+        assert!(!simulation.is_valid());
+        assert_eq!(simulation.diagnostics[0].coin_id, Some(coin.coin_id()));
+
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,854 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    hash::BuildHasher,
+};
+
+use chia_consensus::{
+    consensus_constants::ConsensusConstants,
+    gen::{flags::MEMPOOL_MODE, validation_error::ErrorCode},
+    spendbundle_validation::{get_flags_for_height_and_constants, validate_clvm_and_signature},
+};
+use chia_protocol::{Bytes, Bytes32, Coin, CoinSpend, CoinState, Program, SpendBundle};
+use chia_sdk_types::{
+    announcement_id, max_mempool_cost, Condition, MessageCommitment, MessageMode,
+};
+use clvm_traits::{FromClvm, ToClvm};
+use clvm_utils::tree_hash;
+use clvmr::{reduction::Reduction, run_program, Allocator, ChiaDialect, NodePtr};
+
+/// Looks up the state of coins on the blockchain, for use by the [`SpendBundleValidator`].
+pub trait CoinStateLookup {
+    /// The current state of a coin, or `None` if it doesn't exist.
+    fn coin_state(&self, coin_id: Bytes32) -> Option<CoinState>;
+
+    /// The timestamp of the transaction block at the given height, if known.
+    /// This is needed to check relative seconds and birth seconds, which are skipped otherwise.
+    fn block_timestamp(&self, _height: u32) -> Option<u64> {
+        None
+    }
+}
+
+impl<S> CoinStateLookup for HashMap<Bytes32, CoinState, S>
+where
+    S: BuildHasher,
+{
+    fn coin_state(&self, coin_id: Bytes32) -> Option<CoinState> {
+        self.get(&coin_id).copied()
+    }
+}
+
+impl CoinStateLookup for [CoinState] {
+    fn coin_state(&self, coin_id: Bytes32) -> Option<CoinState> {
+        self.iter()
+            .find(|coin_state| coin_state.coin.coin_id() == coin_id)
+            .copied()
+    }
+}
+
+impl CoinStateLookup for Vec<CoinState> {
+    fn coin_state(&self, coin_id: Bytes32) -> Option<CoinState> {
+        self.as_slice().coin_state(coin_id)
+    }
+}
+
+/// A reason why a spend bundle would be rejected, along with the spend and condition responsible.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationDiagnostic {
+    /// The consensus error that would be raised.
+    pub error: ErrorCode,
+    /// The index of the coin spend in the spend bundle, if the error can be attributed to one.
+    pub spend_index: Option<usize>,
+    /// The id of the coin being spent, if the error can be attributed to a spend.
+    pub coin_id: Option<Bytes32>,
+    /// The index of the condition in the output of the spend, if the error can be attributed to one.
+    pub condition_index: Option<usize>,
+    /// The condition that failed.
+    pub condition: Option<Condition<Program>>,
+}
+
+impl ValidationDiagnostic {
+    fn new(error: ErrorCode) -> Self {
+        Self {
+            error,
+            spend_index: None,
+            coin_id: None,
+            condition_index: None,
+            condition: None,
+        }
+    }
+
+    fn spend(error: ErrorCode, spend: &SpendOutput) -> Self {
+        Self {
+            spend_index: Some(spend.index),
+            coin_id: Some(spend.coin.coin_id()),
+            ..Self::new(error)
+        }
+    }
+
+    fn condition(
+        error: ErrorCode,
+        allocator: &Allocator,
+        spend: &SpendOutput,
+        condition_index: usize,
+    ) -> Self {
+        let condition =
+            Condition::<Program>::from_clvm(allocator, spend.conditions[condition_index].0).ok();
+
+        Self {
+            condition_index: Some(condition_index),
+            condition,
+            ..Self::spend(error, spend)
+        }
+    }
+}
+
+impl fmt::Display for ValidationDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.error)?;
+
+        if let (Some(spend_index), Some(coin_id)) = (self.spend_index, self.coin_id) {
+            write!(f, " in spend {spend_index} (coin {coin_id})")?;
+        }
+
+        if let Some(condition_index) = self.condition_index {
+            write!(f, " at condition {condition_index}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Validates a spend bundle against a set of coin states without submitting it, and reports
+/// every problem it finds rather than stopping at the first one.
+///
+/// The signature and cost are checked with the same code that full nodes use, against the cost
+/// limit of the mempool rather than that of a block. Time locks,
+/// announcements, messages, concurrent spends and ephemeral coins are checked for each condition,
+/// so that the spend and condition responsible for a failure can be reported.
+#[derive(Debug, Clone, Copy)]
+pub struct SpendBundleValidator<'a, L: ?Sized> {
+    lookup: &'a L,
+    height: u32,
+    timestamp: u64,
+    constants: &'a ConsensusConstants,
+    time_locks: bool,
+}
+
+#[derive(Debug)]
+pub(crate) struct SpendOutput {
+    index: usize,
+    pub(crate) coin: Coin,
+    pub(crate) puzzle: NodePtr,
+    /// The height the coin was created at, if it has been confirmed on the blockchain.
+    created_height: Option<u32>,
+    /// Whether the coin exists but hasn't been confirmed yet, so it has no birth height.
+    unconfirmed: bool,
+    /// Whether the coin is created by another spend in the same spend bundle.
+    ephemeral: bool,
+    pub(crate) conditions: Vec<(NodePtr, Condition<NodePtr>)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct MessageKey {
+    mode: u8,
+    message: Bytes,
+    sender: MessageCommitment,
+    receiver: MessageCommitment,
+}
+
+impl<'a, L> SpendBundleValidator<'a, L>
+where
+    L: CoinStateLookup + ?Sized,
+{
+    /// Creates a validator for the given coin states, and the height and timestamp of the peak transaction block.
+    pub fn new(
+        lookup: &'a L,
+        height: u32,
+        timestamp: u64,
+        constants: &'a ConsensusConstants,
+    ) -> Self {
+        Self {
+            lookup,
+            height,
+            timestamp,
+            constants,
+            time_locks: true,
+        }
+    }
+
+    /// Creates a validator that doesn't know the peak, so time locks aren't checked.
+    pub(crate) fn without_time_locks(lookup: &'a L, constants: &'a ConsensusConstants) -> Self {
+        Self {
+            lookup,
+            height: u32::MAX,
+            timestamp: u64::MAX,
+            constants,
+            time_locks: false,
+        }
+    }
+
+    /// Validates the spend bundle and returns every problem that was found.
+    /// If the list is empty, the spend bundle should be accepted.
+    pub fn validate(&self, spend_bundle: &SpendBundle) -> Vec<ValidationDiagnostic> {
+        let mut allocator = Allocator::new();
+        let (_spends, mut diagnostics) =
+            self.check_coin_spends(&mut allocator, &spend_bundle.coin_spends);
+
+        if spend_bundle.coin_spends.is_empty() {
+            return diagnostics;
+        }
+
+        // The signature and cost can only be checked by the full validation, which also repeats
+        // most of the checks above. Only report its error if it hasn't already been found.
+        if let Err(error) = validate_clvm_and_signature(
+            spend_bundle,
+            max_mempool_cost(self.constants),
+            self.constants,
+            self.height,
+        ) {
+            if !diagnostics
+                .iter()
+                .any(|diagnostic| diagnostic.error == error)
+            {
+                diagnostics.push(ValidationDiagnostic::new(error));
+            }
+        }
+
+        diagnostics
+    }
+
+    /// Runs the coin spends and checks their conditions, but not the signature or cost.
+    pub(crate) fn check_coin_spends(
+        &self,
+        allocator: &mut Allocator,
+        coin_spends: &[CoinSpend],
+    ) -> (Vec<SpendOutput>, Vec<ValidationDiagnostic>) {
+        let mut diagnostics = Vec::new();
+        let mut spends = Vec::new();
+
+        if coin_spends.is_empty() {
+            diagnostics.push(ValidationDiagnostic::new(ErrorCode::InvalidSpendBundle));
+            return (spends, diagnostics);
+        }
+
+        for (index, coin_spend) in coin_spends.iter().enumerate() {
+            match self.run_spend(allocator, index, coin_spend) {
+                Ok(spend) => spends.push(spend),
+                Err(error) => diagnostics.push(ValidationDiagnostic {
+                    spend_index: Some(index),
+                    coin_id: Some(coin_spend.coin.coin_id()),
+                    ..ValidationDiagnostic::new(error)
+                }),
+            }
+        }
+
+        self.check_coins(&mut spends, &mut diagnostics);
+        self.check_conditions(allocator, &spends, &mut diagnostics);
+
+        (spends, diagnostics)
+    }
+
+    fn run_spend(
+        &self,
+        allocator: &mut Allocator,
+        index: usize,
+        coin_spend: &CoinSpend,
+    ) -> Result<SpendOutput, ErrorCode> {
+        let puzzle = coin_spend
+            .puzzle_reveal
+            .to_clvm(allocator)
+            .map_err(|_| ErrorCode::InvalidSpendBundle)?;
+        let solution = coin_spend
+            .solution
+            .to_clvm(allocator)
+            .map_err(|_| ErrorCode::InvalidSpendBundle)?;
+
+        if tree_hash(allocator, puzzle) != coin_spend.coin.puzzle_hash.into() {
+            return Err(ErrorCode::WrongPuzzleHash);
+        }
+
+        let flags = get_flags_for_height_and_constants(self.height, self.constants) | MEMPOOL_MODE;
+
+        let Reduction(_cost, output) = run_program(
+            allocator,
+            &ChiaDialect::new(flags),
+            puzzle,
+            solution,
+            max_mempool_cost(self.constants),
+        )
+        .map_err(|_| ErrorCode::GeneratorRuntimeError)?;
+
+        let items = Vec::<NodePtr>::from_clvm(allocator, output)
+            .map_err(|_| ErrorCode::InvalidCondition)?;
+
+        let mut conditions = Vec::with_capacity(items.len());
+
+        for item in items {
+            let condition = Condition::<NodePtr>::from_clvm(allocator, item)
+                .map_err(|_| ErrorCode::InvalidCondition)?;
+            conditions.push((item, condition));
+        }
+
+        Ok(SpendOutput {
+            index,
+            coin: coin_spend.coin,
+            puzzle,
+            created_height: None,
+            unconfirmed: false,
+            ephemeral: false,
+            conditions,
+        })
+    }
+
+    /// Checks that every coin exists and is unspent, or is created in the same spend bundle.
+    fn check_coins(&self, spends: &mut [SpendOutput], diagnostics: &mut Vec<ValidationDiagnostic>) {
+        let created: HashSet<Bytes32> = spends
+            .iter()
+            .flat_map(|spend| {
+                spend.conditions.iter().filter_map(|(_, condition)| {
+                    let Condition::CreateCoin(create_coin) = condition else {
+                        return None;
+                    };
+                    Some(
+                        Coin::new(
+                            spend.coin.coin_id(),
+                            create_coin.puzzle_hash,
+                            create_coin.amount,
+                        )
+                        .coin_id(),
+                    )
+                })
+            })
+            .collect();
+
+        let mut spent = HashSet::new();
+
+        for spend in spends {
+            let coin_id = spend.coin.coin_id();
+
+            if !spent.insert(coin_id) {
+                diagnostics.push(ValidationDiagnostic::spend(ErrorCode::DoubleSpend, spend));
+                continue;
+            }
+
+            if created.contains(&coin_id) {
+                spend.ephemeral = true;
+                continue;
+            }
+
+            match self.lookup.coin_state(coin_id) {
+                None => diagnostics.push(ValidationDiagnostic::spend(
+                    ErrorCode::UnknownUnspent,
+                    spend,
+                )),
+                Some(coin_state) if coin_state.spent_height.is_some() => {
+                    diagnostics.push(ValidationDiagnostic::spend(ErrorCode::DoubleSpend, spend));
+                }
+                Some(coin_state) => {
+                    spend.created_height = coin_state.created_height;
+                    spend.unconfirmed = coin_state.created_height.is_none();
+                }
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn check_conditions(
+        &self,
+        allocator: &Allocator,
+        spends: &[SpendOutput],
+        diagnostics: &mut Vec<ValidationDiagnostic>,
+    ) {
+        let mut coin_announcements = HashSet::new();
+        let mut puzzle_announcements = HashSet::new();
+        let mut coin_ids = HashSet::new();
+        let mut puzzle_hashes = HashSet::new();
+        let mut sent_messages = HashMap::<MessageKey, Vec<(usize, usize)>>::new();
+        let mut received_messages = HashMap::<MessageKey, Vec<(usize, usize)>>::new();
+        let mut outputs = HashSet::new();
+
+        let mut removals: u128 = 0;
+        let mut additions: u128 = 0;
+
+        // First collect everything that the spends create, so that assertions can be checked in any order.
+        for (spend_position, spend) in spends.iter().enumerate() {
+            let coin_id = spend.coin.coin_id();
+            coin_ids.insert(coin_id);
+            puzzle_hashes.insert(spend.coin.puzzle_hash);
+            removals += u128::from(spend.coin.amount);
+
+            for (condition_index, (_, condition)) in spend.conditions.iter().enumerate() {
+                match condition {
+                    Condition::CreateCoin(create_coin) => {
+                        additions += u128::from(create_coin.amount);
+                        if !outputs.insert((coin_id, create_coin.puzzle_hash, create_coin.amount)) {
+                            diagnostics.push(ValidationDiagnostic::condition(
+                                ErrorCode::DuplicateOutput,
+                                allocator,
+                                spend,
+                                condition_index,
+                            ));
+                        }
+                    }
+                    Condition::CreateCoinAnnouncement(announcement) => {
+                        coin_announcements
+                            .insert(announcement_id(coin_id, announcement.message.as_ref()));
+                    }
+                    Condition::CreatePuzzleAnnouncement(announcement) => {
+                        puzzle_announcements.insert(announcement_id(
+                            spend.coin.puzzle_hash,
+                            &announcement.message,
+                        ));
+                    }
+                    Condition::SendMessage(send) => {
+                        let key = send_message_key(
+                            allocator,
+                            spend,
+                            send.mode,
+                            &send.message,
+                            &send.data,
+                        );
+                        match key {
+                            Some(key) => sent_messages
+                                .entry(key)
+                                .or_default()
+                                .push((spend_position, condition_index)),
+                            None => diagnostics.push(ValidationDiagnostic::condition(
+                                message_error(send.mode),
+                                allocator,
+                                spend,
+                                condition_index,
+                            )),
+                        }
+                    }
+                    Condition::ReceiveMessage(receive) => {
+                        let key = receive_message_key(
+                            allocator,
+                            spend,
+                            receive.mode,
+                            &receive.message,
+                            &receive.data,
+                        );
+                        match key {
+                            Some(key) => received_messages
+                                .entry(key)
+                                .or_default()
+                                .push((spend_position, condition_index)),
+                            None => diagnostics.push(ValidationDiagnostic::condition(
+                                message_error(receive.mode),
+                                allocator,
+                                spend,
+                                condition_index,
+                            )),
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        if additions > removals {
+            diagnostics.push(ValidationDiagnostic::new(ErrorCode::MintingCoin));
+        }
+
+        let surplus = removals.saturating_sub(additions);
+        let mut reserved_fee: u128 = 0;
+
+        for spend in spends {
+            for (condition_index, (_, condition)) in spend.conditions.iter().enumerate() {
+                if let Condition::ReserveFee(reserve_fee) = condition {
+                    reserved_fee += u128::from(reserve_fee.amount);
+                }
+
+                let error = match condition {
+                    Condition::ReserveFee(..) => {
+                        (reserved_fee > surplus).then_some(ErrorCode::ReserveFeeConditionFailed)
+                    }
+                    Condition::AssertCoinAnnouncement(assertion) => (!coin_announcements
+                        .contains(&assertion.announcement_id))
+                    .then_some(ErrorCode::AssertCoinAnnouncementFailed),
+                    Condition::AssertPuzzleAnnouncement(assertion) => (!puzzle_announcements
+                        .contains(&assertion.announcement_id))
+                    .then_some(ErrorCode::AssertPuzzleAnnouncementFailed),
+                    Condition::AssertConcurrentSpend(assertion) => (!coin_ids
+                        .contains(&assertion.coin_id))
+                    .then_some(ErrorCode::AssertConcurrentSpendFailed),
+                    Condition::AssertConcurrentPuzzle(assertion) => (!puzzle_hashes
+                        .contains(&assertion.puzzle_hash))
+                    .then_some(ErrorCode::AssertConcurrentPuzzleFailed),
+                    Condition::AssertMyCoinId(assertion) => (assertion.coin_id
+                        != spend.coin.coin_id())
+                    .then_some(ErrorCode::AssertMyCoinIdFailed),
+                    Condition::AssertMyParentId(assertion) => (assertion.parent_id
+                        != spend.coin.parent_coin_info)
+                        .then_some(ErrorCode::AssertMyParentIdFailed),
+                    Condition::AssertMyPuzzleHash(assertion) => (assertion.puzzle_hash
+                        != spend.coin.puzzle_hash)
+                        .then_some(ErrorCode::AssertMyPuzzleHashFailed),
+                    Condition::AssertMyAmount(assertion) => (assertion.amount != spend.coin.amount)
+                        .then_some(ErrorCode::AssertMyAmountFailed),
+                    Condition::AssertEphemeral(..) => {
+                        (!spend.ephemeral).then_some(ErrorCode::AssertEphemeralFailed)
+                    }
+                    condition => self.check_time_lock(spend, condition),
+                };
+
+                if let Some(error) = error {
+                    diagnostics.push(ValidationDiagnostic::condition(
+                        error,
+                        allocator,
+                        spend,
+                        condition_index,
+                    ));
+                }
+            }
+        }
+
+        // Every message must be received exactly once.
+        for (key, senders) in &sent_messages {
+            let receivers = received_messages.get(key).map_or(0, Vec::len);
+            for &(spend_position, condition_index) in senders.iter().skip(receivers) {
+                diagnostics.push(ValidationDiagnostic::condition(
+                    ErrorCode::MessageNotSentOrReceived,
+                    allocator,
+                    &spends[spend_position],
+                    condition_index,
+                ));
+            }
+        }
+
+        for (key, receivers) in &received_messages {
+            let senders = sent_messages.get(key).map_or(0, Vec::len);
+            for &(spend_position, condition_index) in receivers.iter().skip(senders) {
+                diagnostics.push(ValidationDiagnostic::condition(
+                    ErrorCode::MessageNotSentOrReceived,
+                    allocator,
+                    &spends[spend_position],
+                    condition_index,
+                ));
+            }
+        }
+    }
+
+    fn check_time_lock(
+        &self,
+        spend: &SpendOutput,
+        condition: &Condition<NodePtr>,
+    ) -> Option<ErrorCode> {
+        let height = self.height;
+        let timestamp = self.timestamp;
+
+        let is_relative = matches!(
+            condition,
+            Condition::AssertHeightRelative(..)
+                | Condition::AssertBeforeHeightRelative(..)
+                | Condition::AssertSecondsRelative(..)
+                | Condition::AssertBeforeSecondsRelative(..)
+        );
+
+        if is_relative && spend.ephemeral {
+            return Some(ErrorCode::EphemeralRelativeCondition);
+        }
+
+        if !self.time_locks {
+            return None;
+        }
+
+        // An unconfirmed coin can't have waited for a relative time lock, since it isn't in a block yet.
+        if spend.unconfirmed {
+            match condition {
+                Condition::AssertHeightRelative(..) => {
+                    return Some(ErrorCode::AssertHeightRelativeFailed);
+                }
+                Condition::AssertSecondsRelative(..) => {
+                    return Some(ErrorCode::AssertSecondsRelativeFailed);
+                }
+                _ => {}
+            }
+        }
+
+        // Ephemeral and unconfirmed coins don't have a birth height yet, and unknown coins have already been reported.
+        let created_height = spend.created_height;
+        let created_timestamp =
+            created_height.and_then(|created_height| self.lookup.block_timestamp(created_height));
+
+        match condition {
+            Condition::AssertHeightAbsolute(assertion) => {
+                (assertion.height > height).then_some(ErrorCode::AssertHeightAbsoluteFailed)
+            }
+            Condition::AssertBeforeHeightAbsolute(assertion) => {
+                (assertion.height <= height).then_some(ErrorCode::AssertBeforeHeightAbsoluteFailed)
+            }
+            Condition::AssertSecondsAbsolute(assertion) => {
+                (assertion.seconds > timestamp).then_some(ErrorCode::AssertSecondsAbsoluteFailed)
+            }
+            Condition::AssertBeforeSecondsAbsolute(assertion) => (assertion.seconds <= timestamp)
+                .then_some(ErrorCode::AssertBeforeSecondsAbsoluteFailed),
+            Condition::AssertHeightRelative(assertion) => {
+                (u64::from(created_height?) + u64::from(assertion.height) > u64::from(height))
+                    .then_some(ErrorCode::AssertHeightRelativeFailed)
+            }
+            Condition::AssertBeforeHeightRelative(assertion) => {
+                (u64::from(created_height?) + u64::from(assertion.height) <= u64::from(height))
+                    .then_some(ErrorCode::AssertBeforeHeightRelativeFailed)
+            }
+            Condition::AssertSecondsRelative(assertion) => {
+                (created_timestamp?.saturating_add(assertion.seconds) > timestamp)
+                    .then_some(ErrorCode::AssertSecondsRelativeFailed)
+            }
+            Condition::AssertBeforeSecondsRelative(assertion) => {
+                (created_timestamp?.saturating_add(assertion.seconds) <= timestamp)
+                    .then_some(ErrorCode::AssertBeforeSecondsRelativeFailed)
+            }
+            Condition::AssertMyBirthHeight(assertion) => (created_height? != assertion.height)
+                .then_some(ErrorCode::AssertMyBirthHeightFailed),
+            Condition::AssertMyBirthSeconds(assertion) => (created_timestamp? != assertion.seconds)
+                .then_some(ErrorCode::AssertMyBirthSecondsFailed),
+            _ => None,
+        }
+    }
+}
+
+fn message_error(mode: u8) -> ErrorCode {
+    if MessageMode::from_u8(mode).is_none() {
+        ErrorCode::InvalidMessageMode
+    } else {
+        ErrorCode::InvalidMessage
+    }
+}
+
+fn send_message_key(
+    allocator: &Allocator,
+    spend: &SpendOutput,
+    mode: u8,
+    message: &Bytes,
+    data: &[NodePtr],
+) -> Option<MessageKey> {
+    let message_mode = MessageMode::from_u8(mode)?;
+
+    Some(MessageKey {
+        mode,
+        message: message.clone(),
+        sender: MessageCommitment::from_coin(&spend.coin, message_mode.sender),
+        receiver: MessageCommitment::from_data(allocator, message_mode.receiver, data).ok()?,
+    })
+}
+
+fn receive_message_key(
+    allocator: &Allocator,
+    spend: &SpendOutput,
+    mode: u8,
+    message: &Bytes,
+    data: &[NodePtr],
+) -> Option<MessageKey> {
+    let message_mode = MessageMode::from_u8(mode)?;
+
+    Some(MessageKey {
+        mode,
+        message: message.clone(),
+        sender: MessageCommitment::from_data(allocator, message_mode.sender, data).ok()?,
+        receiver: MessageCommitment::from_coin(&spend.coin, message_mode.receiver),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_bls::Signature;
+    use chia_sdk_test::{sign_transaction, Simulator};
+    use chia_sdk_types::{Conditions, TESTNET11_CONSTANTS};
+
+    use crate::{Spend, SpendContext, StandardLayer};
+
+    use super::*;
+
+    fn coin_states(sim: &Simulator, coin_spends: &[CoinSpend]) -> Vec<CoinState> {
+        coin_spends
+            .iter()
+            .filter_map(|coin_spend| sim.coin_state(coin_spend.coin.coin_id()))
+            .collect()
+    }
+
+    fn validate(sim: &Simulator, spend_bundle: &SpendBundle) -> Vec<ValidationDiagnostic> {
+        let coin_states = coin_states(sim, &spend_bundle.coin_spends);
+        SpendBundleValidator::new(&coin_states, sim.height(), 0, &TESTNET11_CONSTANTS)
+            .validate(spend_bundle)
+    }
+
+    /// Spends a coin with the puzzle `1`, which outputs the solution as its conditions.
+    fn spend_unchecked(
+        ctx: &mut SpendContext,
+        sim: &mut Simulator,
+        amount: u64,
+        conditions: impl FnOnce(&mut SpendContext, Coin) -> anyhow::Result<Conditions>,
+    ) -> anyhow::Result<Coin> {
+        let puzzle = ctx.alloc(&1)?;
+        let coin = sim.new_coin(ctx.tree_hash(puzzle).into(), amount);
+        let conditions = conditions(ctx, coin)?;
+        let solution = ctx.alloc(&conditions)?;
+        ctx.spend(coin, Spend::new(puzzle, solution))?;
+        Ok(coin)
+    }
+
+    #[test]
+    fn test_valid_spend_bundle() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1)?;
+        StandardLayer::new(pk).spend(
+            ctx,
+            coin,
+            Conditions::new()
+                .create_coin(puzzle_hash, 1, Vec::new())
+                .assert_height_absolute(sim.height()),
+        )?;
+
+        let coin_spends = ctx.take();
+        let signature = sign_transaction(&coin_spends, &[sk], &TESTNET11_CONSTANTS)?;
+        let spend_bundle = SpendBundle::new(coin_spends, signature);
+
+        assert_eq!(validate(&sim, &spend_bundle), Vec::new());
+        sim.new_transaction(spend_bundle.clone(), &TESTNET11_CONSTANTS)?;
+
+        // The coin has been spent now.
+        let diagnostics = validate(&sim, &spend_bundle);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].error, ErrorCode::DoubleSpend);
+        assert_eq!(diagnostics[0].coin_id, Some(coin.coin_id()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_failing_assertions() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        let coin = spend_unchecked(ctx, &mut sim, 1, |_ctx, _coin| {
+            Ok(Conditions::new()
+                .assert_height_absolute(100)
+                .assert_coin_announcement(Bytes32::new([1; 32]))
+                .assert_concurrent_spend(Bytes32::new([2; 32]))
+                .assert_ephemeral()
+                .reserve_fee(2))
+        })?;
+
+        let spend_bundle = SpendBundle::new(ctx.take(), Signature::default());
+        let diagnostics = validate(&sim, &spend_bundle);
+
+        let errors: Vec<(ErrorCode, Option<usize>)> = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.error, diagnostic.condition_index))
+            .collect();
+
+        assert_eq!(
+            errors,
+            vec![
+                (ErrorCode::AssertHeightAbsoluteFailed, Some(0)),
+                (ErrorCode::AssertCoinAnnouncementFailed, Some(1)),
+                (ErrorCode::AssertConcurrentSpendFailed, Some(2)),
+                (ErrorCode::AssertEphemeralFailed, Some(3)),
+                (ErrorCode::ReserveFeeConditionFailed, Some(4)),
+            ]
+        );
+
+        for diagnostic in &diagnostics {
+            assert_eq!(diagnostic.spend_index, Some(0));
+            assert_eq!(diagnostic.coin_id, Some(coin.coin_id()));
+        }
+
+        assert_eq!(
+            diagnostics[0].condition,
+            Some(Condition::assert_height_absolute(100))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_messages_and_ephemeral_coins() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        let puzzle = ctx.alloc(&1)?;
+        let puzzle_hash: Bytes32 = ctx.tree_hash(puzzle).into();
+
+        let receiver = spend_unchecked(ctx, &mut sim, 1, |_ctx, _coin| {
+            Ok(Conditions::new().create_coin(puzzle_hash, 1, Vec::new()))
+        })?;
+        let ephemeral = Coin::new(receiver.coin_id(), puzzle_hash, 1);
+
+        // The ephemeral coin receives a message from the sender, which it asserts is spent concurrently.
+        let sender = spend_unchecked(ctx, &mut sim, 1, |ctx, _coin| {
+            let coin_id = ctx.alloc(&ephemeral.coin_id())?;
+            Ok(Conditions::new().send_message(0b11_1111, Bytes::new(vec![42]), vec![coin_id]))
+        })?;
+
+        let sender_id = ctx.alloc(&sender.coin_id())?;
+        let receive = Conditions::new()
+            .receive_message(0b11_1111, Bytes::new(vec![42]), vec![sender_id])
+            .assert_ephemeral()
+            .assert_concurrent_spend(sender.coin_id());
+        let solution = ctx.alloc(&receive)?;
+        ctx.spend(ephemeral, Spend::new(puzzle, solution))?;
+
+        let coin_spends = ctx.take();
+        let spend_bundle = SpendBundle::new(coin_spends.clone(), Signature::default());
+        assert_eq!(validate(&sim, &spend_bundle), Vec::new());
+
+        // Without the ephemeral spend, the message is never received.
+        let spend_bundle = SpendBundle::new(coin_spends[..2].to_vec(), Signature::default());
+        let diagnostics = validate(&sim, &spend_bundle);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].error, ErrorCode::MessageNotSentOrReceived);
+        assert_eq!(diagnostics[0].coin_id, Some(sender.coin_id()));
+        assert_eq!(diagnostics[0].condition_index, Some(0));
+
+        // Without the parent spend, the ephemeral coin doesn't exist.
+        let spend_bundle = SpendBundle::new(coin_spends[1..].to_vec(), Signature::default());
+        let diagnostics = validate(&sim, &spend_bundle);
+
+        assert_eq!(diagnostics[0].error, ErrorCode::UnknownUnspent);
+        assert_eq!(diagnostics[0].spend_index, Some(1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_unconfirmed_relative_time_locks() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        let coin = spend_unchecked(ctx, &mut sim, 1, |_ctx, _coin| {
+            Ok(Conditions::new()
+                .assert_height_relative(0)
+                .assert_seconds_relative(0)
+                .assert_before_height_relative(10))
+        })?;
+
+        let spend_bundle = SpendBundle::new(ctx.take(), Signature::default());
+
+        // The coin is known, but hasn't been confirmed in a block yet.
+        let coin_states = vec![CoinState::new(coin, None, None)];
+        let diagnostics =
+            SpendBundleValidator::new(&coin_states, sim.height(), 0, &TESTNET11_CONSTANTS)
+                .validate(&spend_bundle);
+
+        let errors: Vec<(ErrorCode, Option<usize>)> = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.error, diagnostic.condition_index))
+            .collect();
+
+        assert_eq!(
+            errors,
+            vec![
+                (ErrorCode::AssertHeightRelativeFailed, Some(0)),
+                (ErrorCode::AssertSecondsRelativeFailed, Some(1)),
+            ]
+        );
+
+        Ok(())
+    }
+}
@@ -1,11 +1,11 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt};
 
 use chia_protocol::{Coin, CoinSpend, Program};
 use chia_puzzles::{
     cat::{
-        CAT_PUZZLE, CAT_PUZZLE_HASH, EVERYTHING_WITH_SIGNATURE_TAIL_PUZZLE,
-        EVERYTHING_WITH_SIGNATURE_TAIL_PUZZLE_HASH, GENESIS_BY_COIN_ID_TAIL_PUZZLE,
-        GENESIS_BY_COIN_ID_TAIL_PUZZLE_HASH,
+        CAT_PUZZLE, CAT_PUZZLE_HASH, CAT_PUZZLE_HASH_V1, CAT_PUZZLE_V1,
+        EVERYTHING_WITH_SIGNATURE_TAIL_PUZZLE, EVERYTHING_WITH_SIGNATURE_TAIL_PUZZLE_HASH,
+        GENESIS_BY_COIN_ID_TAIL_PUZZLE, GENESIS_BY_COIN_ID_TAIL_PUZZLE_HASH,
     },
     did::{DID_INNER_PUZZLE, DID_INNER_PUZZLE_HASH},
     nft::{
@@ -22,15 +22,16 @@ use chia_puzzles::{
     standard::{STANDARD_PUZZLE, STANDARD_PUZZLE_HASH},
 };
 use chia_sdk_types::run_puzzle;
-use clvm_traits::{FromClvm, ToClvm};
+use clvm_traits::{FromClvm, ToClvm, ToClvmError};
 use clvm_utils::{tree_hash, TreeHash};
-use clvmr::{serde::node_from_bytes, Allocator, NodePtr};
-
-use crate::{
-    DriverError, Spend, P2_DELEGATED_CONDITIONS_PUZZLE, P2_DELEGATED_CONDITIONS_PUZZLE_HASH,
-    P2_ONE_OF_MANY_PUZZLE, P2_ONE_OF_MANY_PUZZLE_HASH,
+use clvmr::{
+    allocator::Checkpoint,
+    serde::{node_from_bytes, node_to_bytes, serialized_length_from_bytes},
+    Allocator, NodePtr,
 };
 
+use crate::{DriverError, Spend};
+
 /// A wrapper around [`Allocator`] that caches puzzles and keeps track of a list of [`CoinSpend`].
 /// It's used to construct spend bundles in an easy and efficient way.
 #[derive(Debug, Default)]
@@ -38,6 +39,7 @@ pub struct SpendContext {
     pub allocator: Allocator,
     puzzles: HashMap<TreeHash, NodePtr>,
     coin_spends: Vec<CoinSpend>,
+    memory_limit: Option<usize>,
 }
 
 impl SpendContext {
@@ -45,6 +47,56 @@ impl SpendContext {
         Self::default()
     }
 
+    /// Creates a context whose allocator can't hold more than `memory_limit` bytes of atoms.
+    /// Allocating past the limit returns [`DriverError::MemoryLimit`] rather than growing the allocator further.
+    ///
+    /// Only the bytes of atoms count towards the limit. Pairs and small atoms are stored separately,
+    /// and are only bounded by the fixed maximum number of nodes of the [`Allocator`].
+    ///
+    /// # Panics
+    ///
+    /// The allocator uses 32 bit pointers, so this panics if the limit is zero or larger than [`u32::MAX`].
+    pub fn with_memory_limit(memory_limit: usize) -> Self {
+        Self {
+            allocator: Allocator::new_limited(memory_limit),
+            puzzles: HashMap::new(),
+            coin_spends: Vec::new(),
+            memory_limit: Some(memory_limit),
+        }
+    }
+
+    /// The number of bytes of atoms that are currently allocated.
+    /// This requires the `memory-usage` feature, which enables the allocation counters of `clvmr`.
+    #[cfg(feature = "memory-usage")]
+    pub fn memory_usage(&self) -> usize {
+        self.allocator.heap_size()
+    }
+
+    /// Replaces the allocator with an empty one, to free the memory used by previous spends.
+    /// The cached puzzles are allocated again, so they don't need to be reloaded.
+    ///
+    /// The [`CoinSpend`]s that have been collected are kept, but any [`NodePtr`] or checkpoint
+    /// created before the reset must no longer be used.
+    pub fn reset(&mut self) -> Result<(), DriverError> {
+        let puzzles = self
+            .puzzles
+            .iter()
+            .map(|(puzzle_hash, ptr)| Ok((*puzzle_hash, node_to_bytes(&self.allocator, *ptr)?)))
+            .collect::<Result<Vec<_>, DriverError>>()?;
+
+        self.allocator = match self.memory_limit {
+            Some(memory_limit) => Allocator::new_limited(memory_limit),
+            None => Allocator::new(),
+        };
+        self.puzzles.clear();
+
+        for (puzzle_hash, puzzle_bytes) in puzzles {
+            self.puzzle(puzzle_hash, &puzzle_bytes)?;
+        }
+
+        Ok(())
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = &CoinSpend> {
         self.coin_spends.iter()
     }
@@ -72,7 +124,14 @@ impl SpendContext {
     where
         T: ToClvm<Allocator>,
     {
-        Ok(value.to_clvm(&mut self.allocator)?)
+        value
+            .to_clvm(&mut self.allocator)
+            .map_err(|error| match (error, self.memory_limit) {
+                (ToClvmError::OutOfMemory, Some(memory_limit)) => {
+                    DriverError::MemoryLimit(memory_limit)
+                }
+                (error, _) => error.into(),
+            })
     }
 
     /// Extract a value from a node pointer.
@@ -112,6 +171,11 @@ impl SpendContext {
         self.puzzle(CAT_PUZZLE_HASH, &CAT_PUZZLE)
     }
 
+    /// Allocate the deprecated CAT1 puzzle and return its pointer.
+    pub fn cat_v1_puzzle(&mut self) -> Result<NodePtr, DriverError> {
+        self.puzzle(CAT_PUZZLE_HASH_V1, &CAT_PUZZLE_V1)
+    }
+
     /// Allocate the DID inner puzzle and return its pointer.
     pub fn did_inner_puzzle(&mut self) -> Result<NodePtr, DriverError> {
         self.puzzle(DID_INNER_PUZZLE_HASH, &DID_INNER_PUZZLE)
@@ -182,17 +246,32 @@ impl SpendContext {
         self.puzzle(SETTLEMENT_PAYMENTS_PUZZLE_HASH, &SETTLEMENT_PAYMENTS_PUZZLE)
     }
 
-    /// Allocate the p2 delegated conditions puzzle and return its pointer.
-    pub fn p2_delegated_conditions_puzzle(&mut self) -> Result<NodePtr, DriverError> {
-        self.puzzle(
-            P2_DELEGATED_CONDITIONS_PUZZLE_HASH,
-            &P2_DELEGATED_CONDITIONS_PUZZLE,
-        )
+    /// Saves the current state of the context, so that it can be restored with [`SpendContext::rollback`].
+    pub fn checkpoint(&self) -> SpendContextCheckpoint {
+        SpendContextCheckpoint {
+            allocator: self.allocator.checkpoint(),
+            puzzles: self.puzzles.clone(),
+            coin_spends: self.coin_spends.len(),
+        }
     }
 
-    /// Allocate the p2 one of many puzzle and return its pointer.
-    pub fn p2_one_of_many_puzzle(&mut self) -> Result<NodePtr, DriverError> {
-        self.puzzle(P2_ONE_OF_MANY_PUZZLE_HASH, &P2_ONE_OF_MANY_PUZZLE)
+    /// Restores the context to the state it was in when the checkpoint was created.
+    ///
+    /// This removes the [`CoinSpend`]s that were added since, and frees the nodes that were allocated since,
+    /// so any [`NodePtr`] created after the checkpoint must no longer be used.
+    ///
+    /// # Panics
+    ///
+    /// Checkpoints can only be restored in reverse order. This panics if the context has already been
+    /// rolled back to a state before the checkpoint, or if [`SpendContext::take`] was called since.
+    pub fn rollback(&mut self, checkpoint: SpendContextCheckpoint) {
+        assert!(
+            self.coin_spends.len() >= checkpoint.coin_spends,
+            "coin spends were removed since the checkpoint was created"
+        );
+        self.allocator.restore_checkpoint(&checkpoint.allocator);
+        self.puzzles = checkpoint.puzzles;
+        self.coin_spends.truncate(checkpoint.coin_spends);
     }
 
     /// Preload a puzzle into the cache.
@@ -214,13 +293,47 @@ impl SpendContext {
         if let Some(puzzle) = self.puzzles.get(&puzzle_hash) {
             Ok(*puzzle)
         } else {
-            let puzzle = node_from_bytes(&mut self.allocator, puzzle_bytes)?;
+            let checkpoint = self.allocator.checkpoint();
+
+            let puzzle = match node_from_bytes(&mut self.allocator, puzzle_bytes) {
+                Ok(puzzle) => puzzle,
+                Err(error) => {
+                    // Free the part of the puzzle that was allocated before the error.
+                    self.allocator.restore_checkpoint(&checkpoint);
+
+                    // If the bytes are a valid serialization, the allocator must have run out of space.
+                    if let Some(memory_limit) = self.memory_limit {
+                        if serialized_length_from_bytes(puzzle_bytes).is_ok() {
+                            return Err(DriverError::MemoryLimit(memory_limit));
+                        }
+                    }
+
+                    return Err(error.into());
+                }
+            };
+
             self.puzzles.insert(puzzle_hash, puzzle);
             Ok(puzzle)
         }
     }
 }
 
+/// A saved state of a [`SpendContext`], created by [`SpendContext::checkpoint`].
+pub struct SpendContextCheckpoint {
+    allocator: Checkpoint,
+    puzzles: HashMap<TreeHash, NodePtr>,
+    coin_spends: usize,
+}
+
+impl fmt::Debug for SpendContextCheckpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SpendContextCheckpoint")
+            .field("puzzles", &self.puzzles)
+            .field("coin_spends", &self.coin_spends)
+            .finish_non_exhaustive()
+    }
+}
+
 impl IntoIterator for SpendContext {
     type Item = CoinSpend;
     type IntoIter = std::vec::IntoIter<Self::Item>;
@@ -236,6 +349,128 @@ impl From<Allocator> for SpendContext {
             allocator,
             puzzles: HashMap::new(),
             coin_spends: Vec::new(),
+            memory_limit: None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chia_bls::PublicKey;
+    use chia_protocol::{Bytes, Bytes32};
+    use chia_sdk_types::Conditions;
+
+    use crate::{Cat, StandardLayer};
+
+    use super::*;
+
+    #[test]
+    fn test_rollback() -> anyhow::Result<()> {
+        let mut ctx = SpendContext::new();
+        let p2 = StandardLayer::new(PublicKey::default());
+
+        let coin = Coin::new(Bytes32::default(), Bytes32::default(), 1);
+        p2.spend(&mut ctx, coin, Conditions::new())?;
+        let kept = ctx.alloc(&[1, 2, 3])?;
+
+        let checkpoint = ctx.checkpoint();
+        let freed = ctx.alloc(&[4, 5, 6])?;
+
+        let (issue_cat, _cat) =
+            Cat::single_issuance_eve(&mut ctx, coin.coin_id(), 1, Conditions::new())?;
+        p2.spend(&mut ctx, coin, issue_cat)?;
+        assert_eq!(ctx.iter().count(), 3);
+        assert!(ctx
+            .get_puzzle(&GENESIS_BY_COIN_ID_TAIL_PUZZLE_HASH)
+            .is_some());
+
+        ctx.rollback(checkpoint);
+
+        assert_eq!(ctx.iter().count(), 1);
+        assert_eq!(ctx.alloc(&[4, 5, 6])?, freed);
+        assert!(ctx
+            .get_puzzle(&GENESIS_BY_COIN_ID_TAIL_PUZZLE_HASH)
+            .is_none());
+        assert_eq!(ctx.extract::<[u8; 3]>(kept)?, [1, 2, 3]);
+
+        // The puzzle is allocated again, since the cached pointer was freed.
+        ctx.genesis_by_coin_id_tail_puzzle()?;
+        assert!(ctx
+            .get_puzzle(&GENESIS_BY_COIN_ID_TAIL_PUZZLE_HASH)
+            .is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reset() -> anyhow::Result<()> {
+        let mut ctx = SpendContext::with_memory_limit(150_000);
+        let p2 = StandardLayer::new(PublicKey::default());
+
+        let coin = Coin::new(Bytes32::default(), Bytes32::default(), 1);
+        p2.spend(&mut ctx, coin, Conditions::new())?;
+        ctx.alloc(&Bytes::new(vec![1; 100_000]))?;
+        assert!(matches!(
+            ctx.alloc(&Bytes::new(vec![1; 100_000])),
+            Err(DriverError::MemoryLimit(150_000))
+        ));
+
+        ctx.reset()?;
+
+        // The memory used by the previous allocations has been freed.
+        ctx.alloc(&Bytes::new(vec![1; 100_000]))?;
+        assert_eq!(ctx.iter().count(), 1);
+
+        // The standard puzzle is still cached, and has been allocated again.
+        let puzzle = ctx
+            .get_puzzle(&STANDARD_PUZZLE_HASH)
+            .expect("standard puzzle");
+        assert_eq!(ctx.tree_hash(puzzle), STANDARD_PUZZLE_HASH);
+        assert_eq!(ctx.standard_puzzle()?, puzzle);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_memory_limit() -> anyhow::Result<()> {
+        let mut ctx = SpendContext::with_memory_limit(100);
+
+        // Only a few bytes are left, which isn't enough for a puzzle with a 32 byte atom.
+        ctx.alloc(&Bytes::new(vec![1; 90]))?;
+        let puzzle_bytes = [&[0xa0][..], &[1; 32]].concat();
+        assert!(matches!(
+            ctx.puzzle(TreeHash::new([1; 32]), &puzzle_bytes),
+            Err(DriverError::MemoryLimit(100))
+        ));
+        assert!(matches!(
+            ctx.alloc(&Bytes::new(vec![1; 200])),
+            Err(DriverError::MemoryLimit(100))
+        ));
+
+        // Invalid bytes are still reported as such.
+        assert!(matches!(
+            ctx.puzzle(TreeHash::new([2; 32]), &puzzle_bytes[..10]),
+            Err(DriverError::Io(..))
+        ));
+
+        let ptr = ctx.alloc(&[1, 2, 3])?;
+        assert_eq!(ctx.extract::<[u8; 3]>(ptr)?, [1, 2, 3]);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "memory-usage")]
+    #[test]
+    fn test_memory_usage() -> anyhow::Result<()> {
+        let mut ctx = SpendContext::new();
+        assert_eq!(ctx.memory_usage(), 0);
+
+        ctx.alloc(&Bytes::new(vec![1; 100]))?;
+        assert_eq!(ctx.memory_usage(), 100);
+
+        ctx.reset()?;
+        assert_eq!(ctx.memory_usage(), 0);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,508 @@
+use std::collections::HashMap;
+
+use chia_bls::PublicKey;
+use chia_consensus::consensus_constants::ConsensusConstants;
+use chia_protocol::{Bytes, Bytes32, Coin};
+use chia_puzzles::standard::StandardArgs;
+use chia_sdk_signer::PartialSpendBundle;
+use chia_sdk_types::Conditions;
+use indexmap::IndexMap;
+
+use crate::{
+    select_coins, Cat, CatSpend, CatVersion, DriverError, HashedPtr, Nft, SpendContext,
+    SpendWithConditions, StandardLayer,
+};
+
+type Output = (Bytes32, u64, Vec<Bytes>);
+
+/// Something that a transaction should accomplish, regardless of which coins are spent to do it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Intent {
+    /// Creates an XCH coin with the given puzzle hash and amount.
+    SendXch {
+        puzzle_hash: Bytes32,
+        amount: u64,
+        memos: Vec<Bytes>,
+    },
+    /// Creates a CAT2 coin with the given puzzle hash and amount.
+    /// The first memo should be the puzzle hash, so that the recipient's wallet can find the coin.
+    SendCat {
+        asset_id: Bytes32,
+        puzzle_hash: Bytes32,
+        amount: u64,
+        memos: Vec<Bytes>,
+    },
+    /// Transfers an NFT to the given puzzle hash.
+    TransferNft {
+        launcher_id: Bytes32,
+        puzzle_hash: Bytes32,
+    },
+}
+
+/// Builds a transaction from a list of [`Intent`]s and the coins that are available to spend.
+///
+/// Coins are selected for each asset, and the change is sent back to the change puzzle hash.
+/// Every spent coin asserts that the next one is spent in the same block,
+/// so that the spend bundle can't be split apart.
+///
+/// All of the coins must be owned by one of the keys, using the [`StandardLayer`].
+#[derive(Debug, Clone)]
+pub struct TransactionBuilder {
+    change_puzzle_hash: Bytes32,
+    fee: u64,
+    intents: Vec<Intent>,
+    keys: HashMap<Bytes32, PublicKey>,
+    xch_coins: Vec<Coin>,
+    cats: Vec<Cat>,
+    nfts: Vec<Nft<HashedPtr>>,
+}
+
+impl TransactionBuilder {
+    pub fn new(change_puzzle_hash: Bytes32) -> Self {
+        Self {
+            change_puzzle_hash,
+            fee: 0,
+            intents: Vec::new(),
+            keys: HashMap::new(),
+            xch_coins: Vec::new(),
+            cats: Vec::new(),
+            nfts: Vec::new(),
+        }
+    }
+
+    /// Adds a synthetic key which owns some of the coins.
+    #[must_use]
+    pub fn add_key(mut self, synthetic_key: PublicKey) -> Self {
+        let p2_puzzle_hash = StandardArgs::curry_tree_hash(synthetic_key).into();
+        self.keys.insert(p2_puzzle_hash, synthetic_key);
+        self
+    }
+
+    /// Adds XCH coins which can be selected to pay for the intents and fee.
+    #[must_use]
+    pub fn add_xch_coins(mut self, coins: impl IntoIterator<Item = Coin>) -> Self {
+        self.xch_coins.extend(coins);
+        self
+    }
+
+    /// Adds CATs which can be selected to pay for the intents.
+    /// CAT1 coins are never selected, since they are a different token than the CAT2 with the same asset id.
+    #[must_use]
+    pub fn add_cats(mut self, cats: impl IntoIterator<Item = Cat>) -> Self {
+        self.cats.extend(cats);
+        self
+    }
+
+    /// Adds NFTs which can be transferred.
+    #[must_use]
+    pub fn add_nfts(mut self, nfts: impl IntoIterator<Item = Nft<HashedPtr>>) -> Self {
+        self.nfts.extend(nfts);
+        self
+    }
+
+    #[must_use]
+    pub fn intent(mut self, intent: Intent) -> Self {
+        self.intents.push(intent);
+        self
+    }
+
+    #[must_use]
+    pub fn send_xch(self, puzzle_hash: Bytes32, amount: u64, memos: Vec<Bytes>) -> Self {
+        self.intent(Intent::SendXch {
+            puzzle_hash,
+            amount,
+            memos,
+        })
+    }
+
+    #[must_use]
+    pub fn send_cat(
+        self,
+        asset_id: Bytes32,
+        puzzle_hash: Bytes32,
+        amount: u64,
+        memos: Vec<Bytes>,
+    ) -> Self {
+        self.intent(Intent::SendCat {
+            asset_id,
+            puzzle_hash,
+            amount,
+            memos,
+        })
+    }
+
+    #[must_use]
+    pub fn transfer_nft(self, launcher_id: Bytes32, puzzle_hash: Bytes32) -> Self {
+        self.intent(Intent::TransferNft {
+            launcher_id,
+            puzzle_hash,
+        })
+    }
+
+    /// Sets the fee, which is paid with the selected XCH coins.
+    #[must_use]
+    pub fn fee(mut self, fee: u64) -> Self {
+        self.fee = fee;
+        self
+    }
+
+    /// Selects coins, spends them in the [`SpendContext`], and takes all of its coin spends.
+    /// The result is unsigned, and contains the [`RequiredSignature`](chia_sdk_signer::RequiredSignature)s
+    /// for every coin spend, including those that were already in the context.
+    ///
+    /// If an error occurs, the context is rolled back to the state it was in before, so none of the spends are kept.
+    pub fn build(
+        self,
+        ctx: &mut SpendContext,
+        constants: &ConsensusConstants,
+    ) -> Result<PartialSpendBundle, DriverError> {
+        let checkpoint = ctx.checkpoint();
+
+        if let Err(error) = self.spend(ctx) {
+            ctx.rollback(checkpoint);
+            return Err(error);
+        }
+
+        let coin_spends = ctx.take();
+
+        Ok(PartialSpendBundle::from_coin_spends(
+            &mut ctx.allocator,
+            coin_spends,
+            constants,
+        )?)
+    }
+
+    fn spend(mut self, ctx: &mut SpendContext) -> Result<(), DriverError> {
+        let mut xch_outputs = Vec::new();
+        let mut xch_total = u128::from(self.fee);
+        let mut cat_outputs: IndexMap<Bytes32, (u128, Vec<Output>)> = IndexMap::new();
+        let mut nft_transfers = Vec::new();
+
+        for intent in self.intents.drain(..) {
+            match intent {
+                Intent::SendXch {
+                    puzzle_hash,
+                    amount,
+                    memos,
+                } => {
+                    xch_total += u128::from(amount);
+                    xch_outputs.push((puzzle_hash, amount, memos));
+                }
+                Intent::SendCat {
+                    asset_id,
+                    puzzle_hash,
+                    amount,
+                    memos,
+                } => {
+                    let (total, outputs) = cat_outputs.entry(asset_id).or_default();
+                    *total += u128::from(amount);
+                    outputs.push((puzzle_hash, amount, memos));
+                }
+                Intent::TransferNft {
+                    launcher_id,
+                    puzzle_hash,
+                } => {
+                    let index = self
+                        .nfts
+                        .iter()
+                        .position(|nft| nft.info.launcher_id == launcher_id)
+                        .ok_or(DriverError::MissingNft(launcher_id))?;
+                    nft_transfers.push((self.nfts.swap_remove(index), puzzle_hash));
+                }
+            }
+        }
+
+        // Outputs with no amount still need a coin to create them.
+        let xch_coins = if xch_total > 0 || !xch_outputs.is_empty() {
+            select_coins(self.xch_coins.clone(), xch_total)?
+        } else {
+            Vec::new()
+        };
+
+        let mut selected_cats = Vec::new();
+
+        for (&asset_id, (total, _)) in &cat_outputs {
+            let coins = self
+                .cats
+                .iter()
+                .filter(|cat| cat.asset_id == asset_id && cat.version == CatVersion::V2)
+                .map(|cat| cat.coin)
+                .collect();
+
+            let cats = select_coins(coins, *total)?
+                .into_iter()
+                .filter_map(|coin| self.cats.iter().find(|cat| cat.coin == coin).copied())
+                .collect::<Vec<_>>();
+
+            selected_cats.push(cats);
+        }
+
+        // Each spent coin asserts the next one, which forms a ring across the whole transaction.
+        let coin_ids: Vec<Bytes32> = xch_coins
+            .iter()
+            .copied()
+            .chain(selected_cats.iter().flatten().map(|cat| cat.coin))
+            .chain(nft_transfers.iter().map(|(nft, _)| nft.coin))
+            .map(|coin| coin.coin_id())
+            .collect();
+
+        let mut coin_index = 0;
+        let mut linked_conditions = || {
+            let mut conditions = Conditions::new();
+            if coin_ids.len() > 1 {
+                conditions =
+                    conditions.assert_concurrent_spend(coin_ids[(coin_index + 1) % coin_ids.len()]);
+            }
+            coin_index += 1;
+            conditions
+        };
+
+        let xch_change = change(&xch_coins, xch_total)?;
+
+        for (index, &coin) in xch_coins.iter().enumerate() {
+            let mut conditions = linked_conditions();
+
+            if index == 0 {
+                for (puzzle_hash, amount, memos) in xch_outputs.drain(..) {
+                    conditions = conditions.create_coin(puzzle_hash, amount, memos);
+                }
+
+                if xch_change > 0 {
+                    conditions =
+                        conditions.create_coin(self.change_puzzle_hash, xch_change, Vec::new());
+                }
+
+                if self.fee > 0 {
+                    conditions = conditions.reserve_fee(self.fee);
+                }
+            }
+
+            self.p2(coin.puzzle_hash)?.spend(ctx, coin, conditions)?;
+        }
+
+        for (cats, (total, outputs)) in selected_cats.into_iter().zip(cat_outputs.into_values()) {
+            let coins: Vec<Coin> = cats.iter().map(|cat| cat.coin).collect();
+            let cat_change = change(&coins, total)?;
+            let mut outputs = Some(outputs);
+            let mut cat_spends = Vec::new();
+
+            for cat in cats {
+                let mut conditions = linked_conditions();
+
+                if let Some(outputs) = outputs.take() {
+                    for (puzzle_hash, amount, memos) in outputs {
+                        conditions = conditions.create_coin(puzzle_hash, amount, memos);
+                    }
+
+                    if cat_change > 0 {
+                        conditions = conditions.create_coin(
+                            self.change_puzzle_hash,
+                            cat_change,
+                            vec![self.change_puzzle_hash.into()],
+                        );
+                    }
+                }
+
+                let inner_spend = self
+                    .p2(cat.p2_puzzle_hash)?
+                    .spend_with_conditions(ctx, conditions)?;
+                cat_spends.push(CatSpend::new(cat, inner_spend));
+            }
+
+            Cat::spend_all(ctx, &cat_spends)?;
+        }
+
+        for (nft, puzzle_hash) in nft_transfers {
+            let p2 = self.p2(nft.info.p2_puzzle_hash)?;
+            let _nft = nft.transfer(ctx, &p2, puzzle_hash, linked_conditions())?;
+        }
+
+        Ok(())
+    }
+
+    fn p2(&self, p2_puzzle_hash: Bytes32) -> Result<StandardLayer, DriverError> {
+        self.keys
+            .get(&p2_puzzle_hash)
+            .map(|&synthetic_key| StandardLayer::new(synthetic_key))
+            .ok_or(DriverError::MissingKey(p2_puzzle_hash))
+    }
+}
+
+fn change(coins: &[Coin], total: u128) -> Result<u64, DriverError> {
+    let selected = coins
+        .iter()
+        .fold(0u128, |sum, coin| sum + u128::from(coin.amount));
+    Ok((selected - total).try_into()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_puzzles::nft::NftMetadata;
+    use chia_sdk_test::Simulator;
+    use chia_sdk_types::TESTNET11_CONSTANTS;
+
+    use crate::{Launcher, NftMint};
+
+    use super::*;
+
+    #[test]
+    fn test_transaction_builder() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1)?;
+        let p2 = StandardLayer::new(pk);
+
+        let mint = NftMint::new(NftMetadata::default(), puzzle_hash, 0, None);
+        let (mint_nft, nft) = Launcher::new(coin.coin_id(), 1).mint_nft(ctx, mint)?;
+        p2.spend(ctx, coin, mint_nft)?;
+
+        let cat_coin = sim.new_coin(puzzle_hash, 100);
+        let (issue_cat, cat) = Cat::single_issuance_eve(
+            ctx,
+            cat_coin.coin_id(),
+            100,
+            Conditions::new().create_coin(puzzle_hash, 100, vec![puzzle_hash.into()]),
+        )?;
+        p2.spend(ctx, cat_coin, issue_cat)?;
+
+        sim.spend_coins(ctx.take(), &[sk.clone()])?;
+
+        let metadata = ctx.alloc(&nft.info.metadata)?;
+        let nft = nft.with_metadata(HashedPtr::from_ptr(&ctx.allocator, metadata));
+        let cat = cat.wrapped_child(puzzle_hash, 100);
+        let xch_coins = [
+            sim.new_coin(puzzle_hash, 600),
+            sim.new_coin(puzzle_hash, 500),
+        ];
+
+        let recipient = Bytes32::new([1; 32]);
+
+        let mut bundle = TransactionBuilder::new(puzzle_hash)
+            .add_key(pk)
+            .add_xch_coins(xch_coins)
+            .add_cats([cat])
+            .add_nfts([nft])
+            .send_xch(recipient, 900, Vec::new())
+            .send_cat(cat.asset_id, recipient, 60, vec![recipient.into()])
+            .transfer_nft(nft.info.launcher_id, recipient)
+            .fee(50)
+            .build(ctx, &TESTNET11_CONSTANTS)?;
+
+        let coin_spends = bundle.coin_spends().to_vec();
+        assert_eq!(coin_spends.len(), 4);
+        assert_eq!(bundle.sign(&[sk.clone()]), 4);
+        assert!(bundle.is_complete());
+
+        // The spends are linked together, so they can't be submitted separately.
+        assert!(sim
+            .spend_coins(coin_spends[..3].to_vec(), &[sk.clone()])
+            .is_err());
+
+        sim.new_transaction(bundle.finalize()?, &TESTNET11_CONSTANTS)?;
+
+        let xch_parent = coin_spends[0].coin.coin_id();
+        for coin in [
+            Coin::new(xch_parent, recipient, 900),
+            Coin::new(xch_parent, puzzle_hash, 150),
+            cat.wrapped_child(recipient, 60).coin,
+            cat.wrapped_child(puzzle_hash, 40).coin,
+        ] {
+            assert!(sim.coin_state(coin.coin_id()).is_some());
+        }
+
+        assert_eq!(sim.children(nft.coin.coin_id()).len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_transaction_builder_errors() {
+        let ctx = &mut SpendContext::new();
+
+        let owned = StandardArgs::curry_tree_hash(PublicKey::default()).into();
+        let unowned = Bytes32::new([2; 32]);
+
+        let result = TransactionBuilder::new(owned)
+            .add_xch_coins([Coin::new(Bytes32::default(), owned, 100)])
+            .send_xch(owned, 200, Vec::new())
+            .build(ctx, &TESTNET11_CONSTANTS);
+        assert!(matches!(result, Err(DriverError::CoinSelection(_))));
+
+        let result = TransactionBuilder::new(owned)
+            .add_xch_coins([Coin::new(Bytes32::default(), unowned, 100)])
+            .send_xch(owned, 100, Vec::new())
+            .build(ctx, &TESTNET11_CONSTANTS);
+        assert!(matches!(result, Err(DriverError::MissingKey(_))));
+
+        // A CAT1 coin can't be used to send the CAT2 with the same asset id.
+        let cat = Cat::new(
+            Coin::new(Bytes32::default(), owned, 100),
+            None,
+            Bytes32::default(),
+            owned,
+        )
+        .with_version(CatVersion::V1);
+        let result = TransactionBuilder::new(owned)
+            .add_key(PublicKey::default())
+            .add_cats([cat])
+            .send_cat(cat.asset_id, owned, 100, vec![owned.into()])
+            .build(ctx, &TESTNET11_CONSTANTS);
+        assert!(matches!(result, Err(DriverError::CoinSelection(_))));
+
+        let result = TransactionBuilder::new(owned)
+            .transfer_nft(Bytes32::default(), owned)
+            .build(ctx, &TESTNET11_CONSTANTS);
+        assert!(matches!(result, Err(DriverError::MissingNft(_))));
+
+        // An output with no amount still needs a coin to create it.
+        let result = TransactionBuilder::new(owned)
+            .send_xch(owned, 0, Vec::new())
+            .build(ctx, &TESTNET11_CONSTANTS);
+        assert!(matches!(result, Err(DriverError::CoinSelection(_))));
+
+        // The XCH is spent before the CAT, whose key is missing, so the XCH spend is rolled back.
+        let cat = Cat::new(
+            Coin::new(Bytes32::default(), unowned, 100),
+            None,
+            Bytes32::default(),
+            unowned,
+        );
+        let result = TransactionBuilder::new(owned)
+            .add_key(PublicKey::default())
+            .add_xch_coins([Coin::new(Bytes32::default(), owned, 100)])
+            .add_cats([cat])
+            .send_xch(owned, 100, Vec::new())
+            .send_cat(cat.asset_id, owned, 100, vec![owned.into()])
+            .build(ctx, &TESTNET11_CONSTANTS);
+        assert!(matches!(result, Err(DriverError::MissingKey(_))));
+        assert_eq!(ctx.iter().count(), 0);
+    }
+
+    #[test]
+    fn test_zero_amount_output() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1)?;
+        let recipient = Bytes32::new([1; 32]);
+
+        let mut bundle = TransactionBuilder::new(puzzle_hash)
+            .add_key(pk)
+            .add_xch_coins([coin])
+            .send_xch(recipient, 0, Vec::new())
+            .build(ctx, &TESTNET11_CONSTANTS)?;
+
+        assert_eq!(bundle.sign(&[sk]), 1);
+        sim.new_transaction(bundle.finalize()?, &TESTNET11_CONSTANTS)?;
+
+        assert!(sim
+            .coin_state(Coin::new(coin.coin_id(), recipient, 0).coin_id())
+            .is_some());
+        assert!(sim
+            .coin_state(Coin::new(coin.coin_id(), puzzle_hash, 1).coin_id())
+            .is_some());
+
+        Ok(())
+    }
+}
@@ -2,6 +2,8 @@ use clvm_traits::{FromClvmError, ToClvmError};
 use clvmr::reduction::EvalErr;
 use thiserror::Error;
 
+use crate::RequiredSignature;
+
 #[derive(Debug, Error)]
 pub enum SignerError {
     #[error("Eval error: {0}")]
@@ -13,6 +15,37 @@ pub enum SignerError {
     #[error("From CLVM error: {0}")]
     FromClvm(#[from] FromClvmError),
 
+    #[error("BLS error: {0}")]
+    Bls(#[from] chia_bls::Error),
+
+    #[cfg(feature = "serde")]
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Streamable error: {0}")]
+    Streamable(#[from] chia_traits::Error),
+
+    #[error("Signing backend error: {0}")]
+    Backend(String),
+
     #[error("Infinity public key")]
     InfinityPublicKey,
+
+    #[error("Signature does not match any required signature")]
+    UnknownSignature,
+
+    #[error("Invalid signature for the required message")]
+    InvalidSignature,
+
+    #[error("Invalid signature for required signature with public key {:?}", .0.public_key())]
+    InvalidRequiredSignature(Box<RequiredSignature>),
+
+    #[error("Partial spend bundles do not have the same required signatures")]
+    MismatchedRequirements,
+
+    #[error("Missing {0} required signatures")]
+    MissingSignatures(usize),
+
+    #[error("Aggregated signature does not cover {} required signatures", .0.len())]
+    UncoveredSignatures(Vec<RequiredSignature>),
 }
@@ -1,5 +1,22 @@
 mod error;
+mod partial_signature;
+mod partial_spend_bundle;
+mod required_secp_signature;
 mod required_signature;
+mod secp_dialect;
+mod signature_requirement;
+mod signing_backend;
+mod signing_request;
+mod verify;
 
 pub use error::*;
+pub use partial_signature::*;
+pub use partial_spend_bundle::*;
+pub use required_secp_signature::*;
 pub use required_signature::*;
+pub use signature_requirement::*;
+pub use signing_backend::*;
+pub use signing_request::*;
+pub use verify::*;
+
+pub(crate) use secp_dialect::run_puzzle_with_secp;
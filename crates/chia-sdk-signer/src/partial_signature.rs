@@ -0,0 +1,47 @@
+use chia_bls::{sign, verify, PublicKey, SecretKey, Signature};
+use chia_protocol::Bytes;
+use chia_streamable_macro::Streamable;
+
+use crate::RequiredSignature;
+
+/// A signature for a single [`RequiredSignature`], created by whoever holds the secret key.
+/// These can be sent between parties and merged into a [`PartialSpendBundle`](crate::PartialSpendBundle).
+#[derive(Debug, Clone, PartialEq, Eq, Streamable)]
+pub struct PartialSignature {
+    /// The public key that the signature is for.
+    pub public_key: PublicKey,
+    /// The final message that was signed, including the appended info and domain string.
+    pub message: Bytes,
+    /// The signature of the message.
+    pub signature: Signature,
+}
+
+impl PartialSignature {
+    pub fn new(public_key: PublicKey, message: Bytes, signature: Signature) -> Self {
+        Self {
+            public_key,
+            message,
+            signature,
+        }
+    }
+
+    /// Signs the final message of a [`RequiredSignature`] with the given secret key.
+    /// The secret key is expected to match the required public key.
+    pub fn sign(secret_key: &SecretKey, required: &RequiredSignature) -> Self {
+        let message = required.final_message();
+        let signature = sign(secret_key, &message);
+        Self::new(required.public_key(), message.into(), signature)
+    }
+
+    /// Checks that the signature is valid for the public key and message.
+    pub fn is_valid(&self) -> bool {
+        verify(&self.signature, &self.public_key, self.message.as_ref())
+    }
+
+    /// Whether this signature satisfies the given [`RequiredSignature`].
+    /// This only compares the public key and message, it doesn't verify the signature itself.
+    pub fn matches(&self, required: &RequiredSignature) -> bool {
+        self.public_key == required.public_key()
+            && self.message.as_ref() == required.final_message()
+    }
+}
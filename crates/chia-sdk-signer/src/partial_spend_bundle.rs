@@ -0,0 +1,417 @@
+use chia_bls::{PublicKey, SecretKey, Signature};
+use chia_consensus::consensus_constants::ConsensusConstants;
+use chia_protocol::{CoinSpend, SpendBundle};
+use chia_streamable_macro::Streamable;
+use clvmr::Allocator;
+
+use crate::{
+    verify_required_signatures, PartialSignature, RequiredSignature, SignerError, SigningBackend,
+    SigningRequest, SigningResponse,
+};
+
+/// A set of coin spends that is being signed by one or more parties.
+///
+/// Each party signs the [`RequiredSignature`]s that they hold the keys for, and the
+/// resulting [`PartialSignature`]s (or the whole partial spend bundle) can be serialized
+/// and passed along to the other parties. Once every requirement has been signed, the
+/// final [`SpendBundle`] can be created with [`PartialSpendBundle::finalize`].
+#[derive(Debug, Clone, PartialEq, Eq, Streamable)]
+pub struct PartialSpendBundle {
+    coin_spends: Vec<CoinSpend>,
+    signatures: Vec<(RequiredSignature, Option<Signature>)>,
+}
+
+impl PartialSpendBundle {
+    /// Creates an unsigned partial spend bundle from a list of required signatures.
+    pub fn new(coin_spends: Vec<CoinSpend>, required_signatures: Vec<RequiredSignature>) -> Self {
+        Self {
+            coin_spends,
+            signatures: required_signatures
+                .into_iter()
+                .map(|required| (required, None))
+                .collect(),
+        }
+    }
+
+    /// Calculates the required signatures for the coin spends and creates an unsigned partial spend bundle.
+    pub fn from_coin_spends(
+        allocator: &mut Allocator,
+        coin_spends: Vec<CoinSpend>,
+        constants: &ConsensusConstants,
+    ) -> Result<Self, SignerError> {
+        let required_signatures =
+            RequiredSignature::from_coin_spends(allocator, &coin_spends, constants)?;
+        Ok(Self::new(coin_spends, required_signatures))
+    }
+
+    /// The coin spends that are being signed.
+    pub fn coin_spends(&self) -> &[CoinSpend] {
+        &self.coin_spends
+    }
+
+    /// All of the required signatures, whether they have been signed or not.
+    pub fn required_signatures(&self) -> impl Iterator<Item = &RequiredSignature> {
+        self.signatures.iter().map(|(required, _)| required)
+    }
+
+    /// The required signatures which have not been signed yet.
+    pub fn unsigned(&self) -> impl Iterator<Item = &RequiredSignature> {
+        self.signatures
+            .iter()
+            .filter(|(_, signature)| signature.is_none())
+            .map(|(required, _)| required)
+    }
+
+    /// The public keys which still need to sign, without duplicates.
+    pub fn missing_public_keys(&self) -> Vec<PublicKey> {
+        let mut public_keys = Vec::new();
+        for required in self.unsigned() {
+            if !public_keys.contains(&required.public_key()) {
+                public_keys.push(required.public_key());
+            }
+        }
+        public_keys
+    }
+
+    /// The signatures that have been collected so far.
+    pub fn partial_signatures(&self) -> Vec<PartialSignature> {
+        self.signatures
+            .iter()
+            .filter_map(|(required, signature)| {
+                Some(PartialSignature::new(
+                    required.public_key(),
+                    required.final_message().into(),
+                    signature.clone()?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Whether every required signature has been signed.
+    pub fn is_complete(&self) -> bool {
+        self.signatures
+            .iter()
+            .all(|(_, signature)| signature.is_some())
+    }
+
+    /// Signs every unsigned requirement that one of the secret keys can sign for.
+    /// Requirements for other public keys are left as is. Returns the number of new signatures.
+    pub fn sign(&mut self, secret_keys: &[SecretKey]) -> usize {
+        let mut count = 0;
+
+        for (required, signature) in &mut self.signatures {
+            if signature.is_some() {
+                continue;
+            }
+
+            let Some(secret_key) = secret_keys
+                .iter()
+                .find(|sk| sk.public_key() == required.public_key())
+            else {
+                continue;
+            };
+
+            *signature = Some(PartialSignature::sign(secret_key, required).signature);
+            count += 1;
+        }
+
+        count
+    }
+
+    /// Adds a signature from another party to every unsigned requirement it matches.
+    /// The signature is verified before it's added.
+    pub fn add_signature(&mut self, partial: &PartialSignature) -> Result<(), SignerError> {
+        let mut found = false;
+
+        for (required, signature) in &mut self.signatures {
+            if !partial.matches(required) {
+                continue;
+            }
+
+            if !found && !partial.is_valid() {
+                return Err(SignerError::InvalidSignature);
+            }
+
+            found = true;
+
+            if signature.is_none() {
+                *signature = Some(partial.signature.clone());
+            }
+        }
+
+        if !found {
+            return Err(SignerError::UnknownSignature);
+        }
+
+        Ok(())
+    }
+
+    /// Creates a request for a [`SigningBackend`] to sign the requirements which are still unsigned.
+    pub fn signing_request(&self) -> SigningRequest {
+        SigningRequest::new(self.coin_spends.clone(), self.unsigned().cloned().collect())
+    }
+
+    /// Adds every signature from a [`SigningBackend`]'s response. Returns the number of new signatures.
+    pub fn apply_response(&mut self, response: &SigningResponse) -> Result<usize, SignerError> {
+        let before = self.unsigned().count();
+        for partial in &response.signatures {
+            self.add_signature(partial)?;
+        }
+        Ok(before - self.unsigned().count())
+    }
+
+    /// Asks a [`SigningBackend`] to sign the unsigned requirements, and adds the signatures it returns.
+    /// Returns the number of new signatures.
+    pub async fn sign_with<B>(&mut self, backend: &B) -> Result<usize, SignerError>
+    where
+        B: SigningBackend,
+    {
+        if self.is_complete() {
+            return Ok(0);
+        }
+        let response = backend.sign(self.signing_request()).await?;
+        self.apply_response(&response)
+    }
+
+    /// Merges the signatures from another partial spend bundle for the same coin spends.
+    /// Each signature that's copied over is verified against its requirement first.
+    pub fn merge(&mut self, other: &Self) -> Result<(), SignerError> {
+        if self.coin_spends != other.coin_spends
+            || !self.required_signatures().eq(other.required_signatures())
+        {
+            return Err(SignerError::MismatchedRequirements);
+        }
+
+        // Check every signature that would be copied before changing anything,
+        // so that a bad signature from the other party can be identified.
+        for ((required, signature), (_, other_signature)) in
+            self.signatures.iter().zip(&other.signatures)
+        {
+            let (None, Some(other_signature)) = (signature, other_signature) else {
+                continue;
+            };
+
+            let partial = PartialSignature::new(
+                required.public_key(),
+                required.final_message().into(),
+                other_signature.clone(),
+            );
+
+            if !partial.is_valid() {
+                return Err(SignerError::InvalidRequiredSignature(Box::new(
+                    required.clone(),
+                )));
+            }
+        }
+
+        for ((_, signature), (_, other_signature)) in
+            self.signatures.iter_mut().zip(&other.signatures)
+        {
+            if signature.is_none() {
+                signature.clone_from(other_signature);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The aggregate of all of the signatures that have been collected so far.
+    pub fn aggregated_signature(&self) -> Signature {
+        let mut aggregated_signature = Signature::default();
+        for signature in self.signatures.iter().filter_map(|(_, sig)| sig.as_ref()) {
+            aggregated_signature += signature;
+        }
+        aggregated_signature
+    }
+
+    /// Checks that every requirement has been signed and that the aggregated signature
+    /// covers all of them, then creates the final [`SpendBundle`].
+    pub fn finalize(self) -> Result<SpendBundle, SignerError> {
+        let missing = self.unsigned().count();
+
+        if missing > 0 {
+            return Err(SignerError::MissingSignatures(missing));
+        }
+
+        let aggregated_signature = self.aggregated_signature();
+        let required_signatures: Vec<RequiredSignature> =
+            self.required_signatures().cloned().collect();
+
+        verify_required_signatures(&aggregated_signature, &required_signatures)?;
+
+        Ok(SpendBundle::new(self.coin_spends, aggregated_signature))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chia_bls::sign;
+    use chia_protocol::{Bytes, Bytes32, Coin, Program};
+    use chia_sdk_types::{AggSigMe, Condition, MAINNET_CONSTANTS};
+    use chia_traits::Streamable;
+    use clvm_traits::{FromClvm, ToClvm};
+
+    fn coin_spend(
+        allocator: &mut Allocator,
+        amount: u64,
+        public_keys: &[PublicKey],
+    ) -> anyhow::Result<CoinSpend> {
+        // The puzzle `1` returns the solution as its output conditions.
+        let puzzle = Program::from(vec![1]);
+
+        let conditions: Vec<Condition> = public_keys
+            .iter()
+            .map(|&public_key| {
+                Condition::AggSigMe(AggSigMe::new(public_key, Bytes::from(vec![1, 2, 3])))
+            })
+            .collect();
+
+        let ptr = conditions.to_clvm(allocator)?;
+        let solution = Program::from_clvm(allocator, ptr)?;
+
+        Ok(CoinSpend::new(
+            Coin::new(Bytes32::default(), Bytes32::default(), amount),
+            puzzle,
+            solution,
+        ))
+    }
+
+    #[test]
+    fn test_multi_party_signing() -> anyhow::Result<()> {
+        let mut allocator = Allocator::new();
+
+        let alice = SecretKey::from_seed(&[1; 32]);
+        let bob = SecretKey::from_seed(&[2; 32]);
+
+        let coin_spends = vec![
+            coin_spend(&mut allocator, 1, &[alice.public_key()])?,
+            coin_spend(&mut allocator, 2, &[alice.public_key(), bob.public_key()])?,
+        ];
+
+        let unsigned =
+            PartialSpendBundle::from_coin_spends(&mut allocator, coin_spends, &MAINNET_CONSTANTS)?;
+        assert_eq!(unsigned.unsigned().count(), 3);
+
+        // Alice signs her part and sends the partial spend bundle to Bob.
+        let mut alice_partial = unsigned.clone();
+        assert_eq!(alice_partial.sign(&[alice.clone()]), 2);
+        assert!(!alice_partial.is_complete());
+        assert_eq!(alice_partial.missing_public_keys(), vec![bob.public_key()]);
+
+        let bytes = alice_partial.to_bytes()?;
+        let mut bob_partial = PartialSpendBundle::from_bytes(&bytes)?;
+        assert_eq!(bob_partial, alice_partial);
+
+        // Bob signs his part separately, then merges Alice's signatures.
+        let mut bob_only = unsigned.clone();
+        assert_eq!(bob_only.sign(&[bob.clone()]), 1);
+        bob_partial.merge(&bob_only)?;
+        assert!(bob_partial.is_complete());
+
+        let spend_bundle = bob_partial.finalize()?;
+        assert_eq!(spend_bundle.coin_spends.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_partial_signatures() -> anyhow::Result<()> {
+        let mut allocator = Allocator::new();
+
+        let alice = SecretKey::from_seed(&[1; 32]);
+        let bob = SecretKey::from_seed(&[2; 32]);
+
+        let coin_spends = vec![coin_spend(
+            &mut allocator,
+            1,
+            &[alice.public_key(), bob.public_key()],
+        )?];
+
+        let mut partial =
+            PartialSpendBundle::from_coin_spends(&mut allocator, coin_spends, &MAINNET_CONSTANTS)?;
+
+        assert!(matches!(
+            partial.clone().finalize().unwrap_err(),
+            SignerError::MissingSignatures(2)
+        ));
+
+        let mut bob_partial = partial.clone();
+        bob_partial.sign(&[bob.clone()]);
+        let signatures = bob_partial.partial_signatures();
+        assert_eq!(signatures.len(), 1);
+
+        let roundtrip = PartialSignature::from_bytes(&signatures[0].to_bytes()?)?;
+        partial.add_signature(&roundtrip)?;
+        partial.sign(&[alice.clone()]);
+
+        partial.finalize()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_partial_signatures() -> anyhow::Result<()> {
+        let mut allocator = Allocator::new();
+
+        let alice = SecretKey::from_seed(&[1; 32]);
+        let bob = SecretKey::from_seed(&[2; 32]);
+
+        let coin_spends = vec![coin_spend(&mut allocator, 1, &[alice.public_key()])?];
+
+        let mut partial = PartialSpendBundle::from_coin_spends(
+            &mut allocator,
+            coin_spends.clone(),
+            &MAINNET_CONSTANTS,
+        )?;
+
+        let required = partial
+            .unsigned()
+            .next()
+            .expect("missing requirement")
+            .clone();
+
+        // Signed with the wrong key.
+        let mut forged = PartialSignature::sign(&bob, &required);
+        forged.public_key = alice.public_key();
+        assert!(matches!(
+            partial.add_signature(&forged).unwrap_err(),
+            SignerError::InvalidSignature
+        ));
+
+        // Doesn't match any requirement.
+        let unknown = PartialSignature::new(
+            bob.public_key(),
+            required.final_message().into(),
+            Signature::default(),
+        );
+        assert!(matches!(
+            partial.add_signature(&unknown).unwrap_err(),
+            SignerError::UnknownSignature
+        ));
+
+        // Different coin spends can't be merged.
+        let other_coin_spends = vec![coin_spend(&mut allocator, 2, &[alice.public_key()])?];
+        let other = PartialSpendBundle::from_coin_spends(
+            &mut allocator,
+            other_coin_spends,
+            &MAINNET_CONSTANTS,
+        )?;
+        assert!(matches!(
+            partial.merge(&other).unwrap_err(),
+            SignerError::MismatchedRequirements
+        ));
+
+        // A bad signature in the other partial spend bundle is reported when merging.
+        let mut other = partial.clone();
+        other.signatures[0].1 = Some(sign(&bob, required.final_message()));
+        assert!(matches!(
+            partial.merge(&other).unwrap_err(),
+            SignerError::InvalidRequiredSignature(bad) if *bad == required
+        ));
+        assert!(!partial.is_complete());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+use chia_protocol::Bytes32;
+use chia_sdk_types::{SecpCurve, SecpPublicKey};
+
+/// A secp256k1 or secp256r1 signature that a puzzle checks when it's run.
+///
+/// Unlike BLS signatures, secp signatures are verified by the puzzle itself.
+/// So the spend is run with a placeholder signature to find out what needs to be signed,
+/// and the signature is then inserted into the solution before the spend bundle is submitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RequiredSecpSignature {
+    coin_id: Bytes32,
+    public_key: SecpPublicKey,
+    message_hash: Bytes32,
+}
+
+impl RequiredSecpSignature {
+    pub fn new(coin_id: Bytes32, public_key: SecpPublicKey, message_hash: Bytes32) -> Self {
+        Self {
+            coin_id,
+            public_key,
+            message_hash,
+        }
+    }
+
+    /// The id of the coin whose puzzle checks the signature.
+    pub fn coin_id(&self) -> Bytes32 {
+        self.coin_id
+    }
+
+    /// The public key required to verify the signature.
+    pub fn public_key(&self) -> SecpPublicKey {
+        self.public_key
+    }
+
+    /// The curve that the public key and signature belong to.
+    pub fn curve(&self) -> SecpCurve {
+        self.public_key.curve
+    }
+
+    /// The 32 byte message hash that needs to be signed, without any further hashing.
+    pub fn message_hash(&self) -> Bytes32 {
+        self.message_hash
+    }
+}
@@ -2,12 +2,13 @@ use chia_bls::PublicKey;
 use chia_consensus::consensus_constants::ConsensusConstants;
 use chia_protocol::{Bytes, Bytes32, Coin, CoinSpend};
 use chia_sdk_types::{run_puzzle, AggSig, AggSigKind, Condition};
+use chia_streamable_macro::Streamable;
 use clvm_traits::{FromClvm, ToClvm};
-use clvmr::Allocator;
+use clvmr::{Allocator, NodePtr};
 
 use crate::SignerError;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Streamable)]
 pub struct RequiredSignature {
     public_key: PublicKey,
     raw_message: Bytes,
@@ -16,6 +17,20 @@ pub struct RequiredSignature {
 }
 
 impl RequiredSignature {
+    pub fn new(
+        public_key: PublicKey,
+        raw_message: Bytes,
+        appended_info: Vec<u8>,
+        domain_string: Option<Bytes32>,
+    ) -> Self {
+        Self {
+            public_key,
+            raw_message,
+            appended_info,
+            domain_string,
+        }
+    }
+
     /// Converts a known [`AggSig`] condition to a `RequiredSignature` if possible.
     pub fn from_condition(coin: &Coin, condition: AggSig, constants: &ConsensusConstants) -> Self {
         let domain_string;
@@ -72,9 +87,10 @@ impl RequiredSignature {
         }
     }
 
-    /// Calculates the required signatures for a coin spend.
-    /// All of these signatures aggregated together should be
-    /// sufficient, unless secp keys are used as well.
+    /// Calculates the required BLS signatures for a coin spend.
+    /// All of these signatures aggregated together should be sufficient.
+    /// Secp signatures are checked by the puzzle instead, and can be found with
+    /// [`SignatureRequirement::from_coin_spend`](crate::SignatureRequirement::from_coin_spend).
     pub fn from_coin_spend(
         allocator: &mut Allocator,
         coin_spend: &CoinSpend,
@@ -83,6 +99,16 @@ impl RequiredSignature {
         let puzzle = coin_spend.puzzle_reveal.to_clvm(allocator)?;
         let solution = coin_spend.solution.to_clvm(allocator)?;
         let output = run_puzzle(allocator, puzzle, solution)?;
+        Self::from_output(allocator, &coin_spend.coin, output, constants)
+    }
+
+    /// Calculates the required BLS signatures from the output conditions of a coin spend.
+    pub(crate) fn from_output(
+        allocator: &Allocator,
+        coin: &Coin,
+        output: NodePtr,
+        constants: &ConsensusConstants,
+    ) -> Result<Vec<Self>, SignerError> {
         let conditions = Vec::<Condition>::from_clvm(allocator, output)?;
 
         let mut result = Vec::new();
@@ -96,15 +122,16 @@ impl RequiredSignature {
                 return Err(SignerError::InfinityPublicKey);
             }
 
-            result.push(Self::from_condition(&coin_spend.coin, agg_sig, constants));
+            result.push(Self::from_condition(coin, agg_sig, constants));
         }
 
         Ok(result)
     }
 
-    /// Calculates the required signatures for a spend bundle.
-    /// All of these signatures aggregated together should be
-    /// sufficient, unless secp keys are used as well.
+    /// Calculates the required BLS signatures for a spend bundle.
+    /// All of these signatures aggregated together should be sufficient.
+    /// Secp signatures are checked by the puzzle instead, and can be found with
+    /// [`SignatureRequirement::from_coin_spends`](crate::SignatureRequirement::from_coin_spends).
     pub fn from_coin_spends(
         allocator: &mut Allocator,
         coin_spends: &[CoinSpend],
@@ -0,0 +1,130 @@
+use std::cell::RefCell;
+
+use chia_consensus::consensus_constants::ConsensusConstants;
+use chia_protocol::Bytes32;
+use chia_sdk_types::{SecpCurve, SecpPublicKey, SecpPublicKeyBytes};
+use clvmr::{
+    cost::Cost,
+    dialect::{Dialect, OperatorSet},
+    reduction::{EvalErr, Reduction, Response},
+    run_program, Allocator, ChiaDialect, NodePtr, SExp,
+};
+
+/// A secp signature check that failed while running a puzzle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct SecpCheck {
+    pub(crate) public_key: SecpPublicKey,
+    pub(crate) message_hash: Bytes32,
+}
+
+/// Wraps the [`ChiaDialect`], but records secp signature checks which fail rather than raising an error.
+/// This makes it possible to run a spend with a placeholder signature and find out what needs to be signed.
+struct SecpDialect {
+    inner: ChiaDialect,
+    checks: RefCell<Vec<SecpCheck>>,
+}
+
+impl SecpDialect {
+    fn new() -> Self {
+        Self {
+            inner: ChiaDialect::new(0),
+            checks: RefCell::new(Vec::new()),
+        }
+    }
+
+    fn parse_check(allocator: &Allocator, curve: SecpCurve, args: NodePtr) -> Option<SecpCheck> {
+        let (public_key, rest) = allocator.next(args)?;
+        let (message_hash, _rest) = allocator.next(rest)?;
+
+        let SExp::Atom = allocator.sexp(public_key) else {
+            return None;
+        };
+
+        let SExp::Atom = allocator.sexp(message_hash) else {
+            return None;
+        };
+
+        let public_key = SecpPublicKeyBytes::try_from(allocator.atom(public_key).as_ref()).ok()?;
+        let message_hash = Bytes32::try_from(allocator.atom(message_hash).as_ref()).ok()?;
+
+        Some(SecpCheck {
+            public_key: SecpPublicKey::new(curve, public_key),
+            message_hash,
+        })
+    }
+}
+
+impl Dialect for SecpDialect {
+    fn quote_kw(&self) -> u32 {
+        self.inner.quote_kw()
+    }
+
+    fn apply_kw(&self) -> u32 {
+        self.inner.apply_kw()
+    }
+
+    fn softfork_kw(&self) -> u32 {
+        self.inner.softfork_kw()
+    }
+
+    fn softfork_extension(&self, ext: u32) -> OperatorSet {
+        self.inner.softfork_extension(ext)
+    }
+
+    fn op(
+        &self,
+        allocator: &mut Allocator,
+        op: NodePtr,
+        args: NodePtr,
+        max_cost: Cost,
+        extensions: OperatorSet,
+    ) -> Response {
+        let response = self.inner.op(allocator, op, args, max_cost, extensions);
+
+        if response.is_ok() || allocator.atom_len(op) != 4 {
+            return response;
+        }
+
+        let opcode = u32::from_be_bytes(
+            allocator
+                .atom(op)
+                .as_ref()
+                .try_into()
+                .expect("opcode should be 4 bytes"),
+        );
+
+        let Some(curve) = SecpCurve::from_verify_opcode(opcode) else {
+            return response;
+        };
+
+        let Some(check) = Self::parse_check(allocator, curve, args) else {
+            return response;
+        };
+
+        self.checks.borrow_mut().push(check);
+
+        Ok(Reduction(curve.verify_cost(), NodePtr::NIL))
+    }
+
+    fn allow_unknown_ops(&self) -> bool {
+        self.inner.allow_unknown_ops()
+    }
+}
+
+/// Runs a puzzle with a solution, and returns the output along with any secp signature checks that failed.
+pub(crate) fn run_puzzle_with_secp(
+    allocator: &mut Allocator,
+    puzzle: NodePtr,
+    solution: NodePtr,
+    constants: &ConsensusConstants,
+) -> Result<(NodePtr, Vec<SecpCheck>), EvalErr> {
+    let dialect = SecpDialect::new();
+    let Reduction(_cost, output) = run_program(
+        allocator,
+        &dialect,
+        puzzle,
+        solution,
+        constants.max_block_cost_clvm,
+    )?;
+    Ok((output, dialect.checks.into_inner()))
+}
@@ -0,0 +1,151 @@
+use chia_consensus::consensus_constants::ConsensusConstants;
+use chia_protocol::CoinSpend;
+use clvm_traits::ToClvm;
+use clvmr::Allocator;
+
+use crate::{run_puzzle_with_secp, RequiredSecpSignature, RequiredSignature, SignerError};
+
+/// A signature that is needed for a coin spend, which is either a BLS or secp signature.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SignatureRequirement {
+    /// A BLS signature required by an `AGG_SIG_*` condition.
+    Bls(RequiredSignature),
+    /// A secp256k1 or secp256r1 signature checked by the puzzle itself.
+    Secp(RequiredSecpSignature),
+}
+
+impl SignatureRequirement {
+    /// Calculates all of the required signatures for a coin spend, including secp signatures.
+    /// The spend can use placeholder secp signatures, since failed secp checks are recorded rather than raised.
+    pub fn from_coin_spend(
+        allocator: &mut Allocator,
+        coin_spend: &CoinSpend,
+        constants: &ConsensusConstants,
+    ) -> Result<Vec<Self>, SignerError> {
+        let puzzle = coin_spend.puzzle_reveal.to_clvm(allocator)?;
+        let solution = coin_spend.solution.to_clvm(allocator)?;
+        let (output, secp_checks) = run_puzzle_with_secp(allocator, puzzle, solution, constants)?;
+        let required =
+            RequiredSignature::from_output(allocator, &coin_spend.coin, output, constants)?;
+
+        let coin_id = coin_spend.coin.coin_id();
+
+        let mut result: Vec<Self> = secp_checks
+            .into_iter()
+            .map(|check| {
+                Self::Secp(RequiredSecpSignature::new(
+                    coin_id,
+                    check.public_key,
+                    check.message_hash,
+                ))
+            })
+            .collect();
+
+        result.extend(required.into_iter().map(Self::Bls));
+
+        Ok(result)
+    }
+
+    /// Calculates all of the required signatures for a spend bundle, including secp signatures.
+    pub fn from_coin_spends(
+        allocator: &mut Allocator,
+        coin_spends: &[CoinSpend],
+        constants: &ConsensusConstants,
+    ) -> Result<Vec<Self>, SignerError> {
+        let mut required_signatures = Vec::new();
+        for coin_spend in coin_spends {
+            required_signatures.extend(Self::from_coin_spend(allocator, coin_spend, constants)?);
+        }
+        Ok(required_signatures)
+    }
+
+    pub fn as_bls(&self) -> Option<&RequiredSignature> {
+        match self {
+            Self::Bls(required) => Some(required),
+            Self::Secp(_) => None,
+        }
+    }
+
+    pub fn as_secp(&self) -> Option<&RequiredSecpSignature> {
+        match self {
+            Self::Bls(_) => None,
+            Self::Secp(required) => Some(required),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chia_bls::SecretKey;
+    use chia_protocol::{Bytes, Bytes32, Coin, Program};
+    use chia_sdk_types::{
+        AggSigMe, Condition, SecpCurve, SecpPublicKey, SecpPublicKeyBytes, SecpSignature,
+        MAINNET_CONSTANTS,
+    };
+    use clvm_traits::{FromClvm, ToClvm};
+    use clvmr::NodePtr;
+
+    /// Builds the puzzle `(c 2 (secp_verify 5 11 23))`, which checks a secp signature
+    /// and outputs the condition from the first solution argument.
+    fn secp_puzzle(allocator: &mut Allocator, curve: SecpCurve) -> anyhow::Result<NodePtr> {
+        let op = allocator.new_atom(&curve.verify_opcode().to_be_bytes())?;
+        let verify = (op, (5, (11, (23, ())))).to_clvm(allocator)?;
+        Ok((4, (2, (verify, ()))).to_clvm(allocator)?)
+    }
+
+    #[test]
+    fn test_secp_requirements() -> anyhow::Result<()> {
+        for curve in [SecpCurve::K1, SecpCurve::R1] {
+            let mut allocator = Allocator::new();
+
+            let sk = SecretKey::from_seed(&[1; 32]);
+            let public_key = SecpPublicKey::new(curve, SecpPublicKeyBytes::new([2; 33]));
+            let message_hash = Bytes32::new([3; 32]);
+
+            let puzzle = secp_puzzle(&mut allocator, curve)?;
+            let condition: Condition =
+                Condition::AggSigMe(AggSigMe::new(sk.public_key(), Bytes::from(vec![1, 2, 3])));
+            let solution = (
+                condition,
+                (
+                    public_key.bytes,
+                    (message_hash, (SecpSignature::new([0; 64]), ())),
+                ),
+            )
+                .to_clvm(&mut allocator)?;
+
+            let coin_spend = CoinSpend::new(
+                Coin::new(Bytes32::default(), Bytes32::default(), 1),
+                Program::from_clvm(&allocator, puzzle)?,
+                Program::from_clvm(&allocator, solution)?,
+            );
+
+            let required = SignatureRequirement::from_coin_spend(
+                &mut allocator,
+                &coin_spend,
+                &MAINNET_CONSTANTS,
+            )?;
+
+            assert_eq!(required.len(), 2);
+
+            let secp = required[0].as_secp().expect("expected secp requirement");
+            assert_eq!(secp.coin_id(), coin_spend.coin.coin_id());
+            assert_eq!(secp.curve(), curve);
+            assert_eq!(secp.public_key(), public_key);
+            assert_eq!(secp.message_hash(), message_hash);
+
+            let bls = required[1].as_bls().expect("expected bls requirement");
+            assert_eq!(bls.public_key(), sk.public_key());
+
+            // Only BLS requirements are calculated here, so the placeholder signature fails the spend.
+            assert!(matches!(
+                RequiredSignature::from_coin_spend(&mut allocator, &coin_spend, &MAINNET_CONSTANTS),
+                Err(SignerError::Eval(..))
+            ));
+        }
+
+        Ok(())
+    }
+}
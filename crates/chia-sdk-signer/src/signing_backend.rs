@@ -0,0 +1,82 @@
+use std::{fmt, future::Future};
+
+use chia_bls::SecretKey;
+
+use crate::{PartialSignature, SignerError, SigningRequest, SigningResponse};
+
+/// Something that can sign a batch of required signatures, such as a hardware wallet,
+/// an HSM, or an offline signer that receives requests as JSON or streamable bytes.
+///
+/// Backends should return a signature for every required signature they hold the key for,
+/// and leave out the rest. It's up to the caller to decide whether that's enough.
+pub trait SigningBackend {
+    fn sign(
+        &self,
+        request: SigningRequest,
+    ) -> impl Future<Output = Result<SigningResponse, SignerError>> + Send;
+}
+
+/// A [`SigningBackend`] which signs in-process with a set of secret keys.
+/// Only the public keys are shown when it's debug formatted.
+#[derive(Default, Clone)]
+pub struct InProcessSigner {
+    secret_keys: Vec<SecretKey>,
+}
+
+impl InProcessSigner {
+    pub fn new(secret_keys: Vec<SecretKey>) -> Self {
+        Self { secret_keys }
+    }
+
+    /// Signs every required signature that one of the secret keys can sign for.
+    pub fn sign_request(&self, request: &SigningRequest) -> SigningResponse {
+        SigningResponse::new(
+            request
+                .required_signatures
+                .iter()
+                .filter_map(|required| {
+                    let secret_key = self
+                        .secret_keys
+                        .iter()
+                        .find(|sk| sk.public_key() == required.public_key())?;
+                    Some(PartialSignature::sign(secret_key, required))
+                })
+                .collect(),
+        )
+    }
+}
+
+impl fmt::Debug for InProcessSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InProcessSigner")
+            .field(
+                "public_keys",
+                &self
+                    .secret_keys
+                    .iter()
+                    .map(SecretKey::public_key)
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl SigningBackend for InProcessSigner {
+    async fn sign(&self, request: SigningRequest) -> Result<SigningResponse, SignerError> {
+        Ok(self.sign_request(&request))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debug_hides_secret_keys() {
+        let sk = SecretKey::from_seed(&[1; 32]);
+        let debug = format!("{:?}", InProcessSigner::new(vec![sk.clone()]));
+
+        assert!(!debug.contains(&hex::encode(sk.to_bytes())));
+        assert!(debug.contains(&hex::encode(sk.public_key().to_bytes())));
+    }
+}
@@ -0,0 +1,206 @@
+use chia_consensus::consensus_constants::ConsensusConstants;
+use chia_protocol::CoinSpend;
+use chia_streamable_macro::Streamable;
+use chia_traits::Streamable;
+use clvmr::Allocator;
+
+use crate::{PartialSignature, RequiredSignature, SignerError};
+
+#[cfg(feature = "serde")]
+mod json;
+
+/// A batch of [`RequiredSignature`]s to be signed by a [`SigningBackend`](crate::SigningBackend),
+/// along with the coin spends they were calculated from.
+///
+/// The coin spends are included so that an offline signer can show what is being signed,
+/// and check the required signatures against them with [`SigningRequest::validate`].
+/// This can be serialized with the streamable format, or as JSON with the `serde` feature.
+#[derive(Debug, Clone, PartialEq, Eq, Streamable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(
+        into = "json::JsonSigningRequest",
+        try_from = "json::JsonSigningRequest"
+    )
+)]
+pub struct SigningRequest {
+    pub coin_spends: Vec<CoinSpend>,
+    pub required_signatures: Vec<RequiredSignature>,
+}
+
+impl SigningRequest {
+    pub fn new(coin_spends: Vec<CoinSpend>, required_signatures: Vec<RequiredSignature>) -> Self {
+        Self {
+            coin_spends,
+            required_signatures,
+        }
+    }
+
+    /// Calculates the required signatures for the coin spends and creates a request to sign all of them.
+    pub fn from_coin_spends(
+        allocator: &mut Allocator,
+        coin_spends: Vec<CoinSpend>,
+        constants: &ConsensusConstants,
+    ) -> Result<Self, SignerError> {
+        let required_signatures =
+            RequiredSignature::from_coin_spends(allocator, &coin_spends, constants)?;
+        Ok(Self::new(coin_spends, required_signatures))
+    }
+
+    /// Checks that every required signature in the request is actually required by the coin spends.
+    /// A signer should do this before signing, rather than trusting the request.
+    pub fn validate(
+        &self,
+        allocator: &mut Allocator,
+        constants: &ConsensusConstants,
+    ) -> Result<(), SignerError> {
+        let expected =
+            RequiredSignature::from_coin_spends(allocator, &self.coin_spends, constants)?;
+
+        if self
+            .required_signatures
+            .iter()
+            .all(|required| expected.contains(required))
+        {
+            Ok(())
+        } else {
+            Err(SignerError::MismatchedRequirements)
+        }
+    }
+
+    pub fn to_streamable_bytes(&self) -> Result<Vec<u8>, SignerError> {
+        Ok(self.to_bytes()?)
+    }
+
+    pub fn from_streamable_bytes(bytes: &[u8]) -> Result<Self, SignerError> {
+        Ok(Self::from_bytes(bytes)?)
+    }
+}
+
+/// The signatures returned by a [`SigningBackend`](crate::SigningBackend) for a [`SigningRequest`].
+/// Signatures may be missing for required signatures that the backend doesn't have the keys for.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Streamable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(
+        into = "json::JsonSigningResponse",
+        try_from = "json::JsonSigningResponse"
+    )
+)]
+pub struct SigningResponse {
+    pub signatures: Vec<PartialSignature>,
+}
+
+impl SigningResponse {
+    pub fn new(signatures: Vec<PartialSignature>) -> Self {
+        Self { signatures }
+    }
+
+    pub fn to_streamable_bytes(&self) -> Result<Vec<u8>, SignerError> {
+        Ok(self.to_bytes()?)
+    }
+
+    pub fn from_streamable_bytes(bytes: &[u8]) -> Result<Self, SignerError> {
+        Ok(Self::from_bytes(bytes)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chia_bls::SecretKey;
+    use chia_protocol::{Bytes, Bytes32, Coin, Program};
+    use chia_sdk_types::{AggSigMe, AggSigUnsafe, Condition, MAINNET_CONSTANTS};
+    use clvm_traits::{FromClvm, ToClvm};
+
+    fn request() -> anyhow::Result<(SecretKey, SigningRequest)> {
+        let mut allocator = Allocator::new();
+
+        let sk = SecretKey::from_seed(&[1; 32]);
+
+        let conditions: Vec<Condition> = vec![
+            Condition::AggSigMe(AggSigMe::new(sk.public_key(), Bytes::from(vec![1, 2, 3]))),
+            Condition::AggSigUnsafe(AggSigUnsafe::new(sk.public_key(), Bytes::from(vec![4]))),
+        ];
+        let ptr = conditions.to_clvm(&mut allocator)?;
+
+        // The puzzle `1` returns the solution as its output conditions.
+        let coin_spend = CoinSpend::new(
+            Coin::new(Bytes32::new([1; 32]), Bytes32::new([2; 32]), 3),
+            Program::from(vec![1]),
+            Program::from_clvm(&allocator, ptr)?,
+        );
+
+        let request =
+            SigningRequest::from_coin_spends(&mut allocator, vec![coin_spend], &MAINNET_CONSTANTS)?;
+
+        Ok((sk, request))
+    }
+
+    #[test]
+    fn test_request_roundtrip() -> anyhow::Result<()> {
+        let (_sk, request) = request()?;
+        assert_eq!(request.required_signatures.len(), 2);
+
+        #[cfg(feature = "serde")]
+        {
+            let json = request.to_json()?;
+            assert_eq!(SigningRequest::from_json(&json)?, request);
+        }
+
+        let bytes = request.to_streamable_bytes()?;
+        assert_eq!(SigningRequest::from_streamable_bytes(&bytes)?, request);
+
+        request.validate(&mut Allocator::new(), &MAINNET_CONSTANTS)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_response_roundtrip() -> anyhow::Result<()> {
+        let (sk, request) = request()?;
+
+        let response = SigningResponse::new(
+            request
+                .required_signatures
+                .iter()
+                .map(|required| PartialSignature::sign(&sk, required))
+                .collect(),
+        );
+
+        #[cfg(feature = "serde")]
+        {
+            let json = response.to_json()?;
+            assert_eq!(SigningResponse::from_json(&json)?, response);
+        }
+
+        let bytes = response.to_streamable_bytes()?;
+        assert_eq!(SigningResponse::from_streamable_bytes(&bytes)?, response);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_request() -> anyhow::Result<()> {
+        let (sk, mut request) = request()?;
+
+        request.required_signatures.push(RequiredSignature::new(
+            sk.public_key(),
+            Bytes::from(vec![5]),
+            Vec::new(),
+            None,
+        ));
+
+        assert!(matches!(
+            request
+                .validate(&mut Allocator::new(), &MAINNET_CONSTANTS)
+                .unwrap_err(),
+            SignerError::MismatchedRequirements
+        ));
+
+        Ok(())
+    }
+}
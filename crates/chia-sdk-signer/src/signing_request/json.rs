@@ -0,0 +1,186 @@
+use chia_bls::{PublicKey, Signature};
+use chia_protocol::{Bytes, Bytes32, Coin, CoinSpend, Program};
+use serde::{Deserialize, Serialize};
+use serde_with::{hex::Hex, serde_as};
+
+use crate::{PartialSignature, RequiredSignature, SignerError};
+
+use super::{SigningRequest, SigningResponse};
+
+impl SigningRequest {
+    pub fn to_json(&self) -> Result<String, SignerError> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, SignerError> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
+impl SigningResponse {
+    pub fn to_json(&self) -> Result<String, SignerError> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, SignerError> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
+#[serde_as]
+#[derive(Serialize, Deserialize)]
+pub(super) struct JsonCoin {
+    #[serde_as(as = "Hex")]
+    parent_coin_info: Bytes32,
+    #[serde_as(as = "Hex")]
+    puzzle_hash: Bytes32,
+    amount: u64,
+}
+
+#[serde_as]
+#[derive(Serialize, Deserialize)]
+pub(super) struct JsonCoinSpend {
+    coin: JsonCoin,
+    #[serde_as(as = "Hex")]
+    puzzle_reveal: Vec<u8>,
+    #[serde_as(as = "Hex")]
+    solution: Vec<u8>,
+}
+
+#[serde_as]
+#[derive(Serialize, Deserialize)]
+pub(super) struct JsonRequiredSignature {
+    #[serde_as(as = "Hex")]
+    public_key: [u8; 48],
+    #[serde_as(as = "Hex")]
+    raw_message: Bytes,
+    #[serde_as(as = "Hex")]
+    appended_info: Vec<u8>,
+    #[serde_as(as = "Option<Hex>")]
+    domain_string: Option<Bytes32>,
+}
+
+#[serde_as]
+#[derive(Serialize, Deserialize)]
+pub(super) struct JsonPartialSignature {
+    #[serde_as(as = "Hex")]
+    public_key: [u8; 48],
+    #[serde_as(as = "Hex")]
+    message: Bytes,
+    #[serde_as(as = "Hex")]
+    signature: [u8; 96],
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct JsonSigningRequest {
+    coin_spends: Vec<JsonCoinSpend>,
+    required_signatures: Vec<JsonRequiredSignature>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct JsonSigningResponse {
+    signatures: Vec<JsonPartialSignature>,
+}
+
+impl From<SigningRequest> for JsonSigningRequest {
+    fn from(value: SigningRequest) -> Self {
+        Self {
+            coin_spends: value
+                .coin_spends
+                .into_iter()
+                .map(|coin_spend| JsonCoinSpend {
+                    coin: JsonCoin {
+                        parent_coin_info: coin_spend.coin.parent_coin_info,
+                        puzzle_hash: coin_spend.coin.puzzle_hash,
+                        amount: coin_spend.coin.amount,
+                    },
+                    puzzle_reveal: coin_spend.puzzle_reveal.to_vec(),
+                    solution: coin_spend.solution.to_vec(),
+                })
+                .collect(),
+            required_signatures: value
+                .required_signatures
+                .into_iter()
+                .map(|required| JsonRequiredSignature {
+                    public_key: required.public_key().to_bytes(),
+                    raw_message: required.raw_message().to_vec().into(),
+                    appended_info: required.appended_info().to_vec(),
+                    domain_string: required.domain_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<JsonSigningRequest> for SigningRequest {
+    type Error = SignerError;
+
+    fn try_from(value: JsonSigningRequest) -> Result<Self, Self::Error> {
+        let coin_spends = value
+            .coin_spends
+            .into_iter()
+            .map(|coin_spend| {
+                CoinSpend::new(
+                    Coin::new(
+                        coin_spend.coin.parent_coin_info,
+                        coin_spend.coin.puzzle_hash,
+                        coin_spend.coin.amount,
+                    ),
+                    Program::from(coin_spend.puzzle_reveal),
+                    Program::from(coin_spend.solution),
+                )
+            })
+            .collect();
+
+        let required_signatures = value
+            .required_signatures
+            .into_iter()
+            .map(|required| {
+                Ok(RequiredSignature::new(
+                    PublicKey::from_bytes(&required.public_key)?,
+                    required.raw_message,
+                    required.appended_info,
+                    required.domain_string,
+                ))
+            })
+            .collect::<Result<_, SignerError>>()?;
+
+        Ok(Self::new(coin_spends, required_signatures))
+    }
+}
+
+impl From<SigningResponse> for JsonSigningResponse {
+    fn from(value: SigningResponse) -> Self {
+        Self {
+            signatures: value
+                .signatures
+                .into_iter()
+                .map(|partial| JsonPartialSignature {
+                    public_key: partial.public_key.to_bytes(),
+                    message: partial.message,
+                    signature: partial.signature.to_bytes(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<JsonSigningResponse> for SigningResponse {
+    type Error = SignerError;
+
+    fn try_from(value: JsonSigningResponse) -> Result<Self, Self::Error> {
+        let signatures = value
+            .signatures
+            .into_iter()
+            .map(|partial| {
+                Ok(PartialSignature::new(
+                    PublicKey::from_bytes(&partial.public_key)?,
+                    partial.message,
+                    Signature::from_bytes(&partial.signature)?,
+                ))
+            })
+            .collect::<Result<_, SignerError>>()?;
+
+        Ok(Self::new(signatures))
+    }
+}
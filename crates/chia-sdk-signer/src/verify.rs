@@ -0,0 +1,229 @@
+use chia_bls::{aggregate_verify, PublicKey, Signature};
+use chia_consensus::consensus_constants::ConsensusConstants;
+use chia_protocol::SpendBundle;
+use clvmr::Allocator;
+
+use crate::{RequiredSignature, SignerError};
+
+/// The maximum number of distinct public keys for which the missing signatures will be narrowed down.
+/// Every subset of the public keys is checked, so this is kept small.
+const MAX_DIAGNOSTIC_PUBLIC_KEYS: usize = 8;
+
+/// Checks that the aggregated signature of a spend bundle covers all of the [`RequiredSignature`]s
+/// of its coin spends, using the same messages that [`RequiredSignature::final_message`] produces.
+///
+/// If the signature is invalid, [`SignerError::UncoveredSignatures`] contains the required signatures
+/// that aren't covered. When this can't be narrowed down, every required signature is included.
+pub fn verify_spend_bundle_signature(
+    spend_bundle: &SpendBundle,
+    constants: &ConsensusConstants,
+) -> Result<(), SignerError> {
+    let mut allocator = Allocator::new();
+
+    let required_signatures =
+        RequiredSignature::from_coin_spends(&mut allocator, &spend_bundle.coin_spends, constants)?;
+
+    verify_required_signatures(&spend_bundle.aggregated_signature, &required_signatures)
+}
+
+/// Checks that an aggregated signature covers all of the given [`RequiredSignature`]s.
+///
+/// If the signature is invalid, [`SignerError::UncoveredSignatures`] contains the required signatures
+/// that aren't covered. When this can't be narrowed down, every required signature is included.
+pub fn verify_required_signatures(
+    aggregated_signature: &Signature,
+    required_signatures: &[RequiredSignature],
+) -> Result<(), SignerError> {
+    if verify_subset(aggregated_signature, required_signatures.iter()) {
+        return Ok(());
+    }
+
+    Err(SignerError::UncoveredSignatures(uncovered_signatures(
+        aggregated_signature,
+        required_signatures,
+    )))
+}
+
+fn verify_subset<'a>(
+    signature: &Signature,
+    required_signatures: impl Iterator<Item = &'a RequiredSignature>,
+) -> bool {
+    let messages: Vec<(PublicKey, Vec<u8>)> = required_signatures
+        .map(|required| (required.public_key(), required.final_message()))
+        .collect();
+
+    aggregate_verify(
+        signature,
+        messages
+            .iter()
+            .map(|(public_key, message)| (public_key, message.as_slice())),
+    )
+}
+
+/// Finds the largest set of public keys whose required signatures are covered by the signature.
+/// The required signatures for the remaining public keys are returned.
+fn uncovered_signatures(
+    signature: &Signature,
+    required_signatures: &[RequiredSignature],
+) -> Vec<RequiredSignature> {
+    let mut public_keys = Vec::new();
+
+    for required in required_signatures {
+        if !public_keys.contains(&required.public_key()) {
+            public_keys.push(required.public_key());
+        }
+    }
+
+    if !signature.is_valid() || public_keys.len() > MAX_DIAGNOSTIC_PUBLIC_KEYS {
+        return required_signatures.to_vec();
+    }
+
+    // The full set has already been checked, so start with the largest proper subsets.
+    let full_mask = (1u32 << public_keys.len()) - 1;
+    let mut masks: Vec<u32> = (0..full_mask).collect();
+    masks.sort_by_key(|mask| std::cmp::Reverse(mask.count_ones()));
+
+    let in_mask = |mask: u32, public_key: &PublicKey| {
+        let index = public_keys
+            .iter()
+            .position(|pk| pk == public_key)
+            .expect("public key should be present");
+        mask & (1 << index) != 0
+    };
+
+    for mask in masks {
+        let covered = required_signatures
+            .iter()
+            .filter(|required| in_mask(mask, &required.public_key()));
+
+        if verify_subset(signature, covered) {
+            return required_signatures
+                .iter()
+                .filter(|required| !in_mask(mask, &required.public_key()))
+                .cloned()
+                .collect();
+        }
+    }
+
+    required_signatures.to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chia_bls::{sign, SecretKey};
+    use chia_protocol::{Bytes, Bytes32, Coin, CoinSpend, Program};
+    use chia_sdk_types::{AggSigMe, Condition, MAINNET_CONSTANTS};
+    use clvm_traits::{FromClvm, ToClvm};
+
+    fn coin_spend(public_keys: &[PublicKey]) -> anyhow::Result<CoinSpend> {
+        let mut allocator = Allocator::new();
+
+        let conditions: Vec<Condition> = public_keys
+            .iter()
+            .map(|&public_key| {
+                Condition::AggSigMe(AggSigMe::new(public_key, Bytes::from(vec![1, 2, 3])))
+            })
+            .collect();
+
+        let ptr = conditions.to_clvm(&mut allocator)?;
+
+        // The puzzle `1` returns the solution as its output conditions.
+        Ok(CoinSpend::new(
+            Coin::new(Bytes32::default(), Bytes32::default(), 1),
+            Program::from(vec![1]),
+            Program::from_clvm(&allocator, ptr)?,
+        ))
+    }
+
+    fn sign_with(coin_spend: &CoinSpend, secret_keys: &[&SecretKey]) -> anyhow::Result<Signature> {
+        let mut allocator = Allocator::new();
+        let required =
+            RequiredSignature::from_coin_spend(&mut allocator, coin_spend, &MAINNET_CONSTANTS)?;
+
+        let mut signature = Signature::default();
+
+        for required in required {
+            if let Some(sk) = secret_keys
+                .iter()
+                .find(|sk| sk.public_key() == required.public_key())
+            {
+                signature += &sign(sk, required.final_message());
+            }
+        }
+
+        Ok(signature)
+    }
+
+    #[test]
+    fn test_valid_signature() -> anyhow::Result<()> {
+        let alice = SecretKey::from_seed(&[1; 32]);
+        let bob = SecretKey::from_seed(&[2; 32]);
+
+        let coin_spend = coin_spend(&[alice.public_key(), bob.public_key()])?;
+        let signature = sign_with(&coin_spend, &[&alice, &bob])?;
+
+        verify_spend_bundle_signature(
+            &SpendBundle::new(vec![coin_spend], signature),
+            &MAINNET_CONSTANTS,
+        )?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_required_signatures() -> anyhow::Result<()> {
+        let coin_spend = coin_spend(&[])?;
+
+        verify_spend_bundle_signature(
+            &SpendBundle::new(vec![coin_spend], Signature::default()),
+            &MAINNET_CONSTANTS,
+        )?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_signature() -> anyhow::Result<()> {
+        let alice = SecretKey::from_seed(&[1; 32]);
+        let bob = SecretKey::from_seed(&[2; 32]);
+
+        let coin_spend = coin_spend(&[alice.public_key(), bob.public_key()])?;
+        let signature = sign_with(&coin_spend, &[&alice])?;
+
+        let SignerError::UncoveredSignatures(uncovered) = verify_spend_bundle_signature(
+            &SpendBundle::new(vec![coin_spend], signature),
+            &MAINNET_CONSTANTS,
+        )
+        .unwrap_err() else {
+            panic!("expected uncovered signatures");
+        };
+
+        assert_eq!(uncovered.len(), 1);
+        assert_eq!(uncovered[0].public_key(), bob.public_key());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_wrong_signature() -> anyhow::Result<()> {
+        let alice = SecretKey::from_seed(&[1; 32]);
+
+        let coin_spend = coin_spend(&[alice.public_key()])?;
+        let signature = sign(&alice, b"wrong message");
+
+        let SignerError::UncoveredSignatures(uncovered) = verify_spend_bundle_signature(
+            &SpendBundle::new(vec![coin_spend], signature),
+            &MAINNET_CONSTANTS,
+        )
+        .unwrap_err() else {
+            panic!("expected uncovered signatures");
+        };
+
+        assert_eq!(uncovered.len(), 1);
+        assert_eq!(uncovered[0].public_key(), alice.public_key());
+
+        Ok(())
+    }
+}
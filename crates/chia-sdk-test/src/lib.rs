@@ -3,6 +3,7 @@ mod error;
 mod events;
 mod keys;
 mod peer_simulator;
+mod signing_backend;
 mod simulator;
 mod transaction;
 
@@ -11,6 +12,7 @@ pub use error::*;
 pub use events::*;
 pub use keys::*;
 pub use peer_simulator::*;
+pub use signing_backend::*;
 pub use simulator::*;
 pub use transaction::*;
 
@@ -0,0 +1,146 @@
+use std::sync::Mutex;
+
+use chia_bls::SecretKey;
+use chia_sdk_signer::{
+    InProcessSigner, SignerError, SigningBackend, SigningRequest, SigningResponse,
+};
+
+/// A [`SigningBackend`] for tests, which signs in-process but records every request it receives.
+/// It can also be made to fail, to simulate a hardware wallet or remote signer rejecting a request.
+#[derive(Debug, Default)]
+pub struct MockSigningBackend {
+    signer: InProcessSigner,
+    requests: Mutex<Vec<SigningRequest>>,
+    error: Option<String>,
+}
+
+impl MockSigningBackend {
+    pub fn new(secret_keys: Vec<SecretKey>) -> Self {
+        Self {
+            signer: InProcessSigner::new(secret_keys),
+            requests: Mutex::new(Vec::new()),
+            error: None,
+        }
+    }
+
+    /// Rejects every request with the given error message.
+    #[must_use]
+    pub fn with_error(mut self, message: impl Into<String>) -> Self {
+        self.error = Some(message.into());
+        self
+    }
+
+    /// The requests that have been received so far.
+    ///
+    /// # Panics
+    /// Will panic if the lock is poisoned.
+    pub fn requests(&self) -> Vec<SigningRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl SigningBackend for MockSigningBackend {
+    async fn sign(&self, request: SigningRequest) -> Result<SigningResponse, SignerError> {
+        // Make sure the request survives being sent to a remote signer.
+        let request = SigningRequest::from_json(&request.to_json()?)?;
+        self.requests.lock().unwrap().push(request.clone());
+
+        if let Some(message) = &self.error {
+            return Err(SignerError::Backend(message.clone()));
+        }
+
+        let response = self.signer.sign_request(&request);
+        SigningResponse::from_streamable_bytes(&response.to_streamable_bytes()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_bls::PublicKey;
+    use chia_protocol::{Bytes, CoinSpend};
+    use chia_sdk_signer::PartialSpendBundle;
+    use chia_sdk_types::{AggSigMe, Condition, TESTNET11_CONSTANTS};
+    use clvmr::Allocator;
+
+    use crate::{to_program, to_puzzle, Simulator};
+
+    use super::*;
+
+    fn coin_spend(sim: &mut Simulator, public_keys: &[PublicKey]) -> anyhow::Result<CoinSpend> {
+        // The puzzle `1` returns the solution as its output conditions.
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+        let coin = sim.new_coin(puzzle_hash, 1);
+
+        let conditions: Vec<Condition> = public_keys
+            .iter()
+            .map(|&public_key| {
+                Condition::AggSigMe(AggSigMe::new(public_key, Bytes::from(vec![1, 2, 3])))
+            })
+            .collect();
+
+        Ok(CoinSpend::new(coin, puzzle_reveal, to_program(conditions)?))
+    }
+
+    #[tokio::test]
+    async fn test_mock_signing_backends() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+
+        let alice = SecretKey::from_seed(&[1; 32]);
+        let bob = SecretKey::from_seed(&[2; 32]);
+
+        let coin_spends = vec![coin_spend(
+            &mut sim,
+            &[alice.public_key(), bob.public_key()],
+        )?];
+
+        let mut partial = PartialSpendBundle::from_coin_spends(
+            &mut Allocator::new(),
+            coin_spends,
+            &TESTNET11_CONSTANTS,
+        )?;
+
+        let alice_backend = MockSigningBackend::new(vec![alice]);
+        let bob_backend = MockSigningBackend::new(vec![bob.clone()]);
+
+        assert_eq!(partial.sign_with(&alice_backend).await?, 1);
+        assert_eq!(partial.missing_public_keys(), vec![bob.public_key()]);
+
+        assert_eq!(partial.sign_with(&bob_backend).await?, 1);
+        assert!(partial.is_complete());
+
+        // Bob is only asked for the signature that is still missing.
+        let requests = bob_backend.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].required_signatures.len(), 1);
+        requests[0].validate(&mut Allocator::new(), &TESTNET11_CONSTANTS)?;
+
+        sim.new_transaction(partial.finalize()?, &TESTNET11_CONSTANTS)?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_failing_signing_backend() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+
+        let alice = SecretKey::from_seed(&[1; 32]);
+        let coin_spends = vec![coin_spend(&mut sim, &[alice.public_key()])?];
+
+        let mut partial = PartialSpendBundle::from_coin_spends(
+            &mut Allocator::new(),
+            coin_spends,
+            &TESTNET11_CONSTANTS,
+        )?;
+
+        let backend = MockSigningBackend::new(vec![alice]).with_error("user rejected");
+
+        assert!(matches!(
+            partial.sign_with(&backend).await.unwrap_err(),
+            SignerError::Backend(message) if message == "user rejected"
+        ));
+        assert_eq!(backend.requests().len(), 1);
+        assert!(!partial.is_complete());
+
+        Ok(())
+    }
+}
@@ -7,7 +7,7 @@ use chia_consensus::{
 };
 use chia_protocol::{Bytes32, Coin, CoinSpend, CoinState, Program, SpendBundle};
 use chia_puzzles::standard::StandardArgs;
-use chia_sdk_types::TESTNET11_CONSTANTS;
+use chia_sdk_types::{max_mempool_cost, TESTNET11_CONSTANTS};
 use fastrand::Rng;
 use indexmap::{IndexMap, IndexSet};
 
@@ -144,10 +144,13 @@ impl Simulator {
             return Err(SimulatorError::Validation(ErrorCode::InvalidSpendBundle));
         }
 
-        // TODO: Fix cost
-        let (conds, _pairings, _duration) =
-            validate_clvm_and_signature(&spend_bundle, 7_700_000_000, constants, self.height)
-                .map_err(SimulatorError::Validation)?;
+        let (conds, _pairings, _duration) = validate_clvm_and_signature(
+            &spend_bundle,
+            max_mempool_cost(constants),
+            constants,
+            self.height,
+        )
+        .map_err(SimulatorError::Validation)?;
 
         let puzzle_hashes: HashSet<Bytes32> =
             conds.spends.iter().map(|spend| spend.puzzle_hash).collect();
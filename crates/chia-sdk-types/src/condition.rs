@@ -4,8 +4,16 @@ use chia_sdk_derive::conditions;
 use clvm_traits::{FromClvm, ToClvm};
 
 mod agg_sig;
+mod message;
+
+#[cfg(feature = "serde")]
+mod json;
 
 pub use agg_sig::*;
+pub use message::*;
+
+#[cfg(feature = "serde")]
+pub use json::ConditionArg;
 
 conditions! {
     pub enum Condition<T> {
@@ -15,42 +23,58 @@ conditions! {
         },
         AggSigParent {
             opcode: i8 if 43,
+            #[serde(rename = "pubkey")]
             public_key: PublicKey,
+            #[serde(rename = "msg")]
             message: Bytes,
         },
         AggSigPuzzle {
             opcode: i8 if 44,
+            #[serde(rename = "pubkey")]
             public_key: PublicKey,
+            #[serde(rename = "msg")]
             message: Bytes,
         },
         AggSigAmount {
             opcode: i8 if 45,
+            #[serde(rename = "pubkey")]
             public_key: PublicKey,
+            #[serde(rename = "msg")]
             message: Bytes,
         },
         AggSigPuzzleAmount {
             opcode: i8 if 46,
+            #[serde(rename = "pubkey")]
             public_key: PublicKey,
+            #[serde(rename = "msg")]
             message: Bytes,
         },
         AggSigParentAmount {
             opcode: i8 if 47,
+            #[serde(rename = "pubkey")]
             public_key: PublicKey,
+            #[serde(rename = "msg")]
             message: Bytes,
         },
         AggSigParentPuzzle {
             opcode: i8 if 48,
+            #[serde(rename = "pubkey")]
             public_key: PublicKey,
+            #[serde(rename = "msg")]
             message: Bytes,
         },
         AggSigUnsafe {
             opcode: i8 if 49,
+            #[serde(rename = "pubkey")]
             public_key: PublicKey,
+            #[serde(rename = "msg")]
             message: Bytes,
         },
         AggSigMe {
             opcode: i8 if 50,
+            #[serde(rename = "pubkey")]
             public_key: PublicKey,
+            #[serde(rename = "msg")]
             message: Bytes,
         },
         CreateCoin {
@@ -65,18 +89,22 @@ conditions! {
         },
         CreateCoinAnnouncement {
             opcode: i8 if 60,
+            #[serde(rename = "msg")]
             message: Bytes,
         },
         AssertCoinAnnouncement as Copy {
             opcode: i8 if 61,
+            #[serde(rename = "msg")]
             announcement_id: Bytes32,
         },
         CreatePuzzleAnnouncement {
             opcode: i8 if 62,
+            #[serde(rename = "msg")]
             message: Bytes,
         },
         AssertPuzzleAnnouncement as Copy {
             opcode: i8 if 63,
+            #[serde(rename = "msg")]
             announcement_id: Bytes32,
         },
         AssertConcurrentSpend as Copy {
@@ -89,14 +117,20 @@ conditions! {
         },
         SendMessage<T> {
             opcode: i8 if 66,
+            #[serde(rename = "mode_integer")]
             mode: u8,
+            #[serde(rename = "msg")]
             message: Bytes,
+            #[serde(rename = "var_args")]
             ...data: Vec<T>,
         },
         ReceiveMessage<T> {
             opcode: i8 if 67,
+            #[serde(rename = "mode_integer")]
             mode: u8,
+            #[serde(rename = "msg")]
             message: Bytes,
+            #[serde(rename = "var_args")]
             ...data: Vec<T>,
         },
         AssertMyCoinId as Copy {
@@ -105,6 +139,7 @@ conditions! {
         },
         AssertMyParentId as Copy {
             opcode: i8 if 71,
+            #[serde(rename = "coin_id")]
             parent_id: Bytes32,
         },
         AssertMyPuzzleHash as Copy {
@@ -207,3 +242,145 @@ pub struct NewMetadataOutput<M, C> {
     pub metadata_info: NewMetadataInfo<M>,
     pub conditions: C,
 }
+
+#[cfg(test)]
+mod tests {
+    use chia_bls::SecretKey;
+    use chia_protocol::Coin;
+    use clvmr::{Allocator, NodePtr};
+
+    use super::*;
+
+    fn roundtrip(allocator: &mut Allocator, condition: &Condition) -> anyhow::Result<()> {
+        let ptr = condition.to_clvm(allocator)?;
+        let parsed = Condition::from_clvm(allocator, ptr)?;
+        assert_eq!(&parsed, condition);
+        Ok(())
+    }
+
+    #[test]
+    fn test_roundtrip_every_opcode() -> anyhow::Result<()> {
+        let mut allocator = Allocator::new();
+        let a = &mut allocator;
+
+        let public_key = SecretKey::from_seed(&[1; 32]).public_key();
+        let message = Bytes::new(vec![1, 2, 3]);
+        let hash = Bytes32::new([4; 32]);
+        let rest = (1, (2, ())).to_clvm(a)?;
+        let program = 1.to_clvm(a)?;
+        let data = vec![hash.to_clvm(a)?];
+
+        let conditions = vec![
+            Condition::remark(rest),
+            Condition::agg_sig_parent(public_key, message.clone()),
+            Condition::agg_sig_puzzle(public_key, message.clone()),
+            Condition::agg_sig_amount(public_key, message.clone()),
+            Condition::agg_sig_puzzle_amount(public_key, message.clone()),
+            Condition::agg_sig_parent_amount(public_key, message.clone()),
+            Condition::agg_sig_parent_puzzle(public_key, message.clone()),
+            Condition::agg_sig_unsafe(public_key, message.clone()),
+            Condition::agg_sig_me(public_key, message.clone()),
+            Condition::create_coin(hash, 1, Vec::new()),
+            Condition::create_coin(hash, 1, vec![hash.into()]),
+            Condition::reserve_fee(1),
+            Condition::create_coin_announcement(message.clone()),
+            Condition::assert_coin_announcement(hash),
+            Condition::create_puzzle_announcement(message.clone()),
+            Condition::assert_puzzle_announcement(hash),
+            Condition::assert_concurrent_spend(hash),
+            Condition::assert_concurrent_puzzle(hash),
+            Condition::send_message(0b11_1111, message.clone(), data.clone()),
+            Condition::receive_message(0b11_1111, message.clone(), data),
+            Condition::assert_my_coin_id(hash),
+            Condition::assert_my_parent_id(hash),
+            Condition::assert_my_puzzle_hash(hash),
+            Condition::assert_my_amount(1),
+            Condition::assert_my_birth_seconds(1),
+            Condition::assert_my_birth_height(1),
+            Condition::assert_ephemeral(),
+            Condition::assert_seconds_relative(1),
+            Condition::assert_seconds_absolute(1),
+            Condition::assert_height_relative(1),
+            Condition::assert_height_absolute(1),
+            Condition::assert_before_seconds_relative(1),
+            Condition::assert_before_seconds_absolute(1),
+            Condition::assert_before_height_relative(1),
+            Condition::assert_before_height_absolute(1),
+            Condition::softfork(1, rest),
+            Condition::melt_singleton(),
+            Condition::transfer_nft(Some(hash), vec![(100, hash)], Some(hash)),
+            Condition::run_cat_tail(program, program),
+            Condition::update_nft_metadata(program, program),
+            Condition::update_data_store_merkle_root(hash, vec![message]),
+            Condition::Other((99, ()).to_clvm(a)?),
+        ];
+
+        for condition in &conditions {
+            roundtrip(a, condition)?;
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_message_modes() {
+        for mode in 0..=0b11_1111 {
+            let parsed = MessageMode::from_u8(mode).expect("valid mode");
+            assert_eq!(parsed.to_u8(), mode);
+        }
+
+        assert_eq!(MessageMode::from_u8(0b100_0000), None);
+        assert_eq!(
+            MessageMode::new(MessageSide::COIN, MessageSide::PUZZLE_HASH).to_u8(),
+            0b11_1010
+        );
+    }
+
+    #[test]
+    fn test_message_commitments() -> anyhow::Result<()> {
+        let mut allocator = Allocator::new();
+
+        let coin = Coin::new(Bytes32::new([1; 32]), Bytes32::new([2; 32]), 3);
+
+        for bits in 0..=0b111 {
+            let side = MessageSide::from_bits(bits).expect("valid side");
+            let commitment = MessageCommitment::from_coin(&coin, side);
+            assert_eq!(commitment.side(), side);
+
+            let send = SendMessage::<NodePtr>::with_receiver(
+                &mut allocator,
+                MessageSide::COIN,
+                Bytes::new(vec![42]),
+                &commitment,
+            )?;
+            assert_eq!(send.receiver(&allocator)?, commitment);
+
+            let ptr = send.to_clvm(&mut allocator)?;
+            let parsed = SendMessage::<NodePtr>::from_clvm(&allocator, ptr)?;
+            assert_eq!(parsed.receiver(&allocator)?, commitment);
+
+            let receive = ReceiveMessage::<NodePtr>::with_sender(
+                &mut allocator,
+                &commitment,
+                Bytes::new(vec![42]),
+                MessageSide::COIN,
+            )?;
+            assert_eq!(receive.sender(&allocator)?, commitment);
+            assert_eq!(
+                receive.message_mode(),
+                Some(MessageMode::new(side, MessageSide::COIN))
+            );
+        }
+
+        assert_eq!(
+            MessageCommitment::parts(
+                Some(coin.parent_coin_info),
+                Some(coin.puzzle_hash),
+                Some(coin.amount)
+            ),
+            MessageCommitment::coin_id(coin.coin_id())
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,506 @@
+use chia_bls::PublicKey;
+use chia_protocol::{Bytes, Bytes32, Program};
+use clvm_traits::{FromClvm, ToClvm};
+use clvmr::{Allocator, NodePtr};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+/// A value that can be used as a condition argument in the JSON representation used by the Chia RPC.
+/// Bytes and public keys are encoded as `0x` prefixed hex strings, and programs as their serialized bytes.
+pub trait ConditionArg: Sized {
+    fn serialize_arg<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer;
+
+    fn deserialize_arg<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>;
+
+    /// Splits an unknown condition into its opcode and arguments, if it's a well formed condition.
+    fn split_condition(&self) -> Option<(i64, Vec<Self>)> {
+        None
+    }
+
+    /// Joins an opcode and arguments into an unknown condition.
+    fn join_condition(_opcode: i64, _args: Vec<Self>) -> Option<Self> {
+        None
+    }
+}
+
+macro_rules! impl_number {
+    ( $( $ty:ty ),* ) => {
+        $( impl ConditionArg for $ty {
+            fn serialize_arg<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                self.serialize(serializer)
+            }
+
+            fn deserialize_arg<'de, D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                Self::deserialize(deserializer)
+            }
+        } )*
+    };
+}
+
+impl_number!(u8, u16, u32, u64);
+
+fn serialize_hex<S>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&format!("0x{}", hex::encode(bytes)))
+}
+
+fn deserialize_hex<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let text = String::deserialize(deserializer)?;
+    let text = text.strip_prefix("0x").unwrap_or(&text);
+    hex::decode(text).map_err(serde::de::Error::custom)
+}
+
+fn deserialize_array<'de, D, const N: usize>(deserializer: D) -> Result<[u8; N], D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let bytes = deserialize_hex(deserializer)?;
+    let length = bytes.len();
+    bytes
+        .try_into()
+        .map_err(|_| serde::de::Error::custom(format!("expected {N} bytes, but found {length}")))
+}
+
+impl ConditionArg for Bytes {
+    fn serialize_arg<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_hex(self, serializer)
+    }
+
+    fn deserialize_arg<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize_hex(deserializer).map(Self::new)
+    }
+}
+
+impl ConditionArg for Bytes32 {
+    fn serialize_arg<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_hex(self, serializer)
+    }
+
+    fn deserialize_arg<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize_array(deserializer).map(Self::new)
+    }
+}
+
+impl ConditionArg for PublicKey {
+    fn serialize_arg<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_hex(&self.to_bytes(), serializer)
+    }
+
+    fn deserialize_arg<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let bytes = deserialize_array(deserializer)?;
+        Self::from_bytes(&bytes).map_err(serde::de::Error::custom)
+    }
+}
+
+impl ConditionArg for Program {
+    fn serialize_arg<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_hex(self.as_slice(), serializer)
+    }
+
+    fn deserialize_arg<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize_hex(deserializer).map(Self::from)
+    }
+
+    fn split_condition(&self) -> Option<(i64, Vec<Self>)> {
+        let mut allocator = Allocator::new();
+        let ptr = self.to_clvm(&mut allocator).ok()?;
+        let (opcode_ptr, args) = <(NodePtr, Vec<Self>)>::from_clvm(&allocator, ptr).ok()?;
+        let opcode = i64::from_clvm(&allocator, opcode_ptr).ok()?;
+
+        // Non-canonical opcodes wouldn't be preserved, so they're left as they are.
+        let canonical = opcode.to_clvm(&mut allocator).ok()?;
+        if allocator.atom(canonical).as_ref() != allocator.atom(opcode_ptr).as_ref() {
+            return None;
+        }
+
+        Some((opcode, args))
+    }
+
+    fn join_condition(opcode: i64, args: Vec<Self>) -> Option<Self> {
+        let mut allocator = Allocator::new();
+        let ptr = (opcode, args).to_clvm(&mut allocator).ok()?;
+        Self::from_clvm(&allocator, ptr).ok()
+    }
+}
+
+impl<T> ConditionArg for Vec<T>
+where
+    T: ConditionArg,
+{
+    fn serialize_arg<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(self.iter().map(Arg))
+    }
+
+    /// A missing list is represented as `null` by the Chia RPC, which is treated as empty.
+    fn deserialize_arg<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let items = Option::<Vec<OwnedArg<T>>>::deserialize(deserializer)?;
+        Ok(items
+            .unwrap_or_default()
+            .into_iter()
+            .map(|item| item.0)
+            .collect())
+    }
+}
+
+impl<T> ConditionArg for Option<T>
+where
+    T: ConditionArg,
+{
+    fn serialize_arg<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Some(value) => serializer.serialize_some(&Arg(value)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    fn deserialize_arg<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(Option::<OwnedArg<T>>::deserialize(deserializer)?.map(|value| value.0))
+    }
+}
+
+impl<A, B> ConditionArg for (A, B)
+where
+    A: ConditionArg,
+    B: ConditionArg,
+{
+    fn serialize_arg<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        (Arg(&self.0), Arg(&self.1)).serialize(serializer)
+    }
+
+    fn deserialize_arg<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let (a, b) = <(OwnedArg<A>, OwnedArg<B>)>::deserialize(deserializer)?;
+        Ok((a.0, b.0))
+    }
+}
+
+struct Arg<'a, T>(&'a T);
+
+impl<T> Serialize for Arg<'_, T>
+where
+    T: ConditionArg,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize_arg(serializer)
+    }
+}
+
+struct OwnedArg<T>(T);
+
+impl<'de, T> Deserialize<'de> for OwnedArg<T>
+where
+    T: ConditionArg,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize_arg(deserializer).map(Self)
+    }
+}
+
+pub(crate) fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: ConditionArg,
+    S: Serializer,
+{
+    value.serialize_arg(serializer)
+}
+
+pub(crate) fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: ConditionArg,
+    D: Deserializer<'de>,
+{
+    T::deserialize_arg(deserializer)
+}
+
+#[derive(Serialize)]
+struct JsonCondition<'a, A> {
+    opcode: Option<i64>,
+    args: &'a A,
+}
+
+pub(crate) fn serialize_condition<S, A>(
+    serializer: S,
+    opcode: i8,
+    args: &A,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    A: Serialize,
+{
+    JsonCondition {
+        opcode: Some(opcode.into()),
+        args,
+    }
+    .serialize(serializer)
+}
+
+/// Conditions that aren't known are stored with their opcode and a list of raw arguments, like the Chia RPC.
+/// If the condition isn't well formed, it has no opcode and the whole condition is stored in the arguments.
+pub(crate) fn serialize_other<S, T>(serializer: S, value: &T) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: ConditionArg,
+{
+    if let Some((opcode, args)) = value.split_condition() {
+        return JsonCondition {
+            opcode: Some(opcode),
+            args: &Arg(&args),
+        }
+        .serialize(serializer);
+    }
+
+    JsonCondition {
+        opcode: None,
+        args: &Arg(value),
+    }
+    .serialize(serializer)
+}
+
+#[derive(Deserialize)]
+pub(crate) struct RawCondition {
+    #[serde(default)]
+    opcode: Option<i64>,
+    #[serde(default)]
+    args: Value,
+}
+
+impl RawCondition {
+    pub(crate) fn is_opcode(&self, opcode: i8) -> bool {
+        self.opcode == Some(opcode.into())
+    }
+
+    pub(crate) fn args<A>(&self) -> Result<A, serde_json::Error>
+    where
+        A: DeserializeOwned,
+    {
+        A::deserialize(&self.args)
+    }
+
+    pub(crate) fn into_other<T>(self) -> Result<T, String>
+    where
+        T: ConditionArg,
+    {
+        let Some(opcode) = self.opcode else {
+            return T::deserialize_arg(self.args).map_err(|error| error.to_string());
+        };
+
+        let args = Vec::<T>::deserialize_arg(self.args).map_err(|error| error.to_string())?;
+
+        T::join_condition(opcode, args)
+            .ok_or_else(|| format!("unknown condition opcode {opcode} can't be represented"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_bls::SecretKey;
+    use serde_json::json;
+
+    use crate::{Condition, Conditions};
+
+    use super::*;
+
+    #[test]
+    fn test_chia_rpc_compatibility() -> anyhow::Result<()> {
+        let public_key = SecretKey::from_seed(&[1; 32]).public_key();
+        let pubkey = format!("0x{}", hex::encode(public_key.to_bytes()));
+        let hash = Bytes32::new([1; 32]);
+        let hash_hex = format!("0x{}", hex::encode(hash));
+
+        // This is the shape produced by `conditions_to_json_dicts` in the Chia reference wallet.
+        let value = json!([
+            { "opcode": 51, "args": { "puzzle_hash": hash_hex, "amount": 1000, "memos": [hash_hex] } },
+            { "opcode": 50, "args": { "pubkey": pubkey, "msg": "0xcafe" } },
+            { "opcode": 52, "args": { "amount": 100 } },
+            { "opcode": 60, "args": { "msg": "0xcafe" } },
+            { "opcode": 61, "args": { "msg": hash_hex, "asserted_id": null, "asserted_msg": null } },
+            { "opcode": 71, "args": { "coin_id": hash_hex } },
+            { "opcode": 83, "args": { "height": 100 } },
+            { "opcode": 1, "args": { "rest": "0xff0180" } },
+        ]);
+
+        let conditions: Conditions<Program> = serde_json::from_value(value)?;
+
+        let expected = Conditions::<Program>::default()
+            .create_coin(hash, 1000, vec![hash.into()])
+            .agg_sig_me(public_key, Bytes::new(vec![0xca, 0xfe]))
+            .reserve_fee(100)
+            .create_coin_announcement(Bytes::new(vec![0xca, 0xfe]))
+            .assert_coin_announcement(hash)
+            .assert_my_parent_id(hash)
+            .assert_height_absolute(100)
+            .remark(Program::from(vec![0xff, 0x01, 0x80]));
+
+        assert_eq!(conditions, expected);
+
+        assert_eq!(
+            serde_json::to_value(Condition::<Program>::agg_sig_me(
+                public_key,
+                Bytes::new(vec![0xca, 0xfe])
+            ))?,
+            json!({ "opcode": 50, "args": { "pubkey": pubkey, "msg": "0xcafe" } })
+        );
+
+        assert_eq!(
+            serde_json::to_value(Condition::<Program>::create_coin(hash, 1, Vec::new()))?,
+            json!({ "opcode": 51, "args": { "puzzle_hash": hash_hex, "amount": 1, "memos": [] } })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_condition_compatibility() -> anyhow::Result<()> {
+        // Unknown conditions are represented by their opcode and raw arguments, like in the reference wallet.
+        let value = json!({ "opcode": 99, "args": ["0x01", "0x80", "0xff0180"] });
+
+        let condition: Condition<Program> = serde_json::from_value(value.clone())?;
+        assert_eq!(
+            condition,
+            Condition::Other(Program::from(vec![
+                0xff, 0x63, 0xff, 0x01, 0xff, 0x80, 0xff, 0xff, 0x01, 0x80, 0x80
+            ]))
+        );
+        assert_eq!(serde_json::to_value(&condition)?, value);
+
+        // Conditions which aren't a list of arguments are kept as they are.
+        let condition = Condition::Other(Program::from(vec![0x01]));
+        assert_eq!(
+            serde_json::to_value(&condition)?,
+            json!({ "opcode": null, "args": "0x01" })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_roundtrip() -> anyhow::Result<()> {
+        let public_key = SecretKey::from_seed(&[1; 32]).public_key();
+        let message = Bytes::new(vec![1, 2, 3]);
+        let hash = Bytes32::new([4; 32]);
+        let program = Program::from(vec![0x01]);
+
+        let conditions = vec![
+            Condition::agg_sig_unsafe(public_key, message.clone()),
+            Condition::create_coin(hash, 1, vec![message.clone()]),
+            Condition::send_message(0b11_1111, message.clone(), vec![program.clone()]),
+            Condition::receive_message(0b11_1111, message.clone(), vec![program.clone()]),
+            Condition::assert_ephemeral(),
+            Condition::softfork(1, program.clone()),
+            Condition::melt_singleton(),
+            Condition::transfer_nft(Some(hash), vec![(100, hash)], None),
+            Condition::run_cat_tail(program.clone(), program.clone()),
+            Condition::update_nft_metadata(program.clone(), program.clone()),
+            Condition::update_data_store_merkle_root(hash, vec![message]),
+            Condition::Other(program),
+            Condition::Other(Program::from(vec![0xff, 0x63, 0xff, 0x01, 0x80])),
+        ];
+
+        for condition in conditions {
+            let json = serde_json::to_string(&condition)?;
+            let parsed: Condition<Program> = serde_json::from_str(&json)?;
+            assert_eq!(parsed, condition);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_errors() {
+        let missing_memos: Condition<Program> = serde_json::from_value(json!({
+            "opcode": 51,
+            "args": { "puzzle_hash": format!("0x{}", "00".repeat(32)), "amount": 1, "memos": null }
+        }))
+        .expect("valid create coin");
+        assert_eq!(
+            missing_memos,
+            Condition::create_coin(Bytes32::default(), 1, Vec::new())
+        );
+
+        assert!(serde_json::from_value::<Condition<Program>>(json!({
+            "opcode": 52,
+            "args": { "amount": "0x01" }
+        }))
+        .is_err());
+
+        assert!(serde_json::from_value::<Condition<Program>>(json!({
+            "opcode": 99,
+            "args": {}
+        }))
+        .is_err());
+
+        assert!(serde_json::from_value::<Condition<Program>>(json!({
+            "opcode": 99,
+            "args": ["0xzz"]
+        }))
+        .is_err());
+
+        assert!(serde_json::from_value::<Condition<Program>>(json!({
+            "opcode": 72,
+            "args": { "puzzle_hash": "0x00" }
+        }))
+        .is_err());
+    }
+}
@@ -0,0 +1,327 @@
+use chia_protocol::{Bytes, Bytes32, Coin};
+use clvm_traits::{ClvmDecoder, ClvmEncoder, FromClvm, FromClvmError, ToClvm, ToClvmError};
+
+use super::{ReceiveMessage, SendMessage};
+
+/// The parts of a coin that one side of a message commits to, as described in CHIP-25.
+/// Committing to the parent, puzzle hash and amount at once is the same as committing to the coin id.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MessageSide {
+    pub parent: bool,
+    pub puzzle_hash: bool,
+    pub amount: bool,
+}
+
+impl MessageSide {
+    pub const NONE: Self = Self::new(false, false, false);
+    pub const PARENT: Self = Self::new(true, false, false);
+    pub const PUZZLE_HASH: Self = Self::new(false, true, false);
+    pub const AMOUNT: Self = Self::new(false, false, true);
+    pub const PARENT_PUZZLE_HASH: Self = Self::new(true, true, false);
+    pub const PARENT_AMOUNT: Self = Self::new(true, false, true);
+    pub const PUZZLE_HASH_AMOUNT: Self = Self::new(false, true, true);
+    pub const COIN: Self = Self::new(true, true, true);
+
+    pub const fn new(parent: bool, puzzle_hash: bool, amount: bool) -> Self {
+        Self {
+            parent,
+            puzzle_hash,
+            amount,
+        }
+    }
+
+    /// The 3 bit representation of this side of the message mode.
+    pub const fn bits(self) -> u8 {
+        (self.parent as u8) << 2 | (self.puzzle_hash as u8) << 1 | self.amount as u8
+    }
+
+    /// Parses the 3 bit representation of one side of the message mode.
+    pub const fn from_bits(bits: u8) -> Option<Self> {
+        if bits > 0b111 {
+            return None;
+        }
+        Some(Self::new(
+            bits & 0b100 != 0,
+            bits & 0b010 != 0,
+            bits & 0b001 != 0,
+        ))
+    }
+}
+
+/// The mode of a `SEND_MESSAGE` or `RECEIVE_MESSAGE` condition, which determines which parts
+/// of the sender and receiver coins the message is committed to.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MessageMode {
+    pub sender: MessageSide,
+    pub receiver: MessageSide,
+}
+
+impl MessageMode {
+    pub const fn new(sender: MessageSide, receiver: MessageSide) -> Self {
+        Self { sender, receiver }
+    }
+
+    /// The mode byte used in the condition.
+    pub const fn to_u8(self) -> u8 {
+        self.sender.bits() << 3 | self.receiver.bits()
+    }
+
+    /// Parses a mode byte, which must only use the lower 6 bits.
+    pub const fn from_u8(mode: u8) -> Option<Self> {
+        if mode > 0b11_1111 {
+            return None;
+        }
+
+        let Some(sender) = MessageSide::from_bits(mode >> 3) else {
+            return None;
+        };
+
+        let Some(receiver) = MessageSide::from_bits(mode & 0b111) else {
+            return None;
+        };
+
+        Some(Self::new(sender, receiver))
+    }
+}
+
+/// The values that one side of a message commits to.
+/// These are the extra arguments of the condition, which refer to the other coin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MessageCommitment {
+    /// Commits to the coin id, which is used when the parent, puzzle hash and amount are all committed to.
+    CoinId(Bytes32),
+    /// Commits to any combination of the parent, puzzle hash and amount, except for all of them.
+    Parts {
+        parent_id: Option<Bytes32>,
+        puzzle_hash: Option<Bytes32>,
+        amount: Option<u64>,
+    },
+}
+
+impl MessageCommitment {
+    /// Commits to nothing, so that any coin can be on the other side of the message.
+    pub fn none() -> Self {
+        Self::parts(None, None, None)
+    }
+
+    pub fn coin_id(coin_id: Bytes32) -> Self {
+        Self::CoinId(coin_id)
+    }
+
+    pub fn parent(parent_id: Bytes32) -> Self {
+        Self::parts(Some(parent_id), None, None)
+    }
+
+    pub fn puzzle_hash(puzzle_hash: Bytes32) -> Self {
+        Self::parts(None, Some(puzzle_hash), None)
+    }
+
+    pub fn amount(amount: u64) -> Self {
+        Self::parts(None, None, Some(amount))
+    }
+
+    /// Commits to the given parts of a coin. If all of them are given, the coin id is used instead.
+    pub fn parts(
+        parent_id: Option<Bytes32>,
+        puzzle_hash: Option<Bytes32>,
+        amount: Option<u64>,
+    ) -> Self {
+        if let (Some(parent_id), Some(puzzle_hash), Some(amount)) = (parent_id, puzzle_hash, amount)
+        {
+            return Self::CoinId(Coin::new(parent_id, puzzle_hash, amount).coin_id());
+        }
+
+        Self::Parts {
+            parent_id,
+            puzzle_hash,
+            amount,
+        }
+    }
+
+    /// Commits to the parts of the coin on the given side of the message.
+    pub fn from_coin(coin: &Coin, side: MessageSide) -> Self {
+        if side == MessageSide::COIN {
+            return Self::CoinId(coin.coin_id());
+        }
+
+        Self::Parts {
+            parent_id: side.parent.then_some(coin.parent_coin_info),
+            puzzle_hash: side.puzzle_hash.then_some(coin.puzzle_hash),
+            amount: side.amount.then_some(coin.amount),
+        }
+    }
+
+    /// The side of the message mode that this commitment corresponds to.
+    pub fn side(&self) -> MessageSide {
+        match self {
+            Self::CoinId(..) => MessageSide::COIN,
+            Self::Parts {
+                parent_id,
+                puzzle_hash,
+                amount,
+            } => MessageSide::new(parent_id.is_some(), puzzle_hash.is_some(), amount.is_some()),
+        }
+    }
+
+    /// Encodes the commitment as the extra arguments of a message condition.
+    pub fn to_data<E>(&self, encoder: &mut E) -> Result<Vec<E::Node>, ToClvmError>
+    where
+        E: ClvmEncoder,
+    {
+        match self {
+            Self::CoinId(coin_id) => Ok(vec![coin_id.to_clvm(encoder)?]),
+            Self::Parts {
+                parent_id,
+                puzzle_hash,
+                amount,
+            } => {
+                let mut data = Vec::new();
+                if let Some(parent_id) = parent_id {
+                    data.push(parent_id.to_clvm(encoder)?);
+                }
+                if let Some(puzzle_hash) = puzzle_hash {
+                    data.push(puzzle_hash.to_clvm(encoder)?);
+                }
+                if let Some(amount) = amount {
+                    data.push(amount.to_clvm(encoder)?);
+                }
+                Ok(data)
+            }
+        }
+    }
+
+    /// Decodes the extra arguments of a message condition, for the given side of the message mode.
+    pub fn from_data<D>(
+        decoder: &D,
+        side: MessageSide,
+        data: &[D::Node],
+    ) -> Result<Self, FromClvmError>
+    where
+        D: ClvmDecoder,
+    {
+        let expected = if side == MessageSide::COIN {
+            1
+        } else {
+            side.bits().count_ones() as usize
+        };
+
+        if data.len() != expected {
+            return Err(FromClvmError::Custom(format!(
+                "expected {expected} message arguments, but found {}",
+                data.len()
+            )));
+        }
+
+        if side == MessageSide::COIN {
+            return Ok(Self::CoinId(Bytes32::from_clvm(decoder, data[0].clone())?));
+        }
+
+        let mut data = data.iter().cloned();
+
+        let parent_id = if side.parent {
+            data.next()
+                .map(|node| Bytes32::from_clvm(decoder, node))
+                .transpose()?
+        } else {
+            None
+        };
+
+        let puzzle_hash = if side.puzzle_hash {
+            data.next()
+                .map(|node| Bytes32::from_clvm(decoder, node))
+                .transpose()?
+        } else {
+            None
+        };
+
+        let amount = if side.amount {
+            data.next()
+                .map(|node| u64::from_clvm(decoder, node))
+                .transpose()?
+        } else {
+            None
+        };
+
+        Ok(Self::Parts {
+            parent_id,
+            puzzle_hash,
+            amount,
+        })
+    }
+}
+
+impl<T> SendMessage<T> {
+    /// Creates a message from this coin to the committed receiver. The receiver's side of the mode
+    /// is determined by the commitment.
+    pub fn with_receiver<E>(
+        encoder: &mut E,
+        sender: MessageSide,
+        message: Bytes,
+        receiver: &MessageCommitment,
+    ) -> Result<Self, ToClvmError>
+    where
+        E: ClvmEncoder<Node = T>,
+    {
+        let mode = MessageMode::new(sender, receiver.side());
+        Ok(Self::new(mode.to_u8(), message, receiver.to_data(encoder)?))
+    }
+
+    /// The typed message mode, if it's valid.
+    pub fn message_mode(&self) -> Option<MessageMode> {
+        MessageMode::from_u8(self.mode)
+    }
+}
+
+impl<T> SendMessage<T>
+where
+    T: Clone,
+{
+    /// Decodes the commitment to the receiver of the message.
+    pub fn receiver<D>(&self, decoder: &D) -> Result<MessageCommitment, FromClvmError>
+    where
+        D: ClvmDecoder<Node = T>,
+    {
+        let mode = self
+            .message_mode()
+            .ok_or_else(|| FromClvmError::Custom(format!("invalid message mode {}", self.mode)))?;
+        MessageCommitment::from_data(decoder, mode.receiver, &self.data)
+    }
+}
+
+impl<T> ReceiveMessage<T> {
+    /// Receives a message from the committed sender into this coin. The sender's side of the mode
+    /// is determined by the commitment.
+    pub fn with_sender<E>(
+        encoder: &mut E,
+        sender: &MessageCommitment,
+        message: Bytes,
+        receiver: MessageSide,
+    ) -> Result<Self, ToClvmError>
+    where
+        E: ClvmEncoder<Node = T>,
+    {
+        let mode = MessageMode::new(sender.side(), receiver);
+        Ok(Self::new(mode.to_u8(), message, sender.to_data(encoder)?))
+    }
+
+    /// The typed message mode, if it's valid.
+    pub fn message_mode(&self) -> Option<MessageMode> {
+        MessageMode::from_u8(self.mode)
+    }
+}
+
+impl<T> ReceiveMessage<T>
+where
+    T: Clone,
+{
+    /// Decodes the commitment to the sender of the message.
+    pub fn sender<D>(&self, decoder: &D) -> Result<MessageCommitment, FromClvmError>
+    where
+        D: ClvmDecoder<Node = T>,
+    {
+        let mode = self
+            .message_mode()
+            .ok_or_else(|| FromClvmError::Custom(format!("invalid message mode {}", self.mode)))?;
+        MessageCommitment::from_data(decoder, mode.sender, &self.data)
+    }
+}
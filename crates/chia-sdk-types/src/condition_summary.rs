@@ -0,0 +1,276 @@
+use chia_protocol::{Bytes, Bytes32, Coin};
+
+use crate::{announcement_id, AggSig, Condition};
+
+/// A coin created by a `CREATE_COIN` condition, along with its memos.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreatedCoin {
+    pub coin: Coin,
+    pub memos: Vec<Bytes>,
+}
+
+/// The range of heights and timestamps in which a set of time locks can be satisfied.
+/// The lower bounds are inclusive, and the upper bounds are exclusive.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TimeWindow {
+    pub min_height: Option<u32>,
+    pub before_height: Option<u32>,
+    pub min_seconds: Option<u64>,
+    pub before_seconds: Option<u64>,
+}
+
+impl TimeWindow {
+    /// Whether the time locks are contradictory, and can therefore never be satisfied.
+    pub fn is_empty(&self) -> bool {
+        let height_empty = matches!(
+            (self.min_height, self.before_height),
+            (Some(min), Some(before)) if min >= before
+        );
+        let seconds_empty = matches!(
+            (self.min_seconds, self.before_seconds),
+            (Some(min), Some(before)) if min >= before
+        );
+        height_empty || seconds_empty
+    }
+
+    /// Whether there are no time locks at all.
+    pub fn is_unbounded(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Whether the given height and timestamp are within the window.
+    pub fn contains(&self, height: u32, seconds: u64) -> bool {
+        self.min_height.map_or(true, |min| height >= min)
+            && self.before_height.map_or(true, |before| height < before)
+            && self.min_seconds.map_or(true, |min| seconds >= min)
+            && self.before_seconds.map_or(true, |before| seconds < before)
+    }
+
+    pub(crate) fn raise_height(&mut self, height: u32) {
+        self.min_height = Some(self.min_height.map_or(height, |min| min.max(height)));
+    }
+
+    pub(crate) fn lower_height(&mut self, height: u32) {
+        self.before_height = Some(self.before_height.map_or(height, |max| max.min(height)));
+    }
+
+    pub(crate) fn raise_seconds(&mut self, seconds: u64) {
+        self.min_seconds = Some(self.min_seconds.map_or(seconds, |min| min.max(seconds)));
+    }
+
+    pub(crate) fn lower_seconds(&mut self, seconds: u64) {
+        self.before_seconds = Some(self.before_seconds.map_or(seconds, |max| max.min(seconds)));
+    }
+}
+
+/// A summary of what a set of conditions does, for transaction previews and policy checks.
+///
+/// Conditions are added per coin, since announcement ids, created coins and signatures
+/// depend on the coin that outputs them. The summary can be built up across multiple spends.
+#[must_use]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ConditionSummary {
+    /// The coins created by `CREATE_COIN` conditions.
+    pub created_coins: Vec<CreatedCoin>,
+    /// The total amount reserved by `RESERVE_FEE` conditions.
+    pub reserved_fee: u64,
+    /// The ids of the coin announcements which are created.
+    pub created_coin_announcements: Vec<Bytes32>,
+    /// The ids of the puzzle announcements which are created.
+    pub created_puzzle_announcements: Vec<Bytes32>,
+    /// The ids of the coin announcements which are asserted.
+    pub asserted_coin_announcements: Vec<Bytes32>,
+    /// The ids of the puzzle announcements which are asserted.
+    pub asserted_puzzle_announcements: Vec<Bytes32>,
+    /// The absolute time locks, which apply to the whole transaction.
+    pub absolute: TimeWindow,
+    /// The relative time locks of each coin, relative to when the coin was created.
+    pub relative: Vec<(Bytes32, TimeWindow)>,
+    /// The `AGG_SIG_*` conditions, along with the coin that output them.
+    pub signatures: Vec<(Coin, AggSig)>,
+}
+
+impl ConditionSummary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the conditions output by a coin's puzzle to the summary.
+    pub fn with_conditions<'a, T>(
+        mut self,
+        coin: Coin,
+        conditions: impl IntoIterator<Item = &'a Condition<T>>,
+    ) -> Self
+    where
+        T: 'a + Clone,
+    {
+        self.add_conditions(coin, conditions);
+        self
+    }
+
+    /// Adds the conditions output by a coin's puzzle to the summary.
+    pub fn add_conditions<'a, T>(
+        &mut self,
+        coin: Coin,
+        conditions: impl IntoIterator<Item = &'a Condition<T>>,
+    ) where
+        T: 'a + Clone,
+    {
+        let coin_id = coin.coin_id();
+        let mut relative = TimeWindow::default();
+
+        for condition in conditions {
+            match condition {
+                Condition::CreateCoin(create_coin) => self.created_coins.push(CreatedCoin {
+                    coin: Coin::new(coin_id, create_coin.puzzle_hash, create_coin.amount),
+                    memos: create_coin.memos.clone(),
+                }),
+                Condition::ReserveFee(reserve_fee) => {
+                    self.reserved_fee = self.reserved_fee.saturating_add(reserve_fee.amount);
+                }
+                Condition::CreateCoinAnnouncement(announcement) => self
+                    .created_coin_announcements
+                    .push(announcement_id(coin_id, &announcement.message)),
+                Condition::CreatePuzzleAnnouncement(announcement) => self
+                    .created_puzzle_announcements
+                    .push(announcement_id(coin.puzzle_hash, &announcement.message)),
+                Condition::AssertCoinAnnouncement(assertion) => self
+                    .asserted_coin_announcements
+                    .push(assertion.announcement_id),
+                Condition::AssertPuzzleAnnouncement(assertion) => self
+                    .asserted_puzzle_announcements
+                    .push(assertion.announcement_id),
+                Condition::AssertHeightAbsolute(assertion) => {
+                    self.absolute.raise_height(assertion.height);
+                }
+                Condition::AssertBeforeHeightAbsolute(assertion) => {
+                    self.absolute.lower_height(assertion.height);
+                }
+                Condition::AssertSecondsAbsolute(assertion) => {
+                    self.absolute.raise_seconds(assertion.seconds);
+                }
+                Condition::AssertBeforeSecondsAbsolute(assertion) => {
+                    self.absolute.lower_seconds(assertion.seconds);
+                }
+                Condition::AssertHeightRelative(assertion) => {
+                    relative.raise_height(assertion.height);
+                }
+                Condition::AssertBeforeHeightRelative(assertion) => {
+                    relative.lower_height(assertion.height);
+                }
+                Condition::AssertSecondsRelative(assertion) => {
+                    relative.raise_seconds(assertion.seconds);
+                }
+                Condition::AssertBeforeSecondsRelative(assertion) => {
+                    relative.lower_seconds(assertion.seconds);
+                }
+                condition => {
+                    if let Some(agg_sig) = condition.clone().into_agg_sig() {
+                        self.signatures.push((coin, agg_sig));
+                    }
+                }
+            }
+        }
+
+        if !relative.is_unbounded() {
+            self.relative.push((coin_id, relative));
+        }
+    }
+
+    /// The total amount of the created coins.
+    pub fn created_amount(&self) -> u128 {
+        self.created_coins
+            .iter()
+            .map(|created| u128::from(created.coin.amount))
+            .sum()
+    }
+
+    /// The asserted coin and puzzle announcements which aren't created by any of the summarized conditions.
+    /// These must be created by other spends in the same transaction.
+    pub fn external_announcements(&self) -> Vec<Bytes32> {
+        self.asserted_coin_announcements
+            .iter()
+            .filter(|id| !self.created_coin_announcements.contains(id))
+            .chain(
+                self.asserted_puzzle_announcements
+                    .iter()
+                    .filter(|id| !self.created_puzzle_announcements.contains(id)),
+            )
+            .copied()
+            .collect()
+    }
+
+    /// Whether any of the time locks are contradictory, which means the conditions can never be valid.
+    pub fn has_impossible_time_locks(&self) -> bool {
+        self.absolute.is_empty() || self.relative.iter().any(|(_, window)| window.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_bls::SecretKey;
+    use clvmr::NodePtr;
+
+    use crate::{AggSigKind, Conditions};
+
+    use super::*;
+
+    #[test]
+    fn test_condition_summary() {
+        let public_key = SecretKey::from_seed(&[1; 32]).public_key();
+        let coin = Coin::new(Bytes32::new([1; 32]), Bytes32::new([2; 32]), 100);
+        let puzzle_hash = Bytes32::new([3; 32]);
+
+        let conditions: Conditions<NodePtr> = Conditions::new()
+            .create_coin(puzzle_hash, 60, vec![puzzle_hash.into()])
+            .create_coin(puzzle_hash, 30, Vec::new())
+            .reserve_fee(6)
+            .reserve_fee(4)
+            .create_coin_announcement(Bytes::new(vec![1]))
+            .create_puzzle_announcement(Bytes::new(vec![2]))
+            .assert_coin_announcement(announcement_id(coin.coin_id(), [1]))
+            .assert_puzzle_announcement(Bytes32::new([4; 32]))
+            .assert_height_absolute(10)
+            .assert_height_absolute(20)
+            .assert_before_height_absolute(30)
+            .assert_seconds_relative(60)
+            .agg_sig_me(public_key, Bytes::new(vec![5]));
+
+        let summary = ConditionSummary::new().with_conditions(coin, conditions.as_ref());
+
+        assert_eq!(summary.created_coins.len(), 2);
+        assert_eq!(
+            summary.created_coins[0].coin.parent_coin_info,
+            coin.coin_id()
+        );
+        assert_eq!(summary.created_coins[0].memos, vec![puzzle_hash.into()]);
+        assert_eq!(summary.created_amount(), 90);
+        assert_eq!(summary.reserved_fee, 10);
+
+        assert_eq!(
+            summary.created_puzzle_announcements,
+            vec![announcement_id(coin.puzzle_hash, [2])]
+        );
+        assert_eq!(
+            summary.external_announcements(),
+            vec![Bytes32::new([4; 32])]
+        );
+
+        assert_eq!(summary.absolute.min_height, Some(20));
+        assert_eq!(summary.absolute.before_height, Some(30));
+        assert!(summary.absolute.contains(25, 0));
+        assert!(!summary.absolute.contains(30, 0));
+        assert_eq!(summary.relative.len(), 1);
+        assert_eq!(summary.relative[0].1.min_seconds, Some(60));
+        assert!(!summary.has_impossible_time_locks());
+
+        assert_eq!(summary.signatures.len(), 1);
+        assert_eq!(summary.signatures[0].1.kind, AggSigKind::Me);
+
+        let contradictory: Conditions<NodePtr> = Conditions::new()
+            .assert_height_absolute(30)
+            .assert_before_height_absolute(30);
+        let summary = summary.with_conditions(coin, contradictory.as_ref());
+        assert!(summary.has_impossible_time_locks());
+    }
+}
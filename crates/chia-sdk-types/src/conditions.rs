@@ -5,6 +5,11 @@ use crate::Condition;
 
 #[must_use]
 #[derive(Debug, Clone, PartialEq, Eq, ToClvm, FromClvm)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(transparent, bound = "T: crate::ConditionArg")
+)]
 #[clvm(transparent)]
 pub struct Conditions<T = NodePtr> {
     conditions: Vec<Condition<T>>,
@@ -12,6 +12,14 @@ const TESTNET11_GENESIS_CHALLENGE: Bytes32 = Bytes32::new(hex!(
     "37a90eb5185a9c4439a91ddc98bbadce7b4feba060d50116a067de66bf236615"
 ));
 
+/// The mempool only accepts spend bundles that cost up to half of the maximum block cost.
+const MEMPOOL_COST_DIVISOR: u64 = 2;
+
+/// The maximum cost of a spend bundle that the mempool will accept.
+pub fn max_mempool_cost(constants: &ConsensusConstants) -> u64 {
+    constants.max_block_cost_clvm / MEMPOOL_COST_DIVISOR
+}
+
 pub static MAINNET_CONSTANTS: Lazy<ConsensusConstants> = Lazy::new(|| ConsensusConstants {
     slot_blocks_target: 32,
     min_blocks_per_challenge_block: 16,
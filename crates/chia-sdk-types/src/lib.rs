@@ -1,9 +1,15 @@
 mod condition;
+mod condition_summary;
 mod conditions;
 mod constants;
+mod lint;
 mod run_puzzle;
+mod secp;
 
 pub use condition::*;
+pub use condition_summary::*;
 pub use conditions::*;
 pub use constants::*;
+pub use lint::*;
 pub use run_puzzle::*;
+pub use secp::*;
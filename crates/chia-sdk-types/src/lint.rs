@@ -0,0 +1,409 @@
+use chia_protocol::Bytes32;
+
+use crate::{Condition, Conditions, TimeWindow};
+
+/// The maximum number of announcement, concurrent spend and message conditions per spend.
+pub const MAX_ANNOUNCEMENTS: usize = 1024;
+
+/// The maximum size of an announcement or signature message in bytes.
+pub const MAX_MESSAGE_SIZE: usize = 1024;
+
+/// The maximum size of the first memo of a `CREATE_COIN` condition for it to be used as a hint.
+pub const MAX_HINT_SIZE: usize = 32;
+
+/// How serious a [`Lint`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LintSeverity {
+    /// The conditions are valid, but probably not what was intended.
+    Warning,
+    /// The spend will be rejected by consensus or the mempool.
+    Error,
+}
+
+/// The type of time lock that a [`LintKind::ContradictoryTimeLocks`] refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimeLockKind {
+    AbsoluteHeight,
+    AbsoluteSeconds,
+    RelativeHeight,
+    RelativeSeconds,
+}
+
+/// A problem found in a set of conditions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintKind {
+    /// The lower bound of a time lock is at or above its upper bound, so the spend can never be valid.
+    ContradictoryTimeLocks {
+        kind: TimeLockKind,
+        min: u64,
+        before: u64,
+    },
+    /// A coin with the same puzzle hash and amount is created more than once.
+    DuplicateOutput { puzzle_hash: Bytes32, amount: u64 },
+    /// More fee is reserved than the amount left over after the created coins.
+    ReserveFeeExceedsSurplus { reserved: u128, surplus: u128 },
+    /// More coins are created than the amount being spent.
+    CreatedAmountExceedsSpent { created: u128, spent: u64 },
+    /// There are more announcement, concurrent spend and message conditions than allowed.
+    TooManyAnnouncements { count: usize },
+    /// An announcement or signature message is larger than allowed.
+    MessageTooLarge { size: usize },
+    /// The first memo is too large to be used as a hint, so the coin won't be discoverable by it.
+    HintTooLarge { size: usize },
+    /// The condition is identical to an earlier one, so it has no effect.
+    RedundantCondition { duplicate_of: usize },
+}
+
+impl LintKind {
+    pub fn severity(&self) -> LintSeverity {
+        match self {
+            Self::HintTooLarge { .. } | Self::RedundantCondition { .. } => LintSeverity::Warning,
+            _ => LintSeverity::Error,
+        }
+    }
+}
+
+/// A problem found in a set of conditions, along with the condition responsible, if any.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lint {
+    pub condition_index: Option<usize>,
+    pub kind: LintKind,
+}
+
+impl Lint {
+    pub fn severity(&self) -> LintSeverity {
+        self.kind.severity()
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity() == LintSeverity::Error
+    }
+}
+
+impl<T> Conditions<T>
+where
+    T: PartialEq,
+{
+    /// Checks the conditions for problems that would make the spend invalid, or that are likely mistakes.
+    ///
+    /// If the amount of the coin being spent is known, the created coins and reserved fee are checked against it.
+    pub fn lint(&self, spent_amount: Option<u64>) -> Vec<Lint> {
+        lint_conditions(self.as_ref(), spent_amount)
+    }
+}
+
+fn lint_conditions<T>(conditions: &[Condition<T>], spent_amount: Option<u64>) -> Vec<Lint>
+where
+    T: PartialEq,
+{
+    let mut lints = Vec::new();
+
+    let mut absolute = TimeWindow::default();
+    let mut relative = TimeWindow::default();
+    let mut outputs = Vec::new();
+    let mut created: u128 = 0;
+    let mut reserved: u128 = 0;
+    let mut announcements = 0;
+
+    for (index, condition) in conditions.iter().enumerate() {
+        let mut push = |kind| {
+            lints.push(Lint {
+                condition_index: Some(index),
+                kind,
+            });
+        };
+
+        if is_assertion(condition) {
+            if let Some(duplicate_of) = conditions[..index].iter().position(|c| c == condition) {
+                push(LintKind::RedundantCondition { duplicate_of });
+            }
+        }
+
+        if let Some(size) = message_size(condition) {
+            if size > MAX_MESSAGE_SIZE {
+                push(LintKind::MessageTooLarge { size });
+            }
+        }
+
+        if counts_as_announcement(condition) {
+            announcements += 1;
+            if announcements == MAX_ANNOUNCEMENTS + 1 {
+                push(LintKind::TooManyAnnouncements {
+                    count: conditions
+                        .iter()
+                        .filter(|c| counts_as_announcement(c))
+                        .count(),
+                });
+            }
+        }
+
+        match condition {
+            Condition::CreateCoin(create_coin) => {
+                created += u128::from(create_coin.amount);
+
+                let output = (create_coin.puzzle_hash, create_coin.amount);
+                if outputs.contains(&output) {
+                    push(LintKind::DuplicateOutput {
+                        puzzle_hash: create_coin.puzzle_hash,
+                        amount: create_coin.amount,
+                    });
+                } else {
+                    outputs.push(output);
+                }
+
+                if let Some(hint) = create_coin.memos.first() {
+                    if hint.len() > MAX_HINT_SIZE {
+                        push(LintKind::HintTooLarge { size: hint.len() });
+                    }
+                }
+            }
+            Condition::ReserveFee(reserve_fee) => reserved += u128::from(reserve_fee.amount),
+            Condition::AssertHeightAbsolute(assertion) => absolute.raise_height(assertion.height),
+            Condition::AssertBeforeHeightAbsolute(assertion) => {
+                absolute.lower_height(assertion.height);
+            }
+            Condition::AssertSecondsAbsolute(assertion) => {
+                absolute.raise_seconds(assertion.seconds);
+            }
+            Condition::AssertBeforeSecondsAbsolute(assertion) => {
+                absolute.lower_seconds(assertion.seconds);
+            }
+            Condition::AssertHeightRelative(assertion) => relative.raise_height(assertion.height),
+            Condition::AssertBeforeHeightRelative(assertion) => {
+                relative.lower_height(assertion.height);
+            }
+            Condition::AssertSecondsRelative(assertion) => {
+                relative.raise_seconds(assertion.seconds);
+            }
+            Condition::AssertBeforeSecondsRelative(assertion) => {
+                relative.lower_seconds(assertion.seconds);
+            }
+            _ => {}
+        }
+    }
+
+    let time_locks = [
+        (
+            TimeLockKind::AbsoluteHeight,
+            absolute.min_height.map(u64::from),
+            absolute.before_height.map(u64::from),
+        ),
+        (
+            TimeLockKind::AbsoluteSeconds,
+            absolute.min_seconds,
+            absolute.before_seconds,
+        ),
+        (
+            TimeLockKind::RelativeHeight,
+            relative.min_height.map(u64::from),
+            relative.before_height.map(u64::from),
+        ),
+        (
+            TimeLockKind::RelativeSeconds,
+            relative.min_seconds,
+            relative.before_seconds,
+        ),
+    ];
+
+    for (kind, min, before) in time_locks {
+        if let (Some(min), Some(before)) = (min, before) {
+            if min >= before {
+                lints.push(Lint {
+                    condition_index: None,
+                    kind: LintKind::ContradictoryTimeLocks { kind, min, before },
+                });
+            }
+        }
+    }
+
+    if let Some(spent) = spent_amount {
+        if created > u128::from(spent) {
+            lints.push(Lint {
+                condition_index: None,
+                kind: LintKind::CreatedAmountExceedsSpent { created, spent },
+            });
+        }
+
+        let surplus = u128::from(spent).saturating_sub(created);
+
+        if reserved > surplus {
+            lints.push(Lint {
+                condition_index: None,
+                kind: LintKind::ReserveFeeExceedsSurplus { reserved, surplus },
+            });
+        }
+    }
+
+    lints
+}
+
+fn counts_as_announcement<T>(condition: &Condition<T>) -> bool {
+    matches!(
+        condition,
+        Condition::CreateCoinAnnouncement(..)
+            | Condition::CreatePuzzleAnnouncement(..)
+            | Condition::AssertCoinAnnouncement(..)
+            | Condition::AssertPuzzleAnnouncement(..)
+            | Condition::AssertConcurrentSpend(..)
+            | Condition::AssertConcurrentPuzzle(..)
+            | Condition::SendMessage(..)
+            | Condition::ReceiveMessage(..)
+    )
+}
+
+fn is_assertion<T>(condition: &Condition<T>) -> bool {
+    matches!(
+        condition,
+        Condition::AssertCoinAnnouncement(..)
+            | Condition::AssertPuzzleAnnouncement(..)
+            | Condition::AssertConcurrentSpend(..)
+            | Condition::AssertConcurrentPuzzle(..)
+            | Condition::AssertMyCoinId(..)
+            | Condition::AssertMyParentId(..)
+            | Condition::AssertMyPuzzleHash(..)
+            | Condition::AssertMyAmount(..)
+            | Condition::AssertMyBirthSeconds(..)
+            | Condition::AssertMyBirthHeight(..)
+            | Condition::AssertEphemeral(..)
+            | Condition::AssertSecondsRelative(..)
+            | Condition::AssertSecondsAbsolute(..)
+            | Condition::AssertHeightRelative(..)
+            | Condition::AssertHeightAbsolute(..)
+            | Condition::AssertBeforeSecondsRelative(..)
+            | Condition::AssertBeforeSecondsAbsolute(..)
+            | Condition::AssertBeforeHeightRelative(..)
+            | Condition::AssertBeforeHeightAbsolute(..)
+    )
+}
+
+fn message_size<T>(condition: &Condition<T>) -> Option<usize> {
+    let size = match condition {
+        Condition::CreateCoinAnnouncement(announcement) => announcement.message.len(),
+        Condition::CreatePuzzleAnnouncement(announcement) => announcement.message.len(),
+        Condition::AggSigParent(agg_sig) => agg_sig.message.len(),
+        Condition::AggSigPuzzle(agg_sig) => agg_sig.message.len(),
+        Condition::AggSigAmount(agg_sig) => agg_sig.message.len(),
+        Condition::AggSigPuzzleAmount(agg_sig) => agg_sig.message.len(),
+        Condition::AggSigParentAmount(agg_sig) => agg_sig.message.len(),
+        Condition::AggSigParentPuzzle(agg_sig) => agg_sig.message.len(),
+        Condition::AggSigUnsafe(agg_sig) => agg_sig.message.len(),
+        Condition::AggSigMe(agg_sig) => agg_sig.message.len(),
+        Condition::SendMessage(send) => send.message.len(),
+        Condition::ReceiveMessage(receive) => receive.message.len(),
+        _ => return None,
+    };
+    Some(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_protocol::Bytes;
+
+    use super::*;
+
+    fn kinds(lints: &[Lint]) -> Vec<(Option<usize>, LintKind)> {
+        lints
+            .iter()
+            .map(|lint| (lint.condition_index, lint.kind))
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_conditions() {
+        let puzzle_hash = Bytes32::new([1; 32]);
+
+        let conditions = Conditions::new()
+            .create_coin(puzzle_hash, 50, vec![puzzle_hash.into()])
+            .create_coin(puzzle_hash, 40, Vec::new())
+            .reserve_fee(10)
+            .assert_height_absolute(10)
+            .assert_before_height_absolute(20);
+
+        assert_eq!(conditions.lint(Some(100)), Vec::new());
+    }
+
+    #[test]
+    fn test_lints() {
+        let puzzle_hash = Bytes32::new([1; 32]);
+
+        let conditions = Conditions::new()
+            .create_coin(puzzle_hash, 50, vec![Bytes::new(vec![0; 33])])
+            .create_coin(puzzle_hash, 50, Vec::new())
+            .reserve_fee(10)
+            .assert_height_absolute(20)
+            .assert_before_height_absolute(20)
+            .assert_concurrent_spend(puzzle_hash)
+            .assert_concurrent_spend(puzzle_hash)
+            .create_coin_announcement(Bytes::new(vec![0; 1025]));
+
+        let lints = conditions.lint(Some(100));
+
+        assert_eq!(
+            kinds(&lints),
+            vec![
+                (Some(0), LintKind::HintTooLarge { size: 33 }),
+                (
+                    Some(1),
+                    LintKind::DuplicateOutput {
+                        puzzle_hash,
+                        amount: 50
+                    }
+                ),
+                (Some(6), LintKind::RedundantCondition { duplicate_of: 5 }),
+                (Some(7), LintKind::MessageTooLarge { size: 1025 }),
+                (
+                    None,
+                    LintKind::ContradictoryTimeLocks {
+                        kind: TimeLockKind::AbsoluteHeight,
+                        min: 20,
+                        before: 20
+                    }
+                ),
+                (
+                    None,
+                    LintKind::ReserveFeeExceedsSurplus {
+                        reserved: 10,
+                        surplus: 0
+                    }
+                ),
+            ]
+        );
+
+        assert_eq!(lints[0].severity(), LintSeverity::Warning);
+        assert!(lints[1].is_error());
+    }
+
+    #[test]
+    fn test_too_many_announcements() {
+        let conditions = Conditions::new()
+            .extend((0..=MAX_ANNOUNCEMENTS).map(|i| {
+                Condition::create_coin_announcement(Bytes::new(i.to_be_bytes().to_vec()))
+            }));
+
+        assert_eq!(
+            kinds(&conditions.lint(None)),
+            vec![(
+                Some(MAX_ANNOUNCEMENTS),
+                LintKind::TooManyAnnouncements {
+                    count: MAX_ANNOUNCEMENTS + 1
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn test_message_too_large() {
+        let conditions: Conditions = Conditions::new()
+            .send_message(0b11_1111, Bytes::new(vec![0; 1025]), Vec::new())
+            .receive_message(0b11_1111, Bytes::new(vec![0; 1025]), Vec::new())
+            .send_message(0b11_1111, Bytes::new(vec![0; 1024]), Vec::new());
+
+        assert_eq!(
+            kinds(&conditions.lint(None)),
+            vec![
+                (Some(0), LintKind::MessageTooLarge { size: 1025 }),
+                (Some(1), LintKind::MessageTooLarge { size: 1025 }),
+            ]
+        );
+    }
+}
@@ -0,0 +1,78 @@
+use chia_protocol::BytesImpl;
+
+/// A compressed SEC1 encoded secp256k1 or secp256r1 public key.
+pub type SecpPublicKeyBytes = BytesImpl<33>;
+
+/// A fixed size secp256k1 or secp256r1 signature, as accepted by the CLVM verify operators.
+pub type SecpSignature = BytesImpl<64>;
+
+/// The elliptic curve used by a secp signature.
+/// Unlike BLS signatures, these are verified by the puzzle itself rather than by a condition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SecpCurve {
+    /// The secp256k1 curve, used by most hardware wallets.
+    K1,
+    /// The secp256r1 curve (also known as P-256), used by passkeys.
+    R1,
+}
+
+impl SecpCurve {
+    /// The opcode of the `secp256k1_verify` CLVM operator.
+    pub const K1_VERIFY_OPCODE: u32 = 0x13d6_1f00;
+
+    /// The opcode of the `secp256r1_verify` CLVM operator.
+    pub const R1_VERIFY_OPCODE: u32 = 0x1c3a_8f00;
+
+    /// The cost of the `secp256k1_verify` CLVM operator.
+    pub const K1_VERIFY_COST: u64 = 1_300_000;
+
+    /// The cost of the `secp256r1_verify` CLVM operator.
+    pub const R1_VERIFY_COST: u64 = 1_850_000;
+
+    /// Returns the curve that is verified by the given CLVM operator, if any.
+    pub fn from_verify_opcode(opcode: u32) -> Option<Self> {
+        match opcode {
+            Self::K1_VERIFY_OPCODE => Some(Self::K1),
+            Self::R1_VERIFY_OPCODE => Some(Self::R1),
+            _ => None,
+        }
+    }
+
+    /// The CLVM operator used to verify signatures on this curve.
+    pub fn verify_opcode(self) -> u32 {
+        match self {
+            Self::K1 => Self::K1_VERIFY_OPCODE,
+            Self::R1 => Self::R1_VERIFY_OPCODE,
+        }
+    }
+
+    /// The cost of verifying a signature on this curve in CLVM.
+    pub fn verify_cost(self) -> u64 {
+        match self {
+            Self::K1 => Self::K1_VERIFY_COST,
+            Self::R1 => Self::R1_VERIFY_COST,
+        }
+    }
+}
+
+/// A secp256k1 or secp256r1 public key, along with the curve it belongs to.
+/// The curve isn't part of the serialized key, so it's tracked separately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SecpPublicKey {
+    pub curve: SecpCurve,
+    pub bytes: SecpPublicKeyBytes,
+}
+
+impl SecpPublicKey {
+    pub fn new(curve: SecpCurve, bytes: SecpPublicKeyBytes) -> Self {
+        Self { curve, bytes }
+    }
+
+    pub fn k1(bytes: SecpPublicKeyBytes) -> Self {
+        Self::new(SecpCurve::K1, bytes)
+    }
+
+    pub fn r1(bytes: SecpPublicKeyBytes) -> Self {
+        Self::new(SecpCurve::R1, bytes)
+    }
+}
@@ -1,10 +1,8 @@
 #![doc = include_str!("../README.md")]
 
 mod address;
-mod coin_selection;
 
 pub use address::*;
-pub use coin_selection::*;
 
 pub use chia_sdk_client::*;
 pub use chia_sdk_driver::*;